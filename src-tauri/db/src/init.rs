@@ -43,10 +43,29 @@ pub async fn init_db(
 
 pub async fn define_db_on_startup(db_connection: DbConnection) -> Result<(), String> {
     let define_statements: Vec<&str> = vec![
+        "DEFINE ANALYZER clinical_en TOKENIZERS blank, class, punct FILTERS lowercase, ascii, snowball(english);",
+        "DEFINE ANALYZER clinical_de TOKENIZERS blank, class, punct FILTERS lowercase, snowball(german), ascii;",
+
         "DEFINE TABLE Organization SCHEMAFULL;",
         "DEFINE FIELD name ON Organization TYPE string;",
         "DEFINE FIELD address ON Organization TYPE string;",
         "DEFINE FIELD email ON Organization TYPE string ASSERT string::is::email($value);",
+        "DEFINE FIELD letterhead ON Organization TYPE option<object>;",
+        "DEFINE FIELD letterhead.title ON Organization TYPE string;",
+        "DEFINE FIELD letterhead.lines ON Organization TYPE array<string>;",
+        "DEFINE FIELD letterhead.footer ON Organization TYPE option<string>;",
+        "DEFINE FIELD hl7 ON Organization TYPE option<object>;",
+        "DEFINE FIELD hl7.host ON Organization TYPE string;",
+        "DEFINE FIELD hl7.port ON Organization TYPE int ASSERT $value > 0 AND $value < 65536;",
+        "DEFINE FIELD hl7.sending_application ON Organization TYPE string;",
+        "DEFINE FIELD hl7.sending_facility ON Organization TYPE string;",
+        "DEFINE FIELD hl7.receiving_application ON Organization TYPE string;",
+        "DEFINE FIELD hl7.receiving_facility ON Organization TYPE string;",
+        "DEFINE FIELD hl7.timeout_secs ON Organization TYPE int DEFAULT 10 ASSERT $value > 0;",
+        "DEFINE FIELD dicomweb ON Organization TYPE option<object>;",
+        "DEFINE FIELD dicomweb.base_url ON Organization TYPE string ASSERT string::is::url($value);",
+        "DEFINE FIELD dicomweb.timeout_secs ON Organization TYPE int DEFAULT 30;",
+        "UPDATE Organization UNSET dicomweb.auth_token WHERE dicomweb.auth_token IS NOT NONE;",
         "DEFINE FIELD created_at ON Organization TYPE datetime DEFAULT time::now();",
         "DEFINE FIELD updated_at ON Organization TYPE datetime DEFAULT time::now() VALUE time::now();",
         "DEFINE FIELD user ON TABLE Organization TYPE option<array<record<User>>>;",
@@ -72,6 +91,7 @@ pub async fn define_db_on_startup(db_connection: DbConnection) -> Result<(), Str
 
         "DEFINE TABLE Patient SCHEMAFULL;",
         "DEFINE FIELD name ON Patient TYPE string;",
+        "DEFINE FIELD name_key ON Patient TYPE string VALUE string::lowercase(name);",
         "DEFINE FIELD date_of_birth ON Patient TYPE datetime;",
         "DEFINE FIELD gender ON Patient TYPE string;",
         "DEFINE FIELD contact_number ON Patient TYPE string;",
@@ -84,6 +104,9 @@ pub async fn define_db_on_startup(db_connection: DbConnection) -> Result<(), Str
         "DEFINE FIELD report.* ON Patient TYPE option<record<Report>>;",
         "DEFINE FIELD image ON TABLE Patient TYPE option<array<record<Image>>>;",
         "DEFINE FIELD image.* ON Patient TYPE option<record<Image>>;",
+        "DEFINE FIELD deleted_at ON Patient TYPE option<datetime>;",
+        "DEFINE FIELD deleted_by ON Patient TYPE option<record<User>>;",
+        "DEFINE FIELD merged_into ON Patient TYPE option<record<Patient>>;",
         "DEFINE FIELD out ON TABLE Treated_By TYPE record<User>;",
         "DEFINE FIELD in ON TABLE Treated_By TYPE record<Patient>;",
 
@@ -91,12 +114,18 @@ pub async fn define_db_on_startup(db_connection: DbConnection) -> Result<(), Str
         "DEFINE FIELD symptoms ON PatientNote TYPE string;",
         "DEFINE FIELD diagnosis ON PatientNote TYPE string;",
         "DEFINE FIELD treatment ON PatientNote TYPE string;",
-        "DEFINE FIELD created_at ON PatientNote TYPE datetime DEFAULT time::now() VALUE time::now();",
+        "DEFINE FIELD created_at ON PatientNote TYPE datetime DEFAULT time::now() READONLY;",
         "DEFINE FIELD updated_at ON PatientNote TYPE datetime DEFAULT time::now() VALUE time::now();",
         "DEFINE FIELD severity ON PatientNote TYPE string ASSERT $value IN ['low', 'medium', 'high'];",
         "DEFINE FIELD is_urgent ON PatientNote TYPE bool;",
         "DEFINE FIELD patient ON PatientNote TYPE record<Patient>;",
         "DEFINE FIELD user_owner ON PatientNote TYPE record<User>;",
+        "DEFINE FIELD deleted_at ON PatientNote TYPE option<datetime>;",
+        "DEFINE FIELD deleted_by ON PatientNote TYPE option<record<User>>;",
+        "DEFINE FIELD deleted_with ON PatientNote TYPE option<record<Patient>>;",
+        "DEFINE FIELD symptoms_de ON PatientNote TYPE string VALUE symptoms;",
+        "DEFINE FIELD diagnosis_de ON PatientNote TYPE string VALUE diagnosis;",
+        "DEFINE FIELD treatment_de ON PatientNote TYPE string VALUE treatment;",
         "DEFINE FIELD out ON TABLE PatientNotes_Reports_Join TYPE record<User>;",
         "DEFINE FIELD in ON TABLE PatientNotes_Reports_Join TYPE record<PatientNote>;",
 
@@ -113,12 +142,27 @@ pub async fn define_db_on_startup(db_connection: DbConnection) -> Result<(), Str
         "DEFINE FIELD body_part ON Report TYPE option<string>;",
         "DEFINE FIELD condition ON Report TYPE option<string>;",
         "DEFINE FIELD report_text ON Report TYPE string;",
+        "DEFINE FIELD sections ON Report TYPE option<object>;",
+        "DEFINE FIELD sections.indication ON Report TYPE string;",
+        "DEFINE FIELD sections.technique ON Report TYPE string;",
+        "DEFINE FIELD sections.findings ON Report TYPE string;",
+        "DEFINE FIELD sections.assessment ON Report TYPE string;",
         "DEFINE FIELD created_at ON Report TYPE datetime DEFAULT time::now();",
         "DEFINE FIELD updated_at ON Report TYPE datetime DEFAULT time::now() VALUE time::now();",
         "DEFINE FIELD in ON TABLE Statements_Reports_Join TYPE record<Statement>;",
         "DEFINE FIELD out ON TABLE Statements_Reports_Join TYPE record<Report>;",
         "DEFINE FIELD patient ON Report TYPE record<Patient>;",
         "DEFINE FIELD user_owner ON Report TYPE record<User>;",
+        "DEFINE FIELD deleted_at ON Report TYPE option<datetime>;",
+        "DEFINE FIELD deleted_by ON Report TYPE option<record<User>>;",
+        "DEFINE FIELD deleted_with ON Report TYPE option<record<Patient>>;",
+        "DEFINE FIELD status ON Report TYPE string DEFAULT 'draft' ASSERT $value IN ['draft', 'preliminary', 'final', 'amended'];",
+        "DEFINE FIELD version ON Report TYPE int DEFAULT 1;",
+        "DEFINE FIELD signed_by ON Report TYPE option<record<User>>;",
+        "DEFINE FIELD signed_at ON Report TYPE option<datetime>;",
+        "DEFINE FIELD amends ON Report TYPE option<record<Report>>;",
+        "DEFINE FIELD superseded_by ON Report TYPE option<record<Report>>;",
+        "DEFINE FIELD report_text_de ON Report TYPE string VALUE report_text;",
 
         "DEFINE TABLE Image SCHEMAFULL;",
         "DEFINE FIELD name ON Image TYPE string;",
@@ -130,15 +174,60 @@ pub async fn define_db_on_startup(db_connection: DbConnection) -> Result<(), Str
         "DEFINE FIELD updated_at ON Image TYPE datetime DEFAULT time::now() VALUE time::now();",
         "DEFINE FIELD patient ON Image TYPE record<Patient>;",
         "DEFINE FIELD user ON Image TYPE record<User>;",
+        "DEFINE FIELD dicom ON Image TYPE option<object>;",
+        "DEFINE FIELD dicom.study_uid ON Image TYPE string;",
+        "DEFINE FIELD dicom.series_uid ON Image TYPE string;",
+        "DEFINE FIELD dicom.sop_instance_uid ON Image TYPE string;",
+        "DEFINE FIELD dicom.sop_class_uid ON Image TYPE string;",
+        "DEFINE FIELD dicom.patient_id ON Image TYPE option<string>;",
+        "DEFINE FIELD dicom.patient_name ON Image TYPE option<string>;",
+        "DEFINE FIELD classification ON Image TYPE option<object>;",
+        "DEFINE FIELD classification.image_type ON Image TYPE string;",
+        "DEFINE FIELD classification.confidence ON Image TYPE float;",
+        "DEFINE FIELD analyzed_at ON Image TYPE option<datetime>;",
+        "DEFINE TABLE AnalysisRun SCHEMAFULL;",
+        "DEFINE FIELD patient ON AnalysisRun TYPE record<Patient>;",
+        "DEFINE FIELD user ON AnalysisRun TYPE record<User>;",
+        "DEFINE FIELD results ON AnalysisRun TYPE array<object>;",
+        "DEFINE FIELD results.*.image ON AnalysisRun TYPE record<Image>;",
+        "DEFINE FIELD results.*.image_type ON AnalysisRun TYPE string;",
+        "DEFINE FIELD results.*.confidence ON AnalysisRun TYPE float;",
+        "DEFINE FIELD analyzed_at ON AnalysisRun TYPE datetime;",
+        "DEFINE INDEX AnalysisRun_patient ON TABLE AnalysisRun COLUMNS patient;",
         "DEFINE FIELD in ON TABLE Images_Reports_Join TYPE record<Image>;",
         "DEFINE FIELD out ON TABLE Images_Reports_Join TYPE record<Report>;",
         "DEFINE INDEX Treated_By ON TABLE Treated_By COLUMNS in, out UNIQUE;",
+        "DEFINE INDEX Treated_By_out ON TABLE Treated_By COLUMNS out;",
         "DEFINE INDEX Access_Statements ON TABLE Access_Statements COLUMNS in, out UNIQUE;",
         "DEFINE INDEX PatientNotes_Reports_Join ON TABLE PatientNotes_Reports_Join COLUMNS in, out UNIQUE;",
         "DEFINE INDEX Statements_Reports_Join ON TABLE Statements_Reports_Join COLUMNS in, out UNIQUE;",
         "DEFINE INDEX Images_Reports_Join ON TABLE Images_Reports_Join COLUMNS in, out UNIQUE;",
         "DEFINE INDEX Write_Reports ON TABLE Write_Reports COLUMNS in, out UNIQUE;",
         "DEFINE INDEX Email ON TABLE User COLUMNS email UNIQUE;",
+        "DEFINE INDEX Patient_name ON TABLE Patient COLUMNS name;",
+        "DEFINE INDEX Patient_name_key ON TABLE Patient COLUMNS name_key;",
+        "UPDATE Patient SET name_key = string::lowercase(name) WHERE name_key IS NONE;",
+        "DEFINE INDEX Patient_date_of_birth ON TABLE Patient COLUMNS date_of_birth;",
+        "DEFINE INDEX Patient_gender ON TABLE Patient COLUMNS gender;",
+        "DEFINE INDEX Patient_created_at ON TABLE Patient COLUMNS created_at;",
+        "DEFINE INDEX PatientNote_patient ON TABLE PatientNote COLUMNS patient;",
+        "DEFINE INDEX PatientNote_symptoms_en ON TABLE PatientNote FIELDS symptoms SEARCH ANALYZER clinical_en BM25;",
+        "DEFINE INDEX PatientNote_diagnosis_en ON TABLE PatientNote FIELDS diagnosis SEARCH ANALYZER clinical_en BM25;",
+        "DEFINE INDEX PatientNote_treatment_en ON TABLE PatientNote FIELDS treatment SEARCH ANALYZER clinical_en BM25;",
+        "DEFINE INDEX PatientNote_symptoms_de ON TABLE PatientNote FIELDS symptoms_de SEARCH ANALYZER clinical_de BM25;",
+        "DEFINE INDEX PatientNote_diagnosis_de ON TABLE PatientNote FIELDS diagnosis_de SEARCH ANALYZER clinical_de BM25;",
+        "DEFINE INDEX PatientNote_treatment_de ON TABLE PatientNote FIELDS treatment_de SEARCH ANALYZER clinical_de BM25;",
+        "DEFINE INDEX Report_text_en ON TABLE Report FIELDS report_text SEARCH ANALYZER clinical_en BM25;",
+        "DEFINE INDEX Report_text_de ON TABLE Report FIELDS report_text_de SEARCH ANALYZER clinical_de BM25;",
+
+        "DEFINE TABLE PatientIdentifier SCHEMAFULL;",
+        "DEFINE FIELD patient ON PatientIdentifier TYPE record<Patient>;",
+        "DEFINE FIELD kind ON PatientIdentifier TYPE string ASSERT $value IN ['mrn', 'dicom_patient_id', 'insurance', 'other'];",
+        "DEFINE FIELD system ON PatientIdentifier TYPE string;",
+        "DEFINE FIELD value ON PatientIdentifier TYPE string;",
+        "DEFINE FIELD created_at ON PatientIdentifier TYPE datetime DEFAULT time::now();",
+        "DEFINE INDEX PatientIdentifier_system_value ON TABLE PatientIdentifier COLUMNS system, value UNIQUE;",
+        "DEFINE INDEX PatientIdentifier_patient ON TABLE PatientIdentifier COLUMNS patient;",
 
         "DEFINE TABLE Treated_By SCHEMAFULL;",
         "DEFINE TABLE Access_Statements SCHEMAFULL;",
@@ -147,6 +236,27 @@ pub async fn define_db_on_startup(db_connection: DbConnection) -> Result<(), Str
         "DEFINE TABLE Images_Reports_Join SCHEMAFULL;",
         "DEFINE TABLE Write_Reports SCHEMAFULL;",
 
+        "DEFINE TABLE ReportRevision SCHEMAFULL PERMISSIONS FOR select, create FULL, FOR update, delete NONE;",
+        "DEFINE FIELD report ON ReportRevision TYPE record<Report> READONLY;",
+        "DEFINE FIELD version ON ReportRevision TYPE int READONLY;",
+        "DEFINE FIELD status ON ReportRevision TYPE string READONLY;",
+        "DEFINE FIELD report_text ON ReportRevision TYPE string READONLY;",
+        "DEFINE FIELD body_part ON ReportRevision TYPE option<string> READONLY;",
+        "DEFINE FIELD author ON ReportRevision TYPE record<User> READONLY;",
+        "DEFINE FIELD created_at ON ReportRevision TYPE datetime DEFAULT time::now() READONLY;",
+        "DEFINE INDEX ReportRevision_report ON TABLE ReportRevision COLUMNS report, created_at;",
+
+        "DEFINE TABLE AuditLog SCHEMAFULL PERMISSIONS FOR select, create FULL, FOR update, delete NONE;",
+        "DEFINE FIELD actor ON AuditLog TYPE option<record<User>> READONLY;",
+        "DEFINE FIELD action ON AuditLog TYPE string READONLY ASSERT $value IN ['read', 'create', 'update', 'delete', 'share', 'restore', 'purge', 'merge'];",
+        "DEFINE FIELD target ON AuditLog TYPE record READONLY;",
+        "DEFINE FIELD patient ON AuditLog TYPE option<record<Patient>> READONLY;",
+        "DEFINE FIELD changes ON AuditLog FLEXIBLE TYPE option<array<object>> READONLY;",
+        "DEFINE FIELD created_at ON AuditLog TYPE datetime DEFAULT time::now() READONLY;",
+        "DEFINE INDEX AuditLog_actor ON TABLE AuditLog COLUMNS actor, created_at;",
+        "DEFINE INDEX AuditLog_patient ON TABLE AuditLog COLUMNS patient, created_at;",
+        "DEFINE EVENT AuditLog_immutable ON TABLE AuditLog WHEN $event != 'CREATE' THEN { THROW 'Audit entries cannot be changed or deleted' };",
+
         "DEFINE TABLE Models SCHEMAFULL;",
         "DEFINE FIELD name ON TABLE Models TYPE string;",
         "DEFINE FIELD version ON TABLE Models TYPE string;",
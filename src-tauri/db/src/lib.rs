@@ -9,7 +9,9 @@ pub use surrealdb::{
     Surreal,
     engine::any::Any,
     engine::local::Mem,
-    sql::{Thing, Datetime}, 
-    Error
+    sql::{Thing, Datetime, Id}, 
+    error::Db as DbError,
+    Error,
+    Response
     
 };
\ No newline at end of file
@@ -0,0 +1,88 @@
+use super::models;
+use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+
+/// Retrieves audit log entries, newest first.
+///
+/// Admins can query the actions of any user; other users only see their own.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `filter` - JSON string with optional `user_id`, `patient_id`, `from` and `to`
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<AuditEntryResponse>)` - Matching audit entries
+/// * `Err(AppError)` - Error message if retrieval fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The filter JSON is invalid
+/// * Database operations fail
+
+#[tauri::command]
+pub async fn get_audit_log(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    filter: String,
+) -> Result<Vec<models::AuditEntryResponse>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let filter: models::AuditFilter = serde_json::from_str(&filter)
+            .map_err(|e| AppError::invalid_input("audit filter", e))?;
+
+        services::get_audit_log_service(&db, filter, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Exports audit log entries as CSV for compliance reviews.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `filter` - JSON string with optional `user_id`, `patient_id`, `from` and `to`
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(String)` - CSV document of the matching entries
+/// * `Err(AppError)` - Error message if the export fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The filter JSON is invalid
+/// * Database operations fail
+
+#[tauri::command]
+pub async fn export_audit_log(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    filter: String,
+) -> Result<String, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let filter: models::AuditFilter = serde_json::from_str(&filter)
+            .map_err(|e| AppError::invalid_input("audit filter", e))?;
+
+        services::export_audit_log_service(&db, filter, &user)
+            .await
+    })
+    .await
+}
@@ -0,0 +1,22 @@
+//! # Audit Module
+//! 
+//! This module records who read or modified patient data, including:
+//! - Append-only audit entries for patients, notes, reports and images
+//! - Field-level before/after changes for updates
+//! - Audit log queries and CSV export
+//! 
+//! ## Components
+//! 
+//! - [`controller`]: Tauri command handlers for audit queries
+//! - [`services`]: Audit hooks used by the other services
+//! - [`models`]: Audit-related data structures
+//! 
+//! ## Main Features
+//! 
+//! - Cross-cutting audit hook for the services layer
+//! - Filtering by user, patient and date range
+//! - Compliance export
+
+pub mod controller;
+pub mod models;
+pub mod services;
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use scanlytics_db::{Thing, Datetime};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Read,
+    Create,
+    Update,
+    Delete,
+    Share,
+    Restore,
+    Purge,
+    Merge,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditRecord {
+    /// User who acted, or `None` for background jobs
+    pub actor: Option<Thing>,
+    pub action: AuditAction,
+    pub target: Thing,
+    pub patient: Option<Thing>,
+    pub changes: Option<Vec<FieldChange>>,
+    pub created_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub user_id: Option<String>,
+    pub patient_id: Option<String>,
+    pub from: Option<Datetime>,
+    pub to: Option<Datetime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntryResponse {
+    pub id: Thing,
+    pub actor: Option<UserInfo>,
+    pub action: AuditAction,
+    pub target: Thing,
+    pub patient: Option<Thing>,
+    pub changes: Option<Vec<FieldChange>>,
+    pub created_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserInfo {
+    pub id: Thing,
+    pub name: String,
+}
@@ -0,0 +1,461 @@
+use super::models::{AuditAction, AuditEntryResponse, AuditFilter, AuditRecord, FieldChange};
+use crate::auth::session::models::AuthenticatedUser;
+use crate::error::AppError;
+
+use scanlytics_db::{Any, Datetime, Surreal, Thing};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Fields that change on every write and carry no audit value
+const IGNORED_FIELDS: [&str; 3] = ["id", "created_at", "updated_at"];
+
+/// Builds the audit entry for a single record.
+///
+/// Services writing patient data store their entries with
+/// `INSERT INTO AuditLog $audit` inside the transaction of the write, so a
+/// write never commits without its audit entries.
+///
+/// # Arguments
+///
+/// * `user` - Authenticated user performing the action
+/// * `action` - Kind of access
+/// * `target` - Record that was accessed
+/// * `patient` - Patient the record belongs to, if any
+/// * `changes` - Field-level changes for writes
+///
+/// # Returns
+///
+/// Returns the entry, to be stored in the transaction of the write
+
+pub fn audit_entry(
+    user: &AuthenticatedUser,
+    action: AuditAction,
+    target: &Thing,
+    patient: Option<&Thing>,
+    changes: Option<Vec<FieldChange>>,
+) -> AuditRecord {
+    AuditRecord {
+        actor: Some(user.id.clone()),
+        action,
+        target: target.clone(),
+        patient: patient.cloned(),
+        changes,
+        created_at: Datetime::default(),
+    }
+}
+
+/// Builds entries with the same action for a batch of records.
+///
+/// # Arguments
+///
+/// * `user` - Authenticated user performing the action, or `None` for
+///   background jobs
+/// * `action` - Kind of access
+/// * `targets` - Records accessed, each with the patient it belongs to
+///
+/// # Returns
+///
+/// Returns the entries, to be stored in the transaction of the write
+
+pub fn audit_entries(
+    user: Option<&AuthenticatedUser>,
+    action: AuditAction,
+    targets: Vec<(Thing, Option<Thing>)>,
+) -> Vec<AuditRecord> {
+    targets
+        .into_iter()
+        .map(|(target, patient)| AuditRecord {
+            actor: user.map(|user| user.id.clone()),
+            action,
+            target,
+            patient,
+            changes: None,
+            created_at: Datetime::default(),
+        })
+        .collect()
+}
+
+/// Appends an audit entry for a single record.
+///
+/// Used for accesses that don't write patient data, such as exports; writes
+/// store their entries in their own transaction instead. Entries are only
+/// ever created, never updated or deleted.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `user` - Authenticated user performing the action
+/// * `action` - Kind of access
+/// * `target` - Record that was accessed
+/// * `patient` - Patient the record belongs to, if any
+/// * `changes` - Field-level changes for writes
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or failure
+
+pub async fn record_audit(
+    db: &Surreal<Any>,
+    user: &AuthenticatedUser,
+    action: AuditAction,
+    target: &Thing,
+    patient: Option<&Thing>,
+    changes: Option<Vec<FieldChange>>,
+) -> Result<(), AppError> {
+    let entry = audit_entry(user, action, target, patient, changes);
+
+    db.query("CREATE AuditLog CONTENT $entry")
+        .bind(("entry", entry))
+        .await?
+        .check()?;
+
+    Ok(())
+}
+
+/// Appends read entries for a batch of records in one statement.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `user` - Authenticated user reading the records
+/// * `targets` - Records read, each with the patient it belongs to
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or failure
+
+pub async fn record_reads(
+    db: &Surreal<Any>,
+    user: &AuthenticatedUser,
+    targets: Vec<(Thing, Option<Thing>)>,
+) -> Result<(), AppError> {
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    db.query("INSERT INTO AuditLog $audit")
+        .bind(("audit", audit_entries(Some(user), AuditAction::Read, targets)))
+        .await?
+        .check()?;
+
+    Ok(())
+}
+
+/// Computes the field-level changes a `MERGE` of `patch` into `before` makes.
+///
+/// Lets services build the audit entry of an update before running it, so
+/// the entry can be stored in the same transaction.
+///
+/// # Arguments
+///
+/// * `before` - Record before the change
+/// * `patch` - Fields the update sets
+///
+/// # Returns
+///
+/// Returns the changed fields, or `None` if nothing changes
+
+pub fn merge_changes<B: Serialize, P: Serialize>(
+    before: &B,
+    patch: &P,
+) -> Option<Vec<FieldChange>> {
+    let before = serde_json::to_value(before).ok();
+    let mut after = before.clone();
+    if let (Some(Value::Object(after)), Ok(Value::Object(patch))) =
+        (after.as_mut(), serde_json::to_value(patch))
+    {
+        after.extend(patch);
+    }
+    audit_changes(before.as_ref(), after.as_ref())
+}
+
+/// Computes the field-level changes between two versions of a record.
+///
+/// Pass `None` as `before` for creations and as `after` for deletions.
+///
+/// # Arguments
+///
+/// * `before` - Record before the change
+/// * `after` - Record after the change
+///
+/// # Returns
+///
+/// Returns the changed fields, or `None` if nothing changed
+
+pub fn audit_changes<B: Serialize, A: Serialize>(
+    before: Option<&B>,
+    after: Option<&A>,
+) -> Option<Vec<FieldChange>> {
+    let before = before.and_then(|record| serde_json::to_value(record).ok());
+    let after = after.and_then(|record| serde_json::to_value(record).ok());
+    let changes = diff_values(before.as_ref(), after.as_ref());
+
+    if changes.is_empty() {
+        None
+    } else {
+        Some(changes)
+    }
+}
+
+fn diff_values(before: Option<&Value>, after: Option<&Value>) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let before = before.and_then(Value::as_object).unwrap_or(&empty);
+    let after = after.and_then(Value::as_object).unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter(|field| !IGNORED_FIELDS.contains(&field.as_str()))
+        .filter_map(|field| {
+            let old = before.get(field).filter(|v| !v.is_null());
+            let new = after.get(field).filter(|v| !v.is_null());
+            (old != new).then(|| FieldChange {
+                field: field.clone(),
+                before: old.cloned(),
+                after: new.cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Retrieves audit entries matching the filter, newest first.
+///
+/// Admins may query every user's actions; other users only see their own.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `filter` - User, patient and date range filter
+/// * `user` - Authenticated user running the query
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<AuditEntryResponse>)` - Matching audit entries
+/// * `Err(AppError)` - Error message if the query fails
+
+pub async fn get_audit_log_service(
+    db: &Surreal<Any>,
+    filter: AuditFilter,
+    user: &AuthenticatedUser,
+) -> Result<Vec<AuditEntryResponse>, AppError> {
+    let actor = if user.is_admin() {
+        filter.user_id.map(|id| Thing::from(("User", id.as_str())))
+    } else {
+        Some(user.id.clone())
+    };
+    let patient = filter
+        .patient_id
+        .map(|id| Thing::from(("Patient", id.as_str())));
+
+    let query = "
+        SELECT
+            id,
+            IF actor IS NONE THEN NONE ELSE { id: actor.id, name: actor.name } END AS actor,
+            action,
+            target,
+            patient,
+            changes,
+            created_at
+        FROM AuditLog
+        WHERE (!$actor OR actor = $actor)
+            AND (!$patient OR patient = $patient)
+            AND (!$from OR created_at >= $from)
+            AND (!$to OR created_at <= $to)
+        ORDER BY created_at DESC;
+    ";
+
+    let entries: Vec<AuditEntryResponse> = db
+        .query(query)
+        .bind(("actor", actor))
+        .bind(("patient", patient))
+        .bind(("from", filter.from))
+        .bind(("to", filter.to))
+        .await?
+        .take(0)?;
+
+    Ok(entries)
+}
+
+/// Exports audit entries matching the filter as CSV.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `filter` - User, patient and date range filter
+/// * `user` - Authenticated user running the export
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(String)` - CSV document with a header row
+/// * `Err(AppError)` - Error message if the export fails
+
+pub async fn export_audit_log_service(
+    db: &Surreal<Any>,
+    filter: AuditFilter,
+    user: &AuthenticatedUser,
+) -> Result<String, AppError> {
+    let entries = get_audit_log_service(db, filter, user).await?;
+
+    let mut csv = String::from("timestamp,actor_id,actor_name,action,target,patient,changes\n");
+    for entry in entries {
+        let action = serde_json::to_value(entry.action)
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default();
+        let changes = entry
+            .changes
+            .map(|changes| serde_json::to_string(&changes).unwrap_or_default())
+            .unwrap_or_default();
+
+        let (actor_id, actor_name) = entry
+            .actor
+            .map(|actor| (actor.id.to_string(), actor.name))
+            .unwrap_or_default();
+        let row = [
+            entry.created_at.to_string(),
+            actor_id,
+            actor_name,
+            action,
+            entry.target.to_string(),
+            entry.patient.map(|p| p.to_string()).unwrap_or_default(),
+            changes,
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    Ok(csv)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::session::models::ADMIN_ROLE;
+    use crate::test_utils::{create_test_user, setup_test_db};
+    use serde_json::json;
+
+    #[test]
+    fn test_audit_changes_for_update() {
+        let before = json!({ "id": "a", "name": "John", "gender": "male", "updated_at": "1" });
+        let after = json!({ "id": "a", "name": "Johnny", "gender": "male", "updated_at": "2" });
+
+        let changes = audit_changes(Some(&before), Some(&after)).unwrap();
+        assert_eq!(
+            changes,
+            vec![FieldChange {
+                field: "name".to_string(),
+                before: Some(json!("John")),
+                after: Some(json!("Johnny")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merge_changes_predict_update() {
+        let before = json!({ "id": "a", "name": "John", "gender": "male" });
+        let patch = json!({ "name": "Johnny", "gender": "male" });
+
+        let changes = merge_changes(&before, &patch).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "name");
+        assert!(merge_changes(&before, &json!({ "gender": "male" })).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_audit_entries_are_immutable() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+
+        let patient = Thing::from(("Patient", "p1"));
+        record_audit(&db, &doctor, AuditAction::Create, &patient, Some(&patient), None)
+            .await
+            .unwrap();
+
+        let update = db
+            .query("UPDATE AuditLog SET action = 'read'")
+            .await
+            .unwrap()
+            .check();
+        assert!(update.is_err());
+
+        let delete = db.query("DELETE AuditLog").await.unwrap().check();
+        assert!(delete.is_err());
+
+        let entries = get_audit_log_service(&db, AuditFilter::default(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, AuditAction::Create);
+    }
+
+    #[test]
+    fn test_audit_changes_without_difference() {
+        let record = json!({ "name": "John" });
+        assert!(audit_changes(Some(&record), Some(&record)).is_none());
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_filtering() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let admin = AuthenticatedUser {
+            role: ADMIN_ROLE.to_string(),
+            ..create_test_user(&db, "admin@test.com", None).await
+        };
+
+        let patient = Thing::from(("Patient", "p1"));
+        let note = Thing::from(("PatientNote", "n1"));
+
+        record_audit(&db, &doctor, AuditAction::Create, &note, Some(&patient), None)
+            .await
+            .unwrap();
+        record_reads(&db, &admin, vec![(patient.clone(), Some(patient.clone()))])
+            .await
+            .unwrap();
+
+        let own = get_audit_log_service(&db, AuditFilter::default(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(own.len(), 1);
+        assert_eq!(own[0].action, AuditAction::Create);
+
+        let all = get_audit_log_service(&db, AuditFilter::default(), &admin)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        let filter = AuditFilter {
+            user_id: Some(admin.id.id.to_raw()),
+            ..Default::default()
+        };
+        let by_admin = get_audit_log_service(&db, filter, &admin).await.unwrap();
+        assert_eq!(by_admin.len(), 1);
+        assert_eq!(by_admin[0].action, AuditAction::Read);
+
+        let csv = export_audit_log_service(&db, AuditFilter::default(), &admin)
+            .await
+            .unwrap();
+        assert_eq!(csv.lines().count(), 3);
+    }
+}
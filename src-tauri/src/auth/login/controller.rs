@@ -1,6 +1,10 @@
 
-use super::models::{ApiResponse, LoginResponse};
+use super::models::{ApiResponse, LoginRequest, LoginResponse};
 use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::start_session;
+use crate::error::AppError;
+use tauri::State;
 
 /// Tauri command for user authentication.
 ///
@@ -8,10 +12,12 @@ use super::services;
 /// 1. Accepting login credentials
 /// 2. Authenticating with the backend server
 /// 3. Managing authentication tokens
-/// 4. Providing response to the frontend
+/// 4. Starting the session for the authenticated user
+/// 5. Providing response to the frontend
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `login_data` - JSON string containing:
 ///   * `user_email`: User's email address
 ///   * `user_password`: User's password
@@ -21,7 +27,7 @@ use super::services;
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(ApiResponse<LoginResponse>)` - Successful login with token
-/// * `Err(String)` - Error message if login fails
+/// * `Err(AppError)` - Error message if login fails
 ///
 /// # Security
 ///
@@ -30,8 +36,18 @@ use super::services;
 /// - Error messages are sanitized
 
 #[tauri::command]
-pub async fn login(login_data: String, base_url: Option<String>) -> Result<ApiResponse<LoginResponse>, String> {
-    services::login_service(login_data, base_url)
-        .await
-        .map_err(|e| e.to_string())
+pub async fn login(
+    session_state: State<'_, SessionState>,
+    login_data: String,
+    base_url: Option<String>,
+) -> Result<ApiResponse<LoginResponse>, AppError> {
+    let login_request: LoginRequest = serde_json::from_str(&login_data)
+        .map_err(|_| AppError::InvalidInput("Invalid login data".to_string()))?;
+
+    let response = services::login_service(login_data, base_url).await?;
+
+    if let Some(data) = &response.data {
+        start_session(&session_state, &login_request.user_email, &data.access_token).await;
+    }
+    Ok(response)
 }
\ No newline at end of file
@@ -1,4 +1,8 @@
 use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::end_session;
+use crate::error::AppError;
+use tauri::State;
 
 
 
@@ -9,13 +13,17 @@ use super::services;
 /// 2. Cleaning up the user session
 /// 3. Providing status feedback
 ///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(String)` - Success message indicating logout status:
 ///   - "Successfully logged out" - When logout was successful
 ///   - "No active session found" - When no active session existed
-/// * `Err(String)` - Error message if logout fails
+/// * `Err(AppError)` - Error message if logout fails
 ///
 /// # Error Messages
 ///
@@ -31,8 +39,10 @@ use super::services;
 /// - Provides clear operation status
 
 #[tauri::command]
-pub async fn logout() -> Result<String, String> {
-    services::logout_service()
+pub async fn logout(session_state: State<'_, SessionState>) -> Result<String, AppError> {
+    let session = end_session(&session_state).await;
+
+    services::logout_service(session.map(|session| session.user_email))
         .await
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
\ No newline at end of file
@@ -1,14 +1,20 @@
 use super::models::LogoutError;
-use keyring::Entry;
+use keyring::{Entry, Error as KeyringError};
+
+const SERVICE_NAME: &str = "com.scanlytics.dev";
 
 
 /// Handles the logout process by removing stored authentication tokens.
 ///
 /// This service:
-/// - Attempts to access the system keyring
+/// - Attempts to access the system keyring entry of the session user
 /// - Removes stored credentials if they exist
 /// - Handles various error conditions gracefully
 ///
+/// # Arguments
+///
+/// * `user_email` - Email address of the user whose session ended, if any
+///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
@@ -30,22 +36,20 @@ use keyring::Entry;
 ///
 /// - Ensures complete removal of authentication tokens
 /// - Handles edge cases like missing credentials
-pub async fn logout_service() -> Result<String, LogoutError> {
-    let service_name = "com.scanlytics.dev";
-    
-    match Entry::new(service_name, "scanlytics") {
-        Ok(entry) => {
-            entry.delete_credential().map_err(|e| {
-                LogoutError::KeyringDelete(format!("Failed to delete credentials: {}", e))
-            })?;
-            Ok("Successfully logged out".to_string())
-        }
-        Err(e) => {
-            if e.to_string().contains("No credential found") {
-                Ok("No active session found".to_string())
-            } else {
-                Err(LogoutError::KeyringAccess(format!("Failed to access keyring: {}", e)))
-            }
-        }
+pub async fn logout_service(user_email: Option<String>) -> Result<String, LogoutError> {
+    let Some(user_email) = user_email else {
+        return Ok("No active session found".to_string());
+    };
+
+    let entry = Entry::new(SERVICE_NAME, user_email.trim())
+        .map_err(|e| LogoutError::KeyringAccess(format!("Failed to access keyring: {}", e)))?;
+
+    match entry.delete_credential() {
+        Ok(()) => Ok("Successfully logged out".to_string()),
+        Err(KeyringError::NoEntry) => Ok("No active session found".to_string()),
+        Err(e) => Err(LogoutError::KeyringDelete(format!(
+            "Failed to delete credentials: {}",
+            e
+        ))),
     }
 }
@@ -12,6 +12,7 @@
 //! - [`signup`]: New user registration
 //! - [`validate`]: Token validation and verification
 //! - [`logout`]: Session termination
+//! - [`session`]: Active session tracking and command protection
 //! 
 
 
@@ -19,3 +20,4 @@ pub mod signup;
 pub mod login;
 pub mod validate;
 pub mod logout;
+pub mod session;
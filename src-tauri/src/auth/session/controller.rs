@@ -0,0 +1,29 @@
+use super::models::{AuthenticatedUser, SessionState};
+use super::services;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+/// Tauri command returning the currently authenticated user.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(AuthenticatedUser)` - The user bound to the active session
+/// * `Err(AppError)` - Error message if no valid session exists
+
+#[tauri::command]
+pub async fn get_current_user(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+) -> Result<AuthenticatedUser, AppError> {
+    services::authenticate(&session_state, &db_connection)
+        .await
+        .map_err(AppError::from)
+}
@@ -0,0 +1,18 @@
+//! # Session Module
+//! 
+//! Tracks the currently authenticated user and guards data commands, including:
+//! - Active session state shared across Tauri commands
+//! - Access token and expiry tracking with proactive refresh
+//! - Session expiry notifications to the UI
+//! - Resolution of the authenticated user from the local database
+//! - Session middleware for protected commands
+//! 
+//! ## Features
+//! 
+//! - Single source of truth for the active user
+//! - Consistent rejection of unauthenticated calls
+//! - Validated user identity passed into services
+
+pub mod controller;
+pub mod models;
+pub mod services;
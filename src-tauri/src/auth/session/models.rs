@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use scanlytics_db::Thing;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Role allowed to manage records owned by other users.
+pub const ADMIN_ROLE: &str = "admin";
+/// Event emitted to the UI when a session can no longer be refreshed.
+pub const SESSION_EXPIRED_EVENT: &str = "session-expired";
+/// Refresh tokens this many seconds before they expire.
+pub const REFRESH_MARGIN_SECS: u64 = 300;
+/// Lifetime assumed for tokens without an `exp` claim.
+pub const DEFAULT_TOKEN_TTL_SECS: u64 = 1800;
+
+#[derive(Debug)]
+pub enum SessionError {
+    NotAuthenticated,
+    Expired(String),
+    InvalidToken(String),
+    UserNotFound(String),
+    Database(String),
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::NotAuthenticated => write!(f, "Not authenticated: please log in"),
+            SessionError::Expired(email) => write!(f, "Session for {} has expired: please log in again", email),
+            SessionError::InvalidToken(msg) => write!(f, "Invalid session token: {}", msg),
+            SessionError::UserNotFound(email) => write!(f, "No local user found for {}", email),
+            SessionError::Database(msg) => write!(f, "Database error: {}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub user_email: String,
+    pub access_token: String,
+    /// Token expiry as seconds since the Unix epoch
+    pub expires_at: u64,
+}
+
+impl Session {
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+
+    pub fn needs_refresh(&self, now: u64) -> bool {
+        now + REFRESH_MARGIN_SECS >= self.expires_at
+    }
+}
+
+/// Tauri managed state holding the active session, if any.
+#[derive(Debug, Clone, Default)]
+pub struct SessionState(pub Arc<Mutex<Option<Session>>>);
+
+impl SessionState {
+    pub fn get(&self) -> &Arc<Mutex<Option<Session>>> {
+        &self.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub id: Thing,
+    pub name: String,
+    pub email: String,
+    pub role: String,
+    pub organization: Option<Thing>,
+}
+
+impl AuthenticatedUser {
+    /// Whether the user's role grants access to every record.
+    pub fn is_admin(&self) -> bool {
+        self.role == ADMIN_ROLE
+    }
+
+    /// Whether the user may modify a record owned by `owner`.
+    pub fn can_modify(&self, owner: &Thing) -> bool {
+        &self.id == owner || self.is_admin()
+    }
+}
@@ -0,0 +1,389 @@
+use super::models::{
+    AuthenticatedUser, Session, SessionError, SessionState, DEFAULT_TOKEN_TTL_SECS,
+    SESSION_EXPIRED_EVENT,
+};
+use crate::auth::logout::models::LogoutError;
+use crate::auth::logout::services::logout_service;
+use crate::auth::validate::models::TokenError;
+use crate::auth::validate::services::{validate_token_service, verify_token_locally};
+use crate::error::AppError;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use scanlytics_db::{Any, DbConnection, Surreal};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager, Runtime};
+
+/// Interval between background session checks.
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Starts a session for the given user, replacing any previous one.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `user_email` - Email address of the user who just authenticated
+/// * `access_token` - Access token issued for the user
+///
+/// # Returns
+///
+/// Returns the newly active session
+
+pub async fn start_session(
+    session_state: &SessionState,
+    user_email: &str,
+    access_token: &str,
+) -> Session {
+    let session = Session {
+        user_email: user_email.trim().to_string(),
+        access_token: access_token.to_string(),
+        expires_at: token_expiry(access_token)
+            .unwrap_or_else(|| unix_now() + DEFAULT_TOKEN_TTL_SECS),
+    };
+
+    *session_state.get().lock().await = Some(session.clone());
+    session
+}
+
+/// Ends the active session.
+///
+/// # Returns
+///
+/// Returns the session that was active, if any
+
+pub async fn end_session(session_state: &SessionState) -> Option<Session> {
+    session_state.get().lock().await.take()
+}
+
+/// Ends an expired session and removes the user's stored token.
+///
+/// The UI logs out when it receives [`SESSION_EXPIRED_EVENT`], but by then
+/// no session is left to name the keyring entry, so it is cleared here.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `user_email` - Email address of the user whose session expired
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(String)` - Logout status message
+/// * `Err(LogoutError)` - The keyring entry couldn't be removed; the session
+///   is ended regardless
+
+pub async fn expire_session(
+    session_state: &SessionState,
+    user_email: &str,
+) -> Result<String, LogoutError> {
+    end_session(session_state).await;
+    logout_service(Some(user_email.to_string())).await
+}
+
+/// Returns a copy of the active session, if any.
+
+pub async fn current_session(session_state: &SessionState) -> Option<Session> {
+    session_state.get().lock().await.clone()
+}
+
+/// Refreshes the active session token when it is close to expiry.
+///
+/// Tokens are only renewed with the backend inside the refresh margin, so
+/// regular calls do not hit the network. A failed refresh is tolerated until
+/// the token actually expires.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Session)` - The active, unexpired session
+/// * `Err(SessionError)` - No session, or the session has expired
+
+pub async fn refresh_session(session_state: &SessionState) -> Result<Session, SessionError> {
+    let session = current_session(session_state)
+        .await
+        .ok_or(SessionError::NotAuthenticated)?;
+
+    let now = unix_now();
+    if !session.needs_refresh(now) {
+        return Ok(session);
+    }
+
+    match validate_token_service(&session.user_email).await {
+        Ok(access_token) => {
+            Ok(start_session(session_state, &session.user_email, &access_token).await)
+        }
+        Err(_) if session.is_expired(now) => Err(SessionError::Expired(session.user_email)),
+        Err(_) => Ok(session),
+    }
+}
+
+/// Watches the active session in the background.
+///
+/// Refreshes tokens ahead of expiry and, once a session can no longer be
+/// refreshed, ends it through [`expire_session`] and emits
+/// [`SESSION_EXPIRED_EVENT`] with the user's email so the UI can return to
+/// the login screen. Neither step has a caller to report failures to, and
+/// the UI is sent to the login screen either way.
+///
+/// # Arguments
+///
+/// * `app_handle` - Tauri application handle managing the `SessionState`
+
+pub fn spawn_session_watcher<R: Runtime>(app_handle: tauri::AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SESSION_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let session_state = app_handle.state::<SessionState>();
+
+            if let Err(SessionError::Expired(user_email)) = refresh_session(&session_state).await {
+                let _ = expire_session(&session_state, &user_email).await;
+                let _ = app_handle.emit(SESSION_EXPIRED_EVENT, user_email);
+            }
+        }
+    });
+}
+
+/// Resolves the authenticated user for the current call.
+///
+/// This service:
+/// 1. Reads the active session
+/// 2. Refreshes the session token if it is about to expire
+/// 3. Validates the token locally (signature, expiry and claims)
+/// 4. Loads the matching local `User` record
+///
+/// Only the refresh step contacts the backend, so protected commands stay
+/// fast and keep working while briefly offline.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(AuthenticatedUser)` - The validated user identity
+/// * `Err(SessionError)` - Authentication error details
+///
+/// # Errors
+///
+/// This function can return several types of errors:
+/// * `SessionError::NotAuthenticated` - No user is logged in
+/// * `SessionError::Expired` - The session token expired and could not be refreshed
+/// * `SessionError::InvalidToken` - The session token failed local validation
+/// * `SessionError::UserNotFound` - No local user matches the session
+/// * `SessionError::Database` - User lookup failed
+
+pub async fn authenticate(
+    session_state: &SessionState,
+    db_connection: &DbConnection,
+) -> Result<AuthenticatedUser, SessionError> {
+    let session = refresh_session(session_state).await?;
+
+    verify_token_locally(&session.access_token, &session.user_email)
+        .await
+        .map_err(|e| match e {
+            TokenError::Expired => SessionError::Expired(session.user_email.clone()),
+            e => SessionError::InvalidToken(e.to_string()),
+        })?;
+
+    let db = db_connection.get().lock().await;
+    find_user_by_email(&db, &session.user_email).await
+}
+
+/// Looks up a local user by email address.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `user_email` - Email address of the user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(AuthenticatedUser)` - Matching user
+/// * `Err(SessionError)` - Lookup error details
+
+pub async fn find_user_by_email(
+    db: &Surreal<Any>,
+    user_email: &str,
+) -> Result<AuthenticatedUser, SessionError> {
+    let users: Vec<AuthenticatedUser> = db
+        .query("SELECT id, name, email, role, organization FROM User WHERE email = $email LIMIT 1")
+        .bind(("email", user_email.trim().to_string()))
+        .await
+        .map_err(|e| SessionError::Database(e.to_string()))?
+        .take(0)
+        .map_err(|e| SessionError::Database(e.to_string()))?;
+
+    users
+        .into_iter()
+        .next()
+        .ok_or_else(|| SessionError::UserNotFound(user_email.to_string()))
+}
+
+/// Reads the `exp` claim of a JWT without verifying it.
+///
+/// Only used to schedule refreshes; [`authenticate`] verifies the token.
+///
+/// # Arguments
+///
+/// * `token` - Encoded JWT
+///
+/// # Returns
+///
+/// Returns the expiry in seconds since the Unix epoch, if present
+
+pub fn token_expiry(token: &str) -> Option<u64> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    claims["exp"].as_u64()
+}
+
+/// Current time in seconds since the Unix epoch.
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Session middleware for protected commands.
+///
+/// Resolves the authenticated user once and hands it to the protected
+/// function, so services never trust identities sent by the frontend.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection
+/// * `f` - Protected async function receiving the authenticated user
+///
+/// # Returns
+///
+/// Returns the result of the protected function if authentication succeeds,
+/// or the authentication failure as an `AppError`
+///
+/// # Example
+///
+/// ```rust,no_run
+/// session_middleware(&session_state, &db_connection, |user| async move {
+///     Ok(user.name)
+/// })
+/// .await
+/// ```
+
+pub async fn session_middleware<F, Fut, R>(
+    session_state: &SessionState,
+    db_connection: &DbConnection,
+    f: F,
+) -> Result<R, AppError>
+where
+    F: FnOnce(AuthenticatedUser) -> Fut,
+    Fut: std::future::Future<Output = Result<R, AppError>>,
+{
+    let user = authenticate(session_state, db_connection).await?;
+    f(user).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn setup_test_db() -> DbConnection {
+        scanlytics_db::init_db(None, true).await.unwrap()
+    }
+
+    async fn create_test_user(db_connection: &DbConnection, email: &str) {
+        let db = db_connection.get().lock().await;
+        db.query("CREATE User CONTENT $user")
+            .bind(("user", json!({
+                "name": "Test Doctor",
+                "email": email,
+                "role": "user"
+            })))
+            .await
+            .unwrap();
+    }
+
+    fn test_token(exp: u64) -> String {
+        let claims = URL_SAFE_NO_PAD.encode(json!({ "sub": "doctor@test.com", "exp": exp }).to_string());
+        format!("header.{}.signature", claims)
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let session_state = SessionState::default();
+        assert!(current_session(&session_state).await.is_none());
+
+        start_session(&session_state, " doctor@test.com ", &test_token(unix_now() + 3600)).await;
+        let session = current_session(&session_state).await.unwrap();
+        assert_eq!(session.user_email, "doctor@test.com");
+
+        let ended = end_session(&session_state).await;
+        assert!(ended.is_some());
+        assert!(current_session(&session_state).await.is_none());
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        assert_eq!(token_expiry(&test_token(1_700_000_000)), Some(1_700_000_000));
+        assert_eq!(token_expiry("not-a-jwt"), None);
+    }
+
+    #[tokio::test]
+    async fn test_fresh_session_is_not_refreshed() {
+        let session_state = SessionState::default();
+        let token = test_token(unix_now() + 3600);
+        start_session(&session_state, "doctor@test.com", &token).await;
+
+        let session = refresh_session(&session_state).await.unwrap();
+        assert_eq!(session.access_token, token);
+    }
+
+    #[tokio::test]
+    async fn test_expired_session_without_refresh() {
+        let session_state = SessionState::default();
+        start_session(&session_state, "nobody@test.com", &test_token(unix_now() - 10)).await;
+
+        let result = refresh_session(&session_state).await;
+        assert!(matches!(result, Err(SessionError::Expired(_))));
+
+        let _ = expire_session(&session_state, "nobody@test.com").await;
+        assert!(current_session(&session_state).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_middleware_rejects_without_session() {
+        let db_connection = setup_test_db().await;
+        let session_state = SessionState::default();
+
+        let result = session_middleware(&session_state, &db_connection, |_| async {
+            Ok("Success".to_string())
+        })
+        .await;
+
+        assert!(matches!(result, Err(AppError::NotAuthenticated(_))));
+    }
+
+    #[tokio::test]
+    async fn test_find_user_by_email() {
+        let db_connection = setup_test_db().await;
+        create_test_user(&db_connection, "doctor@test.com").await;
+
+        let db = db_connection.get().lock().await;
+        let user = find_user_by_email(&db, "doctor@test.com").await.unwrap();
+        assert_eq!(user.name, "Test Doctor");
+
+        let missing = find_user_by_email(&db, "nobody@test.com").await;
+        assert!(matches!(missing, Err(SessionError::UserNotFound(_))));
+    }
+}
@@ -1,5 +1,6 @@
 use super::models::SignupResponse;
 use super::services;
+use crate::error::AppError;
 use tauri::State;
 use scanlytics_db::DbConnection;
 
@@ -25,7 +26,7 @@ use scanlytics_db::DbConnection;
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(SignupResponse)` - Successful registration response
-/// * `Err(String)` - Error message if registration fails
+/// * `Err(AppError)` - Error message if registration fails
 ///
 
 
@@ -35,11 +36,11 @@ pub async fn signup(
     db_connection: State<'_, DbConnection>,
     signup_data: String,
     base_url: Option<String>,
-) -> Result<SignupResponse, String> {
+) -> Result<SignupResponse, AppError> {
     let db = db_connection.get().lock().await;
 
     
     services::signup_service(&db, signup_data, base_url)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
 }
\ No newline at end of file
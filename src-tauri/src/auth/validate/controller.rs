@@ -1,4 +1,8 @@
 use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::start_session;
+use crate::error::AppError;
+use tauri::State;
 
 
 /// Tauri command for validating authentication tokens.
@@ -7,21 +11,27 @@ use super::services;
 /// 1. Retrieving stored token
 /// 2. Validating with backend server
 /// 3. Storing renewed token if provided
+/// 4. Restoring the session for the validated user
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `user_email` - Email address associated with the token
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(())` - Token is valid and renewed if necessary
-/// * `Err(String)` - Error message if validation fails
+/// * `Err(AppError)` - Error message if validation fails
 
 
 #[tauri::command]
-pub async fn validate_token(user_email: String) -> Result<(), String> {
-    services::validate_token_service(&user_email)
-        .await
-        .map_err(|e| e.to_string())
+pub async fn validate_token(
+    session_state: State<'_, SessionState>,
+    user_email: String,
+) -> Result<(), AppError> {
+    let access_token = services::validate_token_service(&user_email).await?;
+
+    start_session(&session_state, &user_email, &access_token).await;
+    Ok(())
 }
@@ -1,7 +1,8 @@
 //! # Token Validation Module
 //! 
 //! Handles authentication token validation and renewal, including:
-//! - Token validation with backend server
+//! - Local token validation against cached signing keys
+//! - Token renewal with backend server
 //! - Token storage management
 //! - Authentication middleware
 //! - Error handling
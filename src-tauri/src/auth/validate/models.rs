@@ -8,6 +8,10 @@ pub enum TokenError {
     ValidationError(String),
     ParseError(String),
     InvalidTokenType,
+    Expired,
+    InvalidSignature(String),
+    ClaimsMismatch(String),
+    KeyUnavailable(String),
 }
 
 impl std::fmt::Display for TokenError {
@@ -19,6 +23,10 @@ impl std::fmt::Display for TokenError {
             TokenError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             TokenError::ParseError(msg) => write!(f, "Parse error: {}", msg),
             TokenError::InvalidTokenType => write!(f, "Invalid token type: expected 'bearer'"),
+            TokenError::Expired => write!(f, "Token has expired"),
+            TokenError::InvalidSignature(msg) => write!(f, "Invalid token signature: {}", msg),
+            TokenError::ClaimsMismatch(msg) => write!(f, "Token claims mismatch: {}", msg),
+            TokenError::KeyUnavailable(msg) => write!(f, "Verification key unavailable: {}", msg),
         }
     }
 }
@@ -41,4 +49,24 @@ impl TokenResponse {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Claims checked during local token validation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: u64,
+    pub email: Option<String>,
+}
+
+impl Claims {
+    /// Whether the token was issued for the given user.
+    pub fn belongs_to(&self, user_email: &str) -> bool {
+        let user_email = user_email.trim();
+        self.email
+            .as_deref()
+            .unwrap_or(&self.sub)
+            .eq_ignore_ascii_case(user_email)
+            || self.sub.eq_ignore_ascii_case(user_email)
+    }
+}
@@ -11,6 +11,7 @@
 /// - `signup`: New user registration
 /// - `logout`: User session termination
 /// - `validate_token`: Token validation
+/// - `get_current_user`: Authenticated session user
 ///
 /// ### User Management
 /// - `get_users`: Retrieve user information
@@ -19,7 +20,16 @@
 /// - `create_patient`: Create new patient records
 /// - `delete_patient`: Remove patient records
 /// - `get_patients`: Retrieve patient information
+/// - `search_patients`: Search, sort and page through patients
 /// - `update_patient`: Modify patient records
+/// - `share_patient`: Share a patient with another user
+/// - `import_patients`: Bulk import patients from FHIR or CSV
+/// - `find_duplicate_patients`: List likely duplicate patients
+/// - `merge_patients`: Merge a duplicate patient into another patient
+/// - `get_patient_identifiers`: List a patient's MRNs and other external identifiers
+/// - `add_patient_identifier`: Add an external identifier to a patient
+/// - `remove_patient_identifier`: Remove an external identifier from a patient
+/// - `find_patient_by_identifier`: Look up a patient by an external identifier
 ///
 /// ### Patient Notes
 /// - `create_patient_note`: Create medical notes
@@ -29,17 +39,71 @@
 ///
 /// ### Medical Reports
 /// - `create_report`: Generate medical reports
+/// - `compose_report`: Compose structured reports from analysis results
 /// - `get_reports`: Retrieve report information
 /// - `get_report_images`: Access report images
+/// - `delete_report`: Move reports to the trash
+/// - `update_report`: Edit draft reports
+/// - `release_report_preliminary`: Release drafts as preliminary
+/// - `sign_report`: Sign off reports as final
+/// - `amend_report`: Create an amended version of a final report
+/// - `get_report_history`: Retrieve the revisions of a report
+/// - `diff_report_revisions`: Compare two report revisions word by word
+/// - `export_report_pdf`: Export signed reports as PDF documents
+/// - `update_letterhead`: Configure the organization letterhead for exports
 ///
 /// ### Image Analysis
 /// - `process_images`: Perform medical image processing
-
+///
+/// ### Audit
+/// - `get_audit_log`: Query the audit trail
+/// - `export_audit_log`: Export the audit trail as CSV
+///
+/// ### Trash
+/// - `get_trash`: List trashed patients, notes and reports
+/// - `restore_from_trash`: Restore a trashed record
+/// - `purge_trash`: Permanently remove expired trash
+///
+/// ### FHIR
+/// - `export_patient_fhir`: Export a patient's record as a FHIR bundle
+/// - `export_report_fhir`: Export a report as a FHIR bundle
+///
+/// ### HL7 v2
+/// - `generate_report_hl7`: Preview the ORU^R01 message of a report
+/// - `send_report_hl7`: Send a final report over MLLP
+/// - `update_hl7_settings`: Configure the organization's HL7 receiver
+///
+/// ### DICOMweb
+/// - `search_dicomweb_studies`: Search the PACS for studies
+/// - `search_dicomweb_series`: List the series of a PACS study
+/// - `pull_dicomweb_series`: Import and analyse a series from the PACS
+/// - `update_dicomweb_settings`: Configure the organization's PACS endpoint
+///
+/// ### DICOM Export
+/// - `export_report_dicom`: Export AI results as DICOM SR and Secondary Capture
+/// - `push_report_dicom`: Store AI results in the source study on the PACS
+///
+/// ### Search
+/// - `search`: Full-text search over notes and reports
+///
+/// ### Timeline
+/// - `get_patient_timeline`: Chronological view of a patient's case
+///
+/// ## Authentication
+///
+/// Except for the authentication commands, every command runs its work
+/// through `session_middleware` and requires an active session; the
+/// authenticated user is passed on to the services.
+///
 /// ## Implementation Details
 ///
 /// The macro expands to a `tauri::generate_handler!` macro call that includes
 /// all available command handlers. Each handler is referenced using the full
 /// path to ensure proper resolution.
+///
+/// Every command fails with [`crate::error::AppError`], which serializes to
+/// an object with a `code`, a user-facing `message`, optional field errors
+/// and whether the command may be retried.
 
 
 #[macro_export]
@@ -52,11 +116,21 @@ macro_rules! get_commands {
             $crate::auth::signup::controller::signup,
             $crate::auth::logout::controller::logout,
             $crate::auth::validate::controller::validate_token,
+            $crate::auth::session::controller::get_current_user,
             // Patients
             $crate::patients::controller::create_patient,
             $crate::patients::controller::delete_patient,
             $crate::patients::controller::get_patients,
+            $crate::patients::controller::search_patients,
             $crate::patients::controller::update_patient,
+            $crate::patients::controller::share_patient,
+            $crate::patients::controller::import_patients,
+            $crate::patients::controller::find_duplicate_patients,
+            $crate::patients::controller::merge_patients,
+            $crate::patients::controller::get_patient_identifiers,
+            $crate::patients::controller::add_patient_identifier,
+            $crate::patients::controller::remove_patient_identifier,
+            $crate::patients::controller::find_patient_by_identifier,
             // Notes
             $crate::notes::controller::create_patient_note,
             $crate::notes::controller::delete_patient_note,
@@ -64,10 +138,46 @@ macro_rules! get_commands {
             $crate::notes::controller::update_patient_note,
             // Reports
             $crate::reports::controller::create_report,
+            $crate::reports::controller::compose_report,
             $crate::reports::controller::get_reports,
             $crate::reports::controller::get_report_images,
+            $crate::reports::controller::delete_report,
+            $crate::reports::controller::update_report,
+            $crate::reports::controller::release_report_preliminary,
+            $crate::reports::controller::sign_report,
+            $crate::reports::controller::amend_report,
+            $crate::reports::controller::get_report_history,
+            $crate::reports::controller::diff_report_revisions,
+            $crate::reports::controller::export_report_pdf,
+            $crate::reports::controller::update_letterhead,
             // Image Analysis
-            $crate::image_analysis::image_processing::controller::process_images
+            $crate::image_analysis::image_processing::controller::process_images,
+            // Audit
+            $crate::audit::controller::get_audit_log,
+            $crate::audit::controller::export_audit_log,
+            // Trash
+            $crate::trash::controller::get_trash,
+            $crate::trash::controller::restore_from_trash,
+            $crate::trash::controller::purge_trash,
+            // FHIR
+            $crate::fhir::controller::export_patient_fhir,
+            $crate::fhir::controller::export_report_fhir,
+            // HL7 v2
+            $crate::hl7::controller::generate_report_hl7,
+            $crate::hl7::controller::send_report_hl7,
+            $crate::hl7::controller::update_hl7_settings,
+            // DICOMweb
+            $crate::dicomweb::controller::search_dicomweb_studies,
+            $crate::dicomweb::controller::search_dicomweb_series,
+            $crate::dicomweb::controller::pull_dicomweb_series,
+            $crate::dicomweb::controller::update_dicomweb_settings,
+            // DICOM Export
+            $crate::dicom::controller::export_report_dicom,
+            $crate::dicom::controller::push_report_dicom,
+            // Search
+            $crate::search::controller::search,
+            // Timeline
+            $crate::timeline::controller::get_patient_timeline
         ]
     };
 }
\ No newline at end of file
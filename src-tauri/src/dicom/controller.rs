@@ -0,0 +1,78 @@
+use super::models;
+use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+
+/// Exports a final report and its AI results as DICOM SR and Secondary Capture files.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+/// * `secondary_capture` - Whether to include Secondary Capture images
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(DicomExportResponse)` - The study UID and the DICOM files
+/// * `Err(AppError)` - Error message if the export fails
+
+#[tauri::command]
+pub async fn export_report_dicom(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+    secondary_capture: bool,
+) -> Result<models::DicomExportResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::export_report_dicom_service(&db, report_id, secondary_capture, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Stores a final report and its AI results in the organization's PACS.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+/// * `secondary_capture` - Whether to include Secondary Capture images
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(DicomPushResponse)` - The study and the stored instances
+/// * `Err(AppError)` - Error message if the export or upload fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * No PACS is configured for the organization
+/// * The report isn't final or has no images retrieved from a PACS
+/// * The PACS is unreachable or rejects the objects
+
+#[tauri::command]
+pub async fn push_report_dicom(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+    secondary_capture: bool,
+) -> Result<models::DicomPushResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        services::push_report_dicom_service(db_connection, report_id, secondary_capture, &user)
+            .await
+    })
+    .await
+}
@@ -0,0 +1,309 @@
+//! Minimal DICOM Part 10 writer.
+//!
+//! Encodes data sets in the Explicit VR Little Endian transfer syntax with
+//! defined-length sequences and items. Only the value representations used
+//! by the exported objects are supported.
+
+use std::collections::BTreeMap;
+
+const EXPLICIT_VR_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2.1";
+const IMPLEMENTATION_CLASS_UID: &str = "2.25.278470939360798418792870884116846441281";
+const IMPLEMENTATION_VERSION_NAME: &str = "SCANLYTICS_1";
+
+const ITEM: (u16, u16) = (0xFFFE, 0xE000);
+
+/// A data element tag as (group, element).
+pub(crate) type Tag = (u16, u16);
+
+/// Value representations used by the exported objects.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Vr {
+    CS,
+    DA,
+    DS,
+    DT,
+    IS,
+    LO,
+    OB,
+    OW,
+    PN,
+    SH,
+    SQ,
+    SS,
+    ST,
+    TM,
+    UI,
+    UL,
+    US,
+    UT,
+}
+
+impl Vr {
+    fn code(self) -> &'static [u8; 2] {
+        match self {
+            Vr::CS => b"CS",
+            Vr::DA => b"DA",
+            Vr::DS => b"DS",
+            Vr::DT => b"DT",
+            Vr::IS => b"IS",
+            Vr::LO => b"LO",
+            Vr::OB => b"OB",
+            Vr::OW => b"OW",
+            Vr::PN => b"PN",
+            Vr::SH => b"SH",
+            Vr::SQ => b"SQ",
+            Vr::SS => b"SS",
+            Vr::ST => b"ST",
+            Vr::TM => b"TM",
+            Vr::UI => b"UI",
+            Vr::UL => b"UL",
+            Vr::US => b"US",
+            Vr::UT => b"UT",
+        }
+    }
+
+    /// Whether the element uses the 4-byte length form.
+    fn long_length(self) -> bool {
+        matches!(self, Vr::OB | Vr::OW | Vr::SQ | Vr::UT)
+    }
+
+    /// Byte used to pad values to an even length: NUL for UIDs and binary
+    /// values, a space for text.
+    fn padding(self) -> u8 {
+        match self {
+            Vr::UI | Vr::OB | Vr::OW => 0,
+            _ => b' ',
+        }
+    }
+}
+
+/// Value of a data element; sequence items are encoded with the data set.
+#[derive(Debug, Clone)]
+enum Value {
+    Bytes(Vec<u8>),
+    Items(Vec<DataSet>),
+}
+
+/// A set of data elements, encoded in ascending tag order.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DataSet {
+    elements: BTreeMap<Tag, (Vr, Value)>,
+}
+
+impl DataSet {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a string element; empty values encode as zero-length elements.
+    pub(crate) fn string(&mut self, tag: Tag, vr: Vr, value: &str) -> &mut Self {
+        self.bytes(tag, vr, value.as_bytes().to_vec())
+    }
+
+    pub(crate) fn us(&mut self, tag: Tag, value: u16) -> &mut Self {
+        self.bytes(tag, Vr::US, value.to_le_bytes().to_vec())
+    }
+
+    pub(crate) fn bytes(&mut self, tag: Tag, vr: Vr, value: Vec<u8>) -> &mut Self {
+        self.elements.insert(tag, (vr, Value::Bytes(value)));
+        self
+    }
+
+    /// Sets a sequence; an empty list encodes as an empty sequence.
+    pub(crate) fn sequence(&mut self, tag: Tag, items: Vec<DataSet>) -> &mut Self {
+        self.elements.insert(tag, (Vr::SQ, Value::Items(items)));
+        self
+    }
+
+    /// Encodes the elements, failing if a value is too long for its length field.
+    pub(crate) fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        for (tag, (vr, value)) in &self.elements {
+            match value {
+                Value::Bytes(bytes) => write_element(&mut out, *tag, *vr, bytes)?,
+                Value::Items(items) => write_element(&mut out, *tag, *vr, &encode_items(items)?)?,
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Encodes sequence items with defined lengths.
+
+fn encode_items(items: &[DataSet]) -> Result<Vec<u8>, String> {
+    let mut value = Vec::new();
+    for item in items {
+        let encoded = item.encode()?;
+        let length = u32::try_from(encoded.len())
+            .map_err(|_| "Sequence item exceeds the maximum DICOM length".to_string())?;
+        write_tag(&mut value, ITEM);
+        value.extend_from_slice(&length.to_le_bytes());
+        value.extend_from_slice(&encoded);
+    }
+    Ok(value)
+}
+
+/// Encodes a data set as a DICOM Part 10 file with its file meta information.
+
+pub(crate) fn write_file(
+    sop_class_uid: &str,
+    sop_instance_uid: &str,
+    data_set: &DataSet,
+) -> Result<Vec<u8>, String> {
+    let mut meta = DataSet::new();
+    meta.bytes((0x0002, 0x0001), Vr::OB, vec![0, 1])
+        .string((0x0002, 0x0002), Vr::UI, sop_class_uid)
+        .string((0x0002, 0x0003), Vr::UI, sop_instance_uid)
+        .string((0x0002, 0x0010), Vr::UI, EXPLICIT_VR_LITTLE_ENDIAN)
+        .string((0x0002, 0x0012), Vr::UI, IMPLEMENTATION_CLASS_UID)
+        .string((0x0002, 0x0013), Vr::SH, IMPLEMENTATION_VERSION_NAME);
+    let meta = meta.encode()?;
+
+    let mut out = vec![0; 128];
+    out.extend_from_slice(b"DICM");
+    write_element(&mut out, (0x0002, 0x0000), Vr::UL, &(meta.len() as u32).to_le_bytes())?;
+    out.extend_from_slice(&meta);
+    out.extend_from_slice(&data_set.encode()?);
+    Ok(out)
+}
+
+fn write_tag(out: &mut Vec<u8>, (group, element): Tag) {
+    out.extend_from_slice(&group.to_le_bytes());
+    out.extend_from_slice(&element.to_le_bytes());
+}
+
+/// Writes one element, padding its value to an even length.
+///
+/// Values that don't fit the element's length field are rejected instead of
+/// being truncated.
+
+fn write_element(out: &mut Vec<u8>, tag: Tag, vr: Vr, value: &[u8]) -> Result<(), String> {
+    let padded = value.len() + value.len() % 2;
+    let too_long = || {
+        format!(
+            "Value of element ({:04X},{:04X}) is too long for VR {}",
+            tag.0,
+            tag.1,
+            String::from_utf8_lossy(vr.code())
+        )
+    };
+
+    write_tag(out, tag);
+    out.extend_from_slice(vr.code());
+    if vr.long_length() {
+        let length = u32::try_from(padded).map_err(|_| too_long())?;
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&length.to_le_bytes());
+    } else {
+        let length = u16::try_from(padded).map_err(|_| too_long())?;
+        out.extend_from_slice(&length.to_le_bytes());
+    }
+    out.extend_from_slice(value);
+    if padded > value.len() {
+        out.push(vr.padding());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Reads the top-level elements of an encoded data set.
+    pub(crate) fn read_elements(mut data: &[u8]) -> Vec<(Tag, String, Vec<u8>)> {
+        let mut elements = Vec::new();
+        while data.len() >= 8 {
+            let tag = (
+                u16::from_le_bytes([data[0], data[1]]),
+                u16::from_le_bytes([data[2], data[3]]),
+            );
+            let vr = String::from_utf8_lossy(&data[4..6]).to_string();
+            let (length, header) = if matches!(vr.as_str(), "OB" | "OW" | "SQ" | "UT") {
+                (u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize, 12)
+            } else {
+                (u16::from_le_bytes([data[6], data[7]]) as usize, 8)
+            };
+            elements.push((tag, vr, data[header..header + length].to_vec()));
+            data = &data[header + length..];
+        }
+        elements
+    }
+
+    /// Returns the string value of a top-level element without padding.
+    pub(crate) fn string_value(data: &[u8], tag: Tag) -> Option<String> {
+        read_elements(data)
+            .into_iter()
+            .find(|(element, _, _)| *element == tag)
+            .map(|(_, _, value)| {
+                String::from_utf8_lossy(&value)
+                    .trim_end_matches(['\0', ' '])
+                    .to_string()
+            })
+    }
+
+    #[test]
+    fn test_elements_are_sorted_and_padded() {
+        let mut data_set = DataSet::new();
+        data_set
+            .string((0x0010, 0x0010), Vr::PN, "Doe^Jane")
+            .string((0x0008, 0x0018), Vr::UI, "1.2.3")
+            .us((0x0028, 0x0010), 2);
+        let encoded = data_set.encode().unwrap();
+
+        let elements = read_elements(&encoded);
+        let tags: Vec<Tag> = elements.iter().map(|(tag, _, _)| *tag).collect();
+        assert_eq!(tags, vec![(0x0008, 0x0018), (0x0010, 0x0010), (0x0028, 0x0010)]);
+        assert_eq!(elements[0].2, b"1.2.3\0");
+        assert_eq!(elements[2].2, vec![2, 0]);
+    }
+
+    #[test]
+    fn test_sequence_items_have_defined_length() {
+        let mut item = DataSet::new();
+        item.string((0x0008, 0x0100), Vr::SH, "18748-4");
+        let mut data_set = DataSet::new();
+        data_set.sequence((0x0040, 0xA043), vec![item]);
+        let encoded = data_set.encode().unwrap();
+
+        let elements = read_elements(&encoded);
+        assert_eq!(elements[0].1, "SQ");
+        let value = &elements[0].2;
+        assert_eq!(&value[..4], &[0xFE, 0xFF, 0x00, 0xE0]);
+        let length = u32::from_le_bytes([value[4], value[5], value[6], value[7]]) as usize;
+        assert_eq!(length, value.len() - 8);
+        assert_eq!(string_value(&value[8..], (0x0008, 0x0100)).unwrap(), "18748-4");
+    }
+
+    #[test]
+    fn test_file_meta_information() {
+        let file = write_file("1.2.840.10008.5.1.4.1.1.88.33", "1.2.3", &DataSet::new()).unwrap();
+        assert_eq!(&file[128..132], b"DICM");
+
+        let meta = &file[132..];
+        let elements = read_elements(meta);
+        let group_length = u32::from_le_bytes(elements[0].2[..4].try_into().unwrap()) as usize;
+        assert_eq!(group_length, meta.len() - 12);
+        assert_eq!(
+            string_value(meta, (0x0002, 0x0010)).unwrap(),
+            EXPLICIT_VR_LITTLE_ENDIAN
+        );
+    }
+    #[test]
+    fn test_binary_values_are_padded_with_nul() {
+        let mut data_set = DataSet::new();
+        data_set
+            .bytes((0x6000, 0x3000), Vr::OW, vec![0xFF; 3])
+            .string((0x6000, 0x0022), Vr::LO, "odd");
+        let elements = read_elements(&data_set.encode().unwrap());
+        assert_eq!(elements[0].2, b"odd ");
+        assert_eq!(elements[1].2, vec![0xFF, 0xFF, 0xFF, 0]);
+    }
+
+    #[test]
+    fn test_oversized_value_is_rejected() {
+        let mut data_set = DataSet::new();
+        data_set.string((0x0040, 0xA160), Vr::ST, &"a".repeat(u16::MAX as usize + 1));
+        assert!(data_set.encode().is_err());
+    }
+}
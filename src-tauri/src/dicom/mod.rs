@@ -0,0 +1,23 @@
+//! # DICOM Module
+//! 
+//! This module exports report and analysis results as DICOM objects, including:
+//! - Comprehensive SR documents with the report text and classification results
+//! - Secondary Capture images of the analysed instances
+//! - Storage of the exported objects in the organization's PACS
+//! 
+//! ## Components
+//! 
+//! - [`controller`]: Tauri command handlers for DICOM export
+//! - [`services`]: Construction of SR and Secondary Capture objects
+//! - [`models`]: Exported file structures
+//! 
+//! ## Main Features
+//! 
+//! - Results reference the study, series and instances they were derived from
+//! - Export as DICOM Part 10 files
+//! - STOW-RS upload into the source study
+
+pub mod controller;
+pub mod models;
+pub mod services;
+mod encoder;
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DicomFile {
+    pub filename: String,
+    pub sop_instance_uid: String,
+    #[serde(with = "serde_bytes")]
+    pub data: Vec<u8>,
+}
+
+/// DICOM objects exported for a report.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DicomExportResponse {
+    /// Study the objects were added to
+    pub study_uid: String,
+    /// The SR document followed by any Secondary Capture images
+    pub files: Vec<DicomFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DicomPushResponse {
+    pub study_uid: String,
+    /// SOP Instance UIDs of the stored objects
+    pub stored: Vec<String>,
+}
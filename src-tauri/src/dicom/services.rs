@@ -0,0 +1,734 @@
+use super::encoder::{write_file, DataSet, Vr};
+use super::models;
+use crate::audit::models::AuditAction;
+use crate::audit::services::record_audit;
+use crate::auth::session::models::AuthenticatedUser;
+use crate::dicomweb::services::{require_dicomweb_settings, DicomwebClient};
+use crate::patients::models::{IdentifierKind, PatientIdentifier};
+use crate::reports::models::{
+    CreateReportResponse, DicomReference, ImageRecord, ReportDocumentInfo, ReportStatus,
+};
+use crate::reports::services::{ensure_status, load_document_info, load_visible_report};
+use crate::error::AppError;
+use std::collections::BTreeMap;
+
+use scanlytics_db::{Surreal, Any, Datetime, DbConnection, Id};
+
+const COMPREHENSIVE_SR: &str = "1.2.840.10008.5.1.4.1.1.88.33";
+const SECONDARY_CAPTURE: &str = "1.2.840.10008.5.1.4.1.1.7";
+const MANUFACTURER: &str = "Scanlytics";
+const SR_SERIES_NUMBER: &str = "901";
+const SC_SERIES_NUMBER: &str = "902";
+
+/// Coded concept as (code value, coding scheme, code meaning).
+type Code = (&'static str, &'static str, &'static str);
+
+const DIAGNOSTIC_IMAGING_REPORT: Code = ("18748-4", "LN", "Diagnostic Imaging Report");
+const INDICATION: Code = ("121109", "DCM", "Indications for Procedure");
+const TECHNIQUE: Code = ("121065", "DCM", "Procedure Description");
+const FINDING: Code = ("121071", "DCM", "Finding");
+const IMPRESSION: Code = ("121073", "DCM", "Impression");
+const SOURCE_IMAGE: Code = ("121112", "DCM", "Source of Measurement");
+const SOURCE_FOR_PROCESSING: Code =
+    ("121322", "DCM", "Source image for image processing operation");
+const AI_CLASSIFICATION: Code = ("classification", "99SCANLYTICS", "AI classification");
+const AI_CONFIDENCE: Code = ("confidence", "99SCANLYTICS", "AI classification confidence");
+const PERCENT: Code = ("%", "UCUM", "percent");
+
+
+/// Exports a final report and its classification results as DICOM files.
+///
+/// The report is exported as a Comprehensive SR document in the study its
+/// images were retrieved from. With `secondary_capture`, each classified
+/// image is also exported as a Secondary Capture image whose overlay is
+/// labelled with the classification.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `secondary_capture` - Whether to include Secondary Capture images
+/// * `user` - Authenticated user exporting the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(DicomExportResponse)` - The study UID and the DICOM files
+/// * `Err(AppError)` - Error message if the export fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report isn't visible to the user or isn't final
+/// * None of the report's images were retrieved from a PACS
+/// * An image file can't be read
+/// * Database operations fail
+
+pub async fn export_report_dicom_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    secondary_capture: bool,
+    user: &AuthenticatedUser,
+) -> Result<models::DicomExportResponse, AppError> {
+    let (report, export) = build_report_objects(db, &report_id, secondary_capture, user).await?;
+    record_audit(db, user, AuditAction::Share, &report.id, Some(&report.patient), None).await?;
+    Ok(export)
+}
+
+/// Stores a final report and its classification results in the PACS.
+///
+/// The objects of [`export_report_dicom_service`] are sent with STOW-RS to
+/// the source study. The database lock is released during the upload.
+///
+/// # Arguments
+///
+/// * `db_connection` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `secondary_capture` - Whether to include Secondary Capture images
+/// * `user` - Authenticated user sending the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(DicomPushResponse)` - The study and the stored instances
+/// * `Err(AppError)` - Error message if the export or upload fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * No PACS is configured for the organization
+/// * The report can't be exported
+/// * The PACS is unreachable or doesn't store every object
+
+pub async fn push_report_dicom_service(
+    db_connection: &DbConnection,
+    report_id: String,
+    secondary_capture: bool,
+    user: &AuthenticatedUser,
+) -> Result<models::DicomPushResponse, AppError> {
+    let (client, report, export) = {
+        let db = db_connection.get().lock().await;
+        let client = DicomwebClient::new(&require_dicomweb_settings(&db, user).await?)?;
+        let (report, export) = build_report_objects(&db, &report_id, secondary_capture, user).await?;
+        (client, report, export)
+    };
+
+    let instances: Vec<Vec<u8>> = export.files.iter().map(|file| file.data.clone()).collect();
+    client.store_instances(&export.study_uid, &instances).await?;
+
+    let db = db_connection.get().lock().await;
+    record_audit(&db, user, AuditAction::Share, &report.id, Some(&report.patient), None).await?;
+
+    Ok(models::DicomPushResponse {
+        study_uid: export.study_uid,
+        stored: export.files.into_iter().map(|file| file.sop_instance_uid).collect(),
+    })
+}
+
+async fn build_report_objects(
+    db: &Surreal<Any>,
+    report_id: &str,
+    secondary_capture: bool,
+    user: &AuthenticatedUser,
+) -> Result<(CreateReportResponse, models::DicomExportResponse), AppError> {
+    let report = load_visible_report(db, report_id, user).await?;
+    // An amended report has been superseded by its amendment, which is the
+    // version the PACS should receive.
+    ensure_status(&report, &[ReportStatus::Final], "exported")?;
+    let info = load_document_info(db, &report.id).await?;
+
+    let images: Vec<ImageRecord> = db
+        .query(
+            "SELECT * FROM Image
+            WHERE id INSIDE (SELECT VALUE in FROM Images_Reports_Join WHERE out = $report)
+            ORDER BY name",
+        )
+        .bind(("report", report.id.clone()))
+        .await?
+        .take(0)?;
+
+    let organization: Option<String> = match user.organization.clone() {
+        Some(organization) => db
+            .query("SELECT VALUE name FROM ONLY $organization")
+            .bind(("organization", organization))
+            .await?
+            .take(0)?,
+        None => None,
+    };
+
+    let context = ExportContext::new(&info, &images)?;
+    let prefix = format!("report-{}-v{}", report.id.id.to_raw(), report.version);
+
+    let mut files = vec![structured_report(
+        &context,
+        &report,
+        &info,
+        &images,
+        organization.as_deref().unwrap_or(MANUFACTURER),
+        &prefix,
+    )?];
+    if secondary_capture {
+        let series_uid = new_uid();
+        let classified = images
+            .iter()
+            .filter(|image| image.dicom.is_some() && image.classification.is_some());
+        for (index, image) in classified.enumerate() {
+            files.push(secondary_capture_image(&context, &series_uid, image, index + 1, &prefix)?);
+        }
+    }
+
+    let export = models::DicomExportResponse {
+        study_uid: context.study_uid,
+        files,
+    };
+    Ok((report, export))
+}
+
+/// Patient and study attributes shared by all exported objects.
+
+struct ExportContext {
+    study_uid: String,
+    patient_id: String,
+    patient_name: String,
+    birth_date: String,
+    sex: &'static str,
+    date: String,
+    time: String,
+}
+
+impl ExportContext {
+    /// Uses the study and patient identification of the first image that was
+    /// retrieved from a PACS, so the objects match the source study. Without
+    /// a PatientID from the PACS, the patient's DICOM PatientID or MRN is used.
+    fn new(info: &ReportDocumentInfo, images: &[ImageRecord]) -> Result<Self, AppError> {
+        let source = images
+            .iter()
+            .find_map(|image| image.dicom.as_ref())
+            .ok_or_else(|| {
+                AppError::OperationFailed(
+                    "None of the report's images were retrieved from a PACS".to_string(),
+                )
+            })?;
+        let patient = &info.patient;
+        let now = Datetime::default();
+
+        Ok(ExportContext {
+            study_uid: source.study_uid.clone(),
+            patient_id: source
+                .patient_id
+                .clone()
+                .or_else(|| dicom_patient_id(&patient.identifiers))
+                .unwrap_or_else(|| patient.id.id.to_raw()),
+            patient_name: source
+                .patient_name
+                .clone()
+                .unwrap_or_else(|| person_name(&patient.name)),
+            birth_date: patient.date_of_birth.0.format("%Y%m%d").to_string(),
+            sex: match patient.gender.as_str() {
+                "male" => "M",
+                "female" => "F",
+                _ => "O",
+            },
+            date: now.0.format("%Y%m%d").to_string(),
+            time: now.0.format("%H%M%S").to_string(),
+        })
+    }
+
+    fn data_set(
+        &self,
+        sop_class_uid: &str,
+        sop_instance_uid: &str,
+        modality: &str,
+        series_uid: &str,
+        series_number: &str,
+        instance_number: usize,
+    ) -> DataSet {
+        let mut data_set = DataSet::new();
+        data_set
+            .string((0x0008, 0x0005), Vr::CS, "ISO_IR 192")
+            .string((0x0008, 0x0012), Vr::DA, &self.date)
+            .string((0x0008, 0x0013), Vr::TM, &self.time)
+            .string((0x0008, 0x0016), Vr::UI, sop_class_uid)
+            .string((0x0008, 0x0018), Vr::UI, sop_instance_uid)
+            .string((0x0008, 0x0020), Vr::DA, "")
+            .string((0x0008, 0x0023), Vr::DA, &self.date)
+            .string((0x0008, 0x0030), Vr::TM, "")
+            .string((0x0008, 0x0033), Vr::TM, &self.time)
+            .string((0x0008, 0x0050), Vr::SH, "")
+            .string((0x0008, 0x0060), Vr::CS, modality)
+            .string((0x0008, 0x0070), Vr::LO, MANUFACTURER)
+            .string((0x0008, 0x0090), Vr::PN, "")
+            .string((0x0010, 0x0010), Vr::PN, &self.patient_name)
+            .string((0x0010, 0x0020), Vr::LO, &self.patient_id)
+            .string((0x0010, 0x0030), Vr::DA, &self.birth_date)
+            .string((0x0010, 0x0040), Vr::CS, self.sex)
+            .string((0x0020, 0x000D), Vr::UI, &self.study_uid)
+            .string((0x0020, 0x000E), Vr::UI, series_uid)
+            .string((0x0020, 0x0010), Vr::SH, "")
+            .string((0x0020, 0x0011), Vr::IS, series_number)
+            .string((0x0020, 0x0013), Vr::IS, &instance_number.to_string());
+        data_set
+    }
+}
+
+/// Builds the Comprehensive SR document of a report.
+///
+/// The document holds the report sections, or the report text when the
+/// report has none, and one classification per analysed PACS image that
+/// references the image it was inferred from.
+
+fn structured_report(
+    context: &ExportContext,
+    report: &CreateReportResponse,
+    info: &ReportDocumentInfo,
+    images: &[ImageRecord],
+    organization: &str,
+    prefix: &str,
+) -> Result<models::DicomFile, AppError> {
+    let sop_instance_uid = new_uid();
+    let mut data_set = context.data_set(
+        COMPREHENSIVE_SR,
+        &sop_instance_uid,
+        "SR",
+        &new_uid(),
+        SR_SERIES_NUMBER,
+        1,
+    );
+    data_set.string((0x0008, 0x103E), Vr::LO, "AI results");
+
+    let mut content = Vec::new();
+    match &report.sections {
+        Some(sections) => {
+            for (concept, text) in [
+                (INDICATION, &sections.indication),
+                (TECHNIQUE, &sections.technique),
+                (FINDING, &sections.findings),
+                (IMPRESSION, &sections.assessment),
+            ] {
+                if !text.trim().is_empty() {
+                    content.push(text_item("CONTAINS", concept, text));
+                }
+            }
+        }
+        None => content.push(text_item("CONTAINS", FINDING, &report.report_text)),
+    }
+
+    for image in images {
+        let (Some(reference), Some(classification)) = (&image.dicom, &image.classification) else {
+            continue;
+        };
+        let mut confidence = content_item("HAS PROPERTIES", "NUM", AI_CONFIDENCE);
+        let mut measurement = DataSet::new();
+        measurement
+            .string((0x0040, 0xA30A), Vr::DS, &format!("{:.1}", classification.confidence * 100.0))
+            .sequence((0x0040, 0x08EA), vec![code(PERCENT)]);
+        confidence.sequence((0x0040, 0xA300), vec![measurement]);
+
+        let mut source = content_item("INFERRED FROM", "IMAGE", SOURCE_IMAGE);
+        source.sequence((0x0008, 0x1199), vec![sop_reference(reference)]);
+
+        let mut item = text_item("CONTAINS", AI_CLASSIFICATION, &classification.image_type);
+        item.sequence((0x0040, 0xA730), vec![confidence, source]);
+        content.push(item);
+    }
+
+    data_set
+        .string((0x0040, 0xA040), Vr::CS, "CONTAINER")
+        .sequence((0x0040, 0xA043), vec![code(DIAGNOSTIC_IMAGING_REPORT)])
+        .string((0x0040, 0xA050), Vr::CS, "SEPARATE")
+        .sequence((0x0008, 0x1111), Vec::new())
+        .sequence((0x0040, 0xA372), Vec::new())
+        .sequence((0x0040, 0xA375), evidence(images))
+        .string((0x0040, 0xA491), Vr::CS, "COMPLETE")
+        .string((0x0040, 0xA496), Vr::CS, "FINAL")
+        .sequence((0x0040, 0xA730), content);
+
+    match (&info.signer, &report.signed_at) {
+        (Some(signer), Some(signed_at)) => {
+            let mut observer = DataSet::new();
+            observer
+                .string((0x0040, 0xA027), Vr::LO, organization)
+                .string((0x0040, 0xA030), Vr::DT, &signed_at.0.format("%Y%m%d%H%M%S").to_string())
+                .string((0x0040, 0xA075), Vr::PN, &person_name(signer))
+                .sequence((0x0040, 0xA088), Vec::new());
+            data_set
+                .string((0x0040, 0xA493), Vr::CS, "VERIFIED")
+                .sequence((0x0040, 0xA073), vec![observer]);
+        }
+        _ => {
+            data_set.string((0x0040, 0xA493), Vr::CS, "UNVERIFIED");
+        }
+    }
+
+    Ok(models::DicomFile {
+        filename: format!("{}-sr.dcm", prefix),
+        data: write_file(COMPREHENSIVE_SR, &sop_instance_uid, &data_set)
+            .map_err(AppError::OperationFailed)?,
+        sop_instance_uid,
+    })
+}
+
+/// Builds a Secondary Capture image of an analysed image.
+///
+/// The pixel data is the stored image; an overlay outlines the analysed
+/// area and carries the classification as its label.
+
+fn secondary_capture_image(
+    context: &ExportContext,
+    series_uid: &str,
+    image: &ImageRecord,
+    instance_number: usize,
+    prefix: &str,
+) -> Result<models::DicomFile, AppError> {
+    let (Some(reference), Some(classification)) = (&image.dicom, &image.classification) else {
+        return Err(AppError::OperationFailed(format!(
+            "Image {} has no analysis result",
+            image.name
+        )));
+    };
+    let pixels = image::open(&image.path)
+        .map_err(|e| AppError::OperationFailed(format!("Failed to load image {}: {}", image.name, e)))?
+        .to_rgb8();
+    let (Ok(columns), Ok(rows)) = (u16::try_from(pixels.width()), u16::try_from(pixels.height())) else {
+        return Err(AppError::OperationFailed(format!(
+            "Image {} is too large for a DICOM image",
+            image.name
+        )));
+    };
+
+    let label = format!(
+        "{} ({:.0}% confidence)",
+        classification.image_type,
+        classification.confidence * 100.0
+    );
+    let sop_instance_uid = new_uid();
+    let mut data_set = context.data_set(
+        SECONDARY_CAPTURE,
+        &sop_instance_uid,
+        "OT",
+        series_uid,
+        SC_SERIES_NUMBER,
+        instance_number,
+    );
+
+    let mut source = sop_reference(reference);
+    source.sequence((0x0040, 0xA170), vec![code(SOURCE_FOR_PROCESSING)]);
+
+    data_set
+        .string((0x0008, 0x0064), Vr::CS, "WSD")
+        .string((0x0008, 0x103E), Vr::LO, "AI results")
+        .string((0x0008, 0x2111), Vr::ST, &format!("AI classification: {}", label))
+        .sequence((0x0008, 0x2112), vec![source])
+        .string((0x0020, 0x0020), Vr::CS, "")
+        .us((0x0028, 0x0002), 3)
+        .string((0x0028, 0x0004), Vr::CS, "RGB")
+        .us((0x0028, 0x0006), 0)
+        .us((0x0028, 0x0010), rows)
+        .us((0x0028, 0x0011), columns)
+        .us((0x0028, 0x0100), 8)
+        .us((0x0028, 0x0101), 8)
+        .us((0x0028, 0x0102), 7)
+        .us((0x0028, 0x0103), 0)
+        .string((0x0028, 0x0301), Vr::CS, "NO")
+        .us((0x6000, 0x0010), rows)
+        .us((0x6000, 0x0011), columns)
+        .string((0x6000, 0x0022), Vr::LO, &label)
+        .string((0x6000, 0x0040), Vr::CS, "G")
+        .bytes((0x6000, 0x0050), Vr::SS, [1i16.to_le_bytes(), 1i16.to_le_bytes()].concat())
+        .us((0x6000, 0x0100), 1)
+        .us((0x6000, 0x0102), 0)
+        .string((0x6000, 0x1500), Vr::LO, &classification.image_type)
+        .bytes((0x6000, 0x3000), Vr::OW, outline(columns as usize, rows as usize))
+        .bytes((0x7FE0, 0x0010), Vr::OB, pixels.into_raw());
+
+    Ok(models::DicomFile {
+        filename: format!("{}-sc{}.dcm", prefix, instance_number),
+        data: write_file(SECONDARY_CAPTURE, &sop_instance_uid, &data_set)
+            .map_err(AppError::OperationFailed)?,
+        sop_instance_uid,
+    })
+}
+
+/// Overlay bitmap outlining the image with a border, packed 1 bit per pixel.
+
+fn outline(columns: usize, rows: usize) -> Vec<u8> {
+    let width = (columns / 100).clamp(1, 8);
+    let mut bits = vec![0u8; (columns * rows).div_ceil(8)];
+    for row in 0..rows {
+        for column in 0..columns {
+            let border = row < width
+                || column < width
+                || row >= rows.saturating_sub(width)
+                || column >= columns.saturating_sub(width);
+            if border {
+                let index = row * columns + column;
+                bits[index / 8] |= 1 << (index % 8);
+            }
+        }
+    }
+    bits
+}
+
+/// Current Requested Procedure Evidence: every referenced PACS instance,
+/// grouped by study and series.
+
+fn evidence(images: &[ImageRecord]) -> Vec<DataSet> {
+    let mut studies: BTreeMap<&str, BTreeMap<&str, Vec<&DicomReference>>> = BTreeMap::new();
+    for reference in images.iter().filter_map(|image| image.dicom.as_ref()) {
+        studies
+            .entry(&reference.study_uid)
+            .or_default()
+            .entry(&reference.series_uid)
+            .or_default()
+            .push(reference);
+    }
+
+    studies
+        .into_iter()
+        .map(|(study_uid, series)| {
+            let series = series
+                .into_iter()
+                .map(|(series_uid, instances)| {
+                    let mut item = DataSet::new();
+                    item.string((0x0020, 0x000E), Vr::UI, series_uid).sequence(
+                        (0x0008, 0x1199),
+                        instances.into_iter().map(sop_reference).collect(),
+                    );
+                    item
+                })
+                .collect();
+            let mut item = DataSet::new();
+            item.string((0x0020, 0x000D), Vr::UI, study_uid)
+                .sequence((0x0008, 0x1115), series);
+            item
+        })
+        .collect()
+}
+
+fn sop_reference(reference: &DicomReference) -> DataSet {
+    let mut item = DataSet::new();
+    item.string((0x0008, 0x1150), Vr::UI, &reference.sop_class_uid)
+        .string((0x0008, 0x1155), Vr::UI, &reference.sop_instance_uid);
+    item
+}
+
+fn code((value, scheme, meaning): Code) -> DataSet {
+    let mut item = DataSet::new();
+    item.string((0x0008, 0x0100), Vr::SH, value)
+        .string((0x0008, 0x0102), Vr::SH, scheme)
+        .string((0x0008, 0x0104), Vr::LO, meaning);
+    item
+}
+
+fn content_item(relationship: &str, value_type: &str, concept: Code) -> DataSet {
+    let mut item = DataSet::new();
+    item.string((0x0040, 0xA010), Vr::CS, relationship)
+        .string((0x0040, 0xA040), Vr::CS, value_type)
+        .sequence((0x0040, 0xA043), vec![code(concept)]);
+    item
+}
+
+fn text_item(relationship: &str, concept: Code, text: &str) -> DataSet {
+    let mut item = content_item(relationship, "TEXT", concept);
+    item.string((0x0040, 0xA160), Vr::UT, text);
+    item
+}
+
+/// Picks the identifier to use as DICOM PatientID: a PACS patient ID, or
+/// else a medical record number.
+
+fn dicom_patient_id(identifiers: &[PatientIdentifier]) -> Option<String> {
+    [IdentifierKind::DicomPatientId, IdentifierKind::Mrn]
+        .iter()
+        .find_map(|kind| identifiers.iter().find(|identifier| identifier.kind == *kind))
+        .map(|identifier| identifier.value.clone())
+}
+
+/// Formats a name as a DICOM person name, `Family^Given`.
+
+fn person_name(name: &str) -> String {
+    match name.trim().rsplit_once(' ') {
+        Some((given, family)) => format!("{}^{}", family, given),
+        None => name.trim().to_string(),
+    }
+}
+
+/// Generates a UID under the `2.25` root from a random UUID.
+
+fn new_uid() -> String {
+    let hex: String = Id::uuid()
+        .to_raw()
+        .chars()
+        .filter(char::is_ascii_hexdigit)
+        .collect();
+    let value = u128::from_str_radix(&hex, 16).unwrap_or_default();
+    format!("2.25.{}", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dicom::encoder::tests::{read_elements, string_value};
+    use crate::dicom::encoder::Tag;
+    use crate::reports::models::{ImageClassification, PatientDemographics, ReportSections};
+    use image::RgbImage;
+    use scanlytics_db::Thing;
+
+    fn final_report() -> (CreateReportResponse, ReportDocumentInfo) {
+        let patient = Thing::from(("Patient", "p1"));
+        let report = CreateReportResponse {
+            id: Thing::from(("Report", "r1")),
+            patient: patient.clone(),
+            user_owner: Thing::from(("User", "u1")),
+            report_text: "Unremarkable chest.".to_string(),
+            body_part: Some("thorax".to_string()),
+            sections: Some(ReportSections {
+                indication: "Cough".to_string(),
+                technique: "Radiograph of the thorax, 1 image.".to_string(),
+                findings: "No consolidation.".to_string(),
+                assessment: "Unremarkable chest.".to_string(),
+            }),
+            status: ReportStatus::Final,
+            version: 1,
+            signed_by: Some(Thing::from(("User", "u1"))),
+            signed_at: Some(Datetime::try_from("2024-03-15T10:30:00Z").unwrap()),
+            amends: None,
+            superseded_by: None,
+            created_at: Datetime::default(),
+            updated_at: Datetime::default(),
+            deleted_at: None,
+        };
+        let info = ReportDocumentInfo {
+            author: "Jane Doe".to_string(),
+            signer: Some("Jane Doe".to_string()),
+            patient: PatientDemographics {
+                id: patient,
+                name: "Max Muster".to_string(),
+                date_of_birth: Datetime::try_from("1975-06-01T00:00:00Z").unwrap(),
+                gender: "male".to_string(),
+                contact_number: None,
+                address: None,
+                identifiers: Vec::new(),
+            },
+        };
+        (report, info)
+    }
+
+    fn pacs_image(path: &str) -> ImageRecord {
+        ImageRecord {
+            id: Thing::from(("Image", "i1")),
+            name: "1.2.3.4.1.png".to_string(),
+            path: path.to_string(),
+            patient: Thing::from(("Patient", "p1")),
+            user: Thing::from(("User", "u1")),
+            file_type: "png".to_string(),
+            modal_type: "xray".to_string(),
+            dicom: Some(DicomReference {
+                study_uid: "1.2.3".to_string(),
+                series_uid: "1.2.3.4".to_string(),
+                sop_instance_uid: "1.2.3.4.1".to_string(),
+                sop_class_uid: "1.2.840.10008.5.1.4.1.1.1.1".to_string(),
+                patient_id: Some("MRN-1".to_string()),
+                patient_name: Some("Muster^Max".to_string()),
+            }),
+            classification: Some(ImageClassification {
+                image_type: "thorax".to_string(),
+                confidence: 0.93,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_structured_report_references_source_study() {
+        let (report, info) = final_report();
+        let images = vec![pacs_image("unused.png")];
+        let context = ExportContext::new(&info, &images).unwrap();
+        let file = structured_report(&context, &report, &info, &images, "General Hospital", "report")
+            .unwrap();
+
+        assert_eq!(&file.data[128..132], b"DICM");
+        let data_set = &file.data[132..];
+
+        assert_eq!(string_value(data_set, (0x0008, 0x0016)).unwrap(), COMPREHENSIVE_SR);
+        assert_eq!(string_value(data_set, (0x0008, 0x0018)).unwrap(), file.sop_instance_uid);
+        assert_eq!(string_value(data_set, (0x0020, 0x000D)).unwrap(), "1.2.3");
+        assert_eq!(string_value(data_set, (0x0010, 0x0020)).unwrap(), "MRN-1");
+        assert_eq!(string_value(data_set, (0x0040, 0xA493)).unwrap(), "VERIFIED");
+
+        let content = read_elements(data_set)
+            .into_iter()
+            .find(|(tag, _, _)| *tag == (0x0040, 0xA730))
+            .unwrap()
+            .2;
+        let text = String::from_utf8_lossy(&content);
+        assert!(text.contains("No consolidation."));
+        assert!(text.contains("AI classification"));
+        assert!(text.contains("thorax"));
+        assert!(text.contains("93.0"));
+        assert!(text.contains("1.2.3.4.1"));
+    }
+
+    #[test]
+    fn test_secondary_capture_pixels_and_overlay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("image.png");
+        RgbImage::from_pixel(4, 3, image::Rgb([10, 20, 30])).save(&path).unwrap();
+        let image = pacs_image(path.to_str().unwrap());
+
+        let (_, info) = final_report();
+        let context = ExportContext::new(&info, std::slice::from_ref(&image)).unwrap();
+        let file = secondary_capture_image(&context, "1.2.9", &image, 1, "report").unwrap();
+        let elements = read_elements(&file.data[132..]);
+        let value = |tag: Tag| {
+            elements
+                .iter()
+                .find(|(element, _, _)| *element == tag)
+                .map(|(_, _, value)| value.clone())
+                .unwrap()
+        };
+
+        assert_eq!(value((0x0028, 0x0010)), vec![3, 0]);
+        assert_eq!(value((0x0028, 0x0011)), vec![4, 0]);
+        assert_eq!(&value((0x7FE0, 0x0010))[..3], &[10, 20, 30]);
+        assert_eq!(value((0x6000, 0x3000)).len(), 2);
+        assert!(String::from_utf8_lossy(&value((0x6000, 0x0022))).starts_with("thorax (93% confidence)"));
+    }
+
+    #[test]
+    fn test_export_requires_pacs_images() {
+        let (_, info) = final_report();
+        let mut image = pacs_image("unused.png");
+        image.dicom = None;
+        assert!(ExportContext::new(&info, &[image]).is_err());
+    }
+
+    #[test]
+    fn test_outline_marks_border() {
+        let bits = outline(4, 3);
+        let set = |index: usize| bits[index / 8] & (1 << (index % 8)) != 0;
+        assert!(set(0) && set(3) && set(4) && set(11));
+        assert!(!set(5) && !set(6));
+    }
+
+    #[test]
+    fn test_odd_length_outline_is_padded_with_nul() {
+        let bits = outline(4, 5);
+        assert_eq!(bits.len(), 3);
+
+        let mut data_set = DataSet::new();
+        data_set.bytes((0x6000, 0x3000), Vr::OW, bits.clone());
+        let elements = read_elements(&data_set.encode().unwrap());
+        assert_eq!(elements[0].2.len(), 4);
+        assert_eq!(&elements[0].2[..3], bits.as_slice());
+        assert_eq!(elements[0].2[3], 0);
+    }
+
+    #[test]
+    fn test_new_uid_is_valid() {
+        let uid = new_uid();
+        assert!(uid.starts_with("2.25."));
+        assert!(uid.len() <= 64);
+        assert_ne!(uid, "2.25.0");
+        assert_ne!(uid, new_uid());
+    }
+}
@@ -0,0 +1,146 @@
+use super::models;
+use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+
+/// Searches the organization's PACS for studies.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `request` - JSON string containing patient, date range and modality criteria
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<StudySummary>)` - Matching studies
+/// * `Err(AppError)` - Error message if the search fails
+
+#[tauri::command]
+pub async fn search_dicomweb_studies(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    request: String,
+) -> Result<Vec<models::StudySummary>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let request: models::StudySearchRequest = serde_json::from_str(&request)
+            .map_err(|e| AppError::invalid_input("study search", e))?;
+
+        services::search_studies_service(db_connection, request, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Lists the series of a study in the organization's PACS.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `study_uid` - Study Instance UID
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<SeriesSummary>)` - Series of the study
+/// * `Err(AppError)` - Error message if the search fails
+
+#[tauri::command]
+pub async fn search_dicomweb_series(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    study_uid: String,
+) -> Result<Vec<models::SeriesSummary>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        services::search_series_service(db_connection, study_uid, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Pulls a series from the PACS into the patient's images and analyses it.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `request` - JSON string containing the patient, study, series and model
+/// * `app_handle` - Tauri application handle
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PullSeriesResponse)` - Stored images and the analysis results
+/// * `Err(AppError)` - Error message if retrieval or analysis fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * No PACS is configured for the organization
+/// * The patient isn't accessible to the user
+/// * The series modality isn't supported
+/// * The PACS is unreachable or the analysis fails
+
+#[tauri::command]
+pub async fn pull_dicomweb_series(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    request: String,
+    app_handle: tauri::AppHandle,
+) -> Result<models::PullSeriesResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let request: models::PullSeriesRequest = serde_json::from_str(&request)
+            .map_err(|e| AppError::invalid_input("series request", e))?;
+
+        services::pull_series_service(db_connection, request, &user, app_handle)
+            .await
+    })
+    .await
+}
+
+
+/// Sets the PACS endpoint of the admin's organization.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `settings` - JSON string containing the DICOMweb base URL, token and timeout
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(DicomwebSettings)` - The stored settings
+/// * `Err(AppError)` - Error message if the update fails
+///
+/// Only admins can call this command.
+
+#[tauri::command]
+pub async fn update_dicomweb_settings(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    settings: String,
+) -> Result<models::DicomwebSettings, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let settings: models::DicomwebSettings = serde_json::from_str(&settings)
+            .map_err(|e| AppError::invalid_input("DICOMweb settings", e))?;
+
+        services::update_dicomweb_settings_service(&db, settings, &user)
+            .await
+    })
+    .await
+}
@@ -0,0 +1,22 @@
+//! # DICOMweb Module
+//! 
+//! This module queries and retrieves images from a PACS over DICOMweb, including:
+//! - QIDO-RS search for studies and series
+//! - WADO-RS retrieval of rendered instances
+//! - Storage of retrieved instances as `Image` records
+//! 
+//! ## Components
+//! 
+//! - [`controller`]: Tauri command handlers for PACS queries and retrieval
+//! - [`services`]: DICOMweb client and import logic
+//! - [`models`]: DICOMweb settings, search and retrieval structures
+//! 
+//! ## Main Features
+//! 
+//! - Study search by patient, date range and modality
+//! - Pulling a series into `Image` records and the analysis pipeline
+//! - Per-organization PACS endpoint settings
+
+pub mod controller;
+pub mod models;
+pub mod services;
@@ -0,0 +1,86 @@
+use crate::image_analysis::image_processing::models::AnalysisResponse;
+use serde::{Deserialize, Serialize};
+use scanlytics_db::Thing;
+
+/// DICOMweb endpoint of an organization's PACS.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DicomwebSettings {
+    /// Base URL of the DICOMweb service, e.g. `https://pacs.example.org/dicom-web`
+    pub base_url: String,
+    /// Bearer token sent with every request. It is kept in the system keyring
+    /// and never serialized, so it is neither stored in the database nor
+    /// returned to the frontend.
+    #[serde(default, skip_serializing)]
+    pub auth_token: Option<String>,
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrganizationDicomweb {
+    pub dicomweb: Option<DicomwebSettings>,
+}
+
+/// Study search criteria; all criteria are optional.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StudySearchRequest {
+    /// Patient ID as known to the PACS
+    pub patient_id: Option<String>,
+    /// Patient name, may contain `*` wildcards
+    pub patient_name: Option<String>,
+    /// Earliest study date as `YYYY-MM-DD`
+    pub date_from: Option<String>,
+    /// Latest study date as `YYYY-MM-DD`
+    pub date_to: Option<String>,
+    /// Modality code such as `CR`, `CT` or `MR`
+    pub modality: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StudySummary {
+    pub study_uid: String,
+    pub patient_id: Option<String>,
+    pub patient_name: Option<String>,
+    /// Study date as `YYYY-MM-DD`
+    pub study_date: Option<String>,
+    pub description: Option<String>,
+    pub modalities: Vec<String>,
+    pub series_count: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SeriesSummary {
+    pub series_uid: String,
+    pub modality: Option<String>,
+    pub description: Option<String>,
+    pub instance_count: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PullSeriesRequest {
+    /// Scanlytics patient the images are stored for
+    pub patient_id: String,
+    pub study_uid: String,
+    pub series_uid: String,
+    /// ML model used to analyse the retrieved images
+    pub model_name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PulledImage {
+    pub id: Thing,
+    pub name: String,
+    pub path: String,
+    pub modal_type: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PullSeriesResponse {
+    pub images: Vec<PulledImage>,
+    pub analysis: AnalysisResponse,
+}
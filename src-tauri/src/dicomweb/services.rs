@@ -0,0 +1,1125 @@
+use super::models;
+use crate::audit::models::AuditAction;
+use crate::audit::services::{audit_changes, audit_entry};
+use crate::auth::session::models::AuthenticatedUser;
+use crate::image_analysis::image_processing::models::ImageData;
+use crate::image_analysis::image_processing::services::process_images_service;
+use crate::patients::models::{IdentifierKind, PatientIdentifier};
+use crate::patients::services::{
+    can_access_patient, identifiers_audit, new_identifiers, CREATE_IDENTIFIERS,
+};
+use crate::reports::models::{DicomReference, ImageRecord};
+use crate::reports::services::store_classifications;
+use crate::error::AppError;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(not(test))]
+use keyring::{Entry, Error as KeyringError};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::StatusCode;
+use scanlytics_db::{Surreal, Any, DbConnection, Id, Thing};
+use serde_json::Value;
+use tauri::Manager;
+
+#[cfg(not(test))]
+const TOKEN_SERVICE_NAME: &str = "com.scanlytics.dev.dicomweb";
+const DICOM_JSON: &str = "application/dicom+json";
+const STUDY_FIELDS: [&str; 3] = ["StudyDescription", "ModalitiesInStudy", "NumberOfStudyRelatedSeries"];
+
+const STUDY_INSTANCE_UID: &str = "0020000D";
+const SERIES_INSTANCE_UID: &str = "0020000E";
+const SOP_CLASS_UID: &str = "00080016";
+const SOP_INSTANCE_UID: &str = "00080018";
+const PATIENT_NAME: &str = "00100010";
+const PATIENT_ID: &str = "00100020";
+const STUDY_DATE: &str = "00080020";
+const STUDY_DESCRIPTION: &str = "00081030";
+const MODALITIES_IN_STUDY: &str = "00080061";
+const MODALITY: &str = "00080060";
+const SERIES_DESCRIPTION: &str = "0008103E";
+const STUDY_SERIES_COUNT: &str = "00201206";
+const SERIES_INSTANCE_COUNT: &str = "00201209";
+const INSTANCE_NUMBER: &str = "00200013";
+
+
+/// Searches the organization's PACS for studies.
+///
+/// The database lock is only held while loading the PACS settings, not
+/// while waiting for the PACS.
+///
+/// # Arguments
+///
+/// * `db_connection` - Database connection
+/// * `request` - Patient, date range and modality criteria
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<StudySummary>)` - Matching studies
+/// * `Err(AppError)` - Error message if the search fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * No PACS is configured for the organization
+/// * A date isn't a valid `YYYY-MM-DD` date
+/// * The PACS is unreachable or answers with an error
+
+pub async fn search_studies_service(
+    db_connection: &DbConnection,
+    request: models::StudySearchRequest,
+    user: &AuthenticatedUser,
+) -> Result<Vec<models::StudySummary>, AppError> {
+    let client = {
+        let db = db_connection.get().lock().await;
+        DicomwebClient::new(&require_dicomweb_settings(&db, user).await?)?
+    };
+    client.search_studies(&request).await
+}
+
+/// Lists the series of a study in the organization's PACS.
+///
+/// # Arguments
+///
+/// * `db_connection` - Database connection
+/// * `study_uid` - Study Instance UID
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<SeriesSummary>)` - Series of the study
+/// * `Err(AppError)` - Error message if the search fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * No PACS is configured for the organization
+/// * The PACS is unreachable or answers with an error
+
+pub async fn search_series_service(
+    db_connection: &DbConnection,
+    study_uid: String,
+    user: &AuthenticatedUser,
+) -> Result<Vec<models::SeriesSummary>, AppError> {
+    let client = {
+        let db = db_connection.get().lock().await;
+        DicomwebClient::new(&require_dicomweb_settings(&db, user).await?)?
+    };
+    client.search_series(&study_uid).await
+}
+
+/// Pulls a series from the PACS into `Image` records and analyses it.
+///
+/// Instances are retrieved as rendered PNGs through WADO-RS, stored in
+/// `saved_images` and recorded as images of the patient. The stored
+/// images are then run through the analysis pipeline with the requested
+/// model; the database lock is not held while the model runs.
+///
+/// # Arguments
+///
+/// * `db_connection` - Database connection
+/// * `request` - Patient, study, series and model to use
+/// * `user` - Authenticated user
+/// * `app_handle` - Tauri application handle for file system and model access
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PullSeriesResponse)` - Stored images and the analysis results
+/// * `Err(AppError)` - Error message if retrieval, storage or analysis fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * No PACS is configured for the organization
+/// * The patient isn't accessible to the user
+/// * The series modality isn't supported or the series is empty
+/// * The PACS is unreachable or returns invalid images
+/// * The analysis fails
+
+pub async fn pull_series_service(
+    db_connection: &DbConnection,
+    request: models::PullSeriesRequest,
+    user: &AuthenticatedUser,
+    app_handle: tauri::AppHandle,
+) -> Result<models::PullSeriesResponse, AppError> {
+    let app_local_data_dir = app_handle
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| AppError::OperationFailed(format!("Failed to get app local data directory: {}", e)))?;
+    let save_dir = app_local_data_dir.join("saved_images");
+
+    let (images, files) = import_series(db_connection, &request, user, &save_dir).await?;
+
+    let image_data = serde_json::to_string(&files)
+        .map_err(|e| AppError::OperationFailed(format!("Failed to serialize images: {}", e)))?;
+    let db = db_connection.get().lock().await.clone();
+    let analysis = process_images_service(
+        image_data,
+        user.email.clone(),
+        request.model_name,
+        app_handle,
+        &db,
+    )
+    .await?;
+
+    let image_ids: Vec<Thing> = images.iter().map(|image| image.id.clone()).collect();
+    let patient = Thing::from(("Patient", request.patient_id.as_str()));
+    let db = db_connection.get().lock().await;
+    store_classifications(&db, &patient, &image_ids, &analysis.results, user).await?;
+
+    Ok(models::PullSeriesResponse { images, analysis })
+}
+
+/// Sets the PACS endpoint of the admin's organization.
+///
+/// The token is stored in the system keyring rather than the database. A
+/// missing token keeps the stored one and an empty token removes it.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `settings` - DICOMweb base URL, token and timeout
+/// * `user` - Authenticated admin
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(DicomwebSettings)` - The stored settings
+/// * `Err(AppError)` - Error message if the update fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The user is not an admin or has no organization
+/// * The base URL is not an http(s) URL
+
+pub async fn update_dicomweb_settings_service(
+    db: &Surreal<Any>,
+    mut settings: models::DicomwebSettings,
+    user: &AuthenticatedUser,
+) -> Result<models::DicomwebSettings, AppError> {
+    if !user.is_admin() {
+        return Err(AppError::Forbidden("Only admins can change the DICOMweb settings".to_string()));
+    }
+    let organization = user
+        .organization
+        .clone()
+        .ok_or_else(|| AppError::Forbidden("User doesn't belong to an organization".to_string()))?;
+
+    settings.base_url = settings.base_url.trim().trim_end_matches('/').to_string();
+    if !settings.base_url.starts_with("http://") && !settings.base_url.starts_with("https://") {
+        return Err(AppError::validation(
+            "base_url",
+            "DICOMweb base URL must start with http:// or https://",
+        ));
+    }
+
+    db.query("UPDATE $organization SET dicomweb = $settings")
+        .bind(("organization", organization.clone()))
+        .bind(("settings", settings.clone()))
+        .await?
+        .check()?;
+
+    if let Some(token) = settings.auth_token.take() {
+        store_dicomweb_token(&organization, token.trim())?;
+    }
+
+    Ok(settings)
+}
+
+/// Retrieves a series and stores its instances as images of the patient.
+///
+/// The PACS PatientID of the instances is assigned to the patient as an
+/// identifier of the PACS, which refuses series of a PACS patient already
+/// linked to another patient. The identifiers are only stored once the
+/// instances have been retrieved, in the same transaction as the images.
+/// Files are written before the records are inserted and removed again if
+/// the insert fails.
+
+async fn import_series(
+    db_connection: &DbConnection,
+    request: &models::PullSeriesRequest,
+    user: &AuthenticatedUser,
+    save_dir: &Path,
+) -> Result<(Vec<models::PulledImage>, Vec<ImageData>), AppError> {
+    let patient = Thing::from(("Patient", request.patient_id.as_str()));
+    let (client, pacs) = {
+        let db = db_connection.get().lock().await;
+        let settings = require_dicomweb_settings(&db, user).await?;
+        if !can_access_patient(&db, &patient, user).await? {
+            return Err(AppError::NotFound("Patient not found".to_string()));
+        }
+        (DicomwebClient::new(&settings)?, settings.base_url)
+    };
+
+    let series = client
+        .search_series(&request.study_uid)
+        .await?
+        .into_iter()
+        .find(|series| series.series_uid == request.series_uid)
+        .ok_or_else(|| AppError::NotFound(format!("Series {} not found in the PACS", request.series_uid)))?;
+    let modality = modal_type(series.modality.as_deref().unwrap_or_default())?;
+
+    let instances = client.search_instances(&request.study_uid, &request.series_uid).await?;
+    if instances.is_empty() {
+        return Err(AppError::OperationFailed(format!(
+            "Series {} has no instances",
+            request.series_uid
+        )));
+    }
+
+    let mut identifiers: Vec<PatientIdentifier> = Vec::new();
+    for patient_id in instances.iter().filter_map(|instance| instance.patient_id.as_deref()) {
+        if !identifiers.iter().any(|identifier| identifier.value == patient_id) {
+            identifiers.push(PatientIdentifier {
+                kind: IdentifierKind::DicomPatientId,
+                system: pacs.clone(),
+                value: patient_id.to_string(),
+            });
+        }
+    }
+    {
+        let db = db_connection.get().lock().await;
+        new_identifiers(&db, &patient, &identifiers).await?;
+    }
+
+    let mut retrieved = Vec::new();
+    for instance in instances {
+        let data = client
+            .retrieve_rendered(&request.study_uid, &request.series_uid, &instance.sop_instance_uid)
+            .await?;
+        image::load_from_memory(&data).map_err(|e| {
+            AppError::OperationFailed(format!(
+                "PACS returned an invalid image for {}: {}",
+                instance.sop_instance_uid, e
+            ))
+        })?;
+        retrieved.push((instance, data));
+    }
+
+    fs::create_dir_all(save_dir)
+        .map_err(|e| AppError::OperationFailed(format!("Failed to create image directory: {}", e)))?;
+
+    let mut records = Vec::new();
+    let mut files = Vec::new();
+    let mut written = Vec::new();
+    for (instance, data) in retrieved {
+        let image_id = Thing::from(("Image", Id::rand()));
+        let file_path = save_dir.join(format!("{}.png", image_id));
+        if let Err(e) = fs::write(&file_path, &data) {
+            remove_files(&written);
+            return Err(AppError::OperationFailed(format!("Failed to save image: {}", e)));
+        }
+        written.push(file_path.clone());
+
+        let file_path_str = file_path
+            .to_str()
+            .ok_or_else(|| AppError::OperationFailed("File path contains invalid Unicode".to_string()))?
+            .to_string();
+        let filename = format!("{}.png", instance.sop_instance_uid);
+
+        records.push(ImageRecord {
+            id: image_id,
+            name: filename.clone(),
+            path: file_path_str,
+            patient: patient.clone(),
+            user: user.id.clone(),
+            file_type: "png".to_string(),
+            modal_type: modality.to_string(),
+            dicom: Some(instance),
+            classification: None,
+        });
+        files.push(ImageData {
+            filename,
+            extension: "png".to_string(),
+            data,
+        });
+    }
+
+    let mut audit: Vec<_> = records
+        .iter()
+        .map(|image| {
+            let changes = audit_changes(None::<&ImageRecord>, Some(image));
+            audit_entry(user, AuditAction::Create, &image.id, Some(&patient), changes)
+        })
+        .collect();
+
+    let db = db_connection.get().lock().await;
+    let identifiers = match new_identifiers(&db, &patient, &identifiers).await {
+        Ok(identifiers) => identifiers,
+        Err(e) => {
+            remove_files(&written);
+            return Err(e);
+        }
+    };
+    if !identifiers.is_empty() {
+        audit.push(identifiers_audit(user, &patient, &identifiers));
+    }
+
+    let insert = format!(
+        "BEGIN TRANSACTION; INSERT INTO Image $images; {} INSERT INTO AuditLog $audit; COMMIT TRANSACTION;",
+        CREATE_IDENTIFIERS
+    );
+    let inserted = db
+        .query(insert)
+        .bind(("images", records.clone()))
+        .bind(("patient", patient.clone()))
+        .bind(("identifiers", identifiers))
+        .bind(("audit", audit))
+        .await
+        .and_then(|response| response.check());
+    if let Err(e) = inserted {
+        remove_files(&written);
+        return Err(e.into());
+    }
+
+    let images = records
+        .into_iter()
+        .map(|image| models::PulledImage {
+            id: image.id,
+            name: image.name,
+            path: image.path,
+            modal_type: image.modal_type,
+        })
+        .collect();
+    Ok((images, files))
+}
+
+fn remove_files(paths: &[std::path::PathBuf]) {
+    for path in paths {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Maps a DICOM modality code to the image modality stored on `Image`.
+
+pub fn modal_type(modality: &str) -> Result<&'static str, AppError> {
+    match modality.to_ascii_uppercase().as_str() {
+        "CR" | "DX" | "DR" | "RF" | "XA" => Ok("xray"),
+        "CT" => Ok("ct"),
+        "MR" => Ok("mri"),
+        other => Err(AppError::OperationFailed(format!(
+            "Modality '{}' is not supported for analysis",
+            other
+        ))),
+    }
+}
+
+/// Converts a `YYYY-MM-DD` date to the DICOM `YYYYMMDD` format.
+
+fn dicom_date(date: &str) -> Result<String, AppError> {
+    let parts: Vec<&str> = date.split('-').collect();
+    let well_formed = parts.len() == 3
+        && [4, 2, 2].iter().zip(&parts).all(|(len, part)| {
+            part.len() == *len && part.chars().all(|c| c.is_ascii_digit())
+        });
+    if !well_formed {
+        return Err(AppError::InvalidInput(format!("'{}' is not a valid YYYY-MM-DD date", date)));
+    }
+    Ok(parts.concat())
+}
+
+/// Converts a DICOM `YYYYMMDD` date to `YYYY-MM-DD`.
+
+fn iso_date(date: &str) -> String {
+    if date.len() == 8 && date.chars().all(|c| c.is_ascii_digit()) {
+        format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..])
+    } else {
+        date.to_string()
+    }
+}
+
+pub(crate) async fn require_dicomweb_settings(
+    db: &Surreal<Any>,
+    user: &AuthenticatedUser,
+) -> Result<models::DicomwebSettings, AppError> {
+    let not_configured =
+        || AppError::OperationFailed("No DICOMweb server is configured for the organization".to_string());
+    let organization = user.organization.clone().ok_or_else(not_configured)?;
+    let stored: Option<models::OrganizationDicomweb> = db
+        .query("SELECT dicomweb FROM $organization")
+        .bind(("organization", organization.clone()))
+        .await?
+        .take(0)?;
+    let mut settings = stored
+        .and_then(|stored| stored.dicomweb)
+        .ok_or_else(not_configured)?;
+    settings.auth_token = load_dicomweb_token(&organization)?;
+    Ok(settings)
+}
+
+/// Stores the PACS token of an organization in the system keyring; an empty
+/// token removes it.
+
+#[cfg_attr(test, allow(unused_variables))]
+fn store_dicomweb_token(organization: &Thing, token: &str) -> Result<(), AppError> {
+    #[cfg(test)]
+    return mock_store_dicomweb_token(organization, token);
+
+    #[cfg(not(test))]
+    {
+        let entry = Entry::new(TOKEN_SERVICE_NAME, &organization.to_string())
+            .map_err(|e| AppError::Keyring(format!("Failed to create keyring entry: {}", e)))?;
+        if token.is_empty() {
+            return match entry.delete_credential() {
+                Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+                Err(e) => Err(AppError::Keyring(format!("Failed to remove DICOMweb token: {}", e))),
+            };
+        }
+        entry
+            .set_password(token)
+            .map_err(|e| AppError::Keyring(format!("Failed to store DICOMweb token: {}", e)))
+    }
+}
+
+/// Loads the PACS token of an organization from the system keyring.
+
+#[cfg_attr(test, allow(unused_variables))]
+fn load_dicomweb_token(organization: &Thing) -> Result<Option<String>, AppError> {
+    #[cfg(test)]
+    return mock_load_dicomweb_token(organization);
+
+    #[cfg(not(test))]
+    {
+        let entry = Entry::new(TOKEN_SERVICE_NAME, &organization.to_string())
+            .map_err(|e| AppError::Keyring(format!("Failed to create keyring entry: {}", e)))?;
+        match entry.get_password() {
+            Ok(token) => Ok(Some(token)),
+            Err(KeyringError::NoEntry) => Ok(None),
+            Err(e) => Err(AppError::Keyring(format!("Failed to retrieve DICOMweb token: {}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod mock_keyring {
+    use crate::error::AppError;
+    use lazy_static::lazy_static;
+    use scanlytics_db::Thing;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    lazy_static! {
+        static ref MOCK_KEYRING: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+    }
+
+    pub fn mock_store_dicomweb_token(organization: &Thing, token: &str) -> Result<(), AppError> {
+        let mut store = MOCK_KEYRING.lock().unwrap();
+        if token.is_empty() {
+            store.remove(&organization.to_string());
+        } else {
+            store.insert(organization.to_string(), token.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn mock_load_dicomweb_token(organization: &Thing) -> Result<Option<String>, AppError> {
+        Ok(MOCK_KEYRING.lock().unwrap().get(&organization.to_string()).cloned())
+    }
+}
+
+#[cfg(test)]
+use mock_keyring::*;
+
+/// Minimal DICOMweb client for QIDO-RS searches and WADO-RS retrieval.
+
+pub struct DicomwebClient {
+    base_url: String,
+    auth_token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl DicomwebClient {
+    pub fn new(settings: &models::DicomwebSettings) -> Result<Self, AppError> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(settings.timeout_secs))
+            .build()
+            .map_err(|e| AppError::OperationFailed(format!("Failed to create DICOMweb client: {}", e)))?;
+        Ok(Self {
+            base_url: settings.base_url.trim_end_matches('/').to_string(),
+            auth_token: settings.auth_token.clone(),
+            http,
+        })
+    }
+
+    pub async fn search_studies(
+        &self,
+        request: &models::StudySearchRequest,
+    ) -> Result<Vec<models::StudySummary>, AppError> {
+        let mut query: Vec<(&str, String)> = STUDY_FIELDS
+            .iter()
+            .map(|field| ("includefield", field.to_string()))
+            .collect();
+        if let Some(patient_id) = non_empty(&request.patient_id) {
+            query.push(("PatientID", patient_id.to_string()));
+        }
+        if let Some(patient_name) = non_empty(&request.patient_name) {
+            query.push(("PatientName", patient_name.to_string()));
+            query.push(("fuzzymatching", "true".to_string()));
+        }
+        let from = non_empty(&request.date_from).map(dicom_date).transpose()?;
+        let to = non_empty(&request.date_to).map(dicom_date).transpose()?;
+        if from.is_some() || to.is_some() {
+            query.push((
+                "StudyDate",
+                format!("{}-{}", from.unwrap_or_default(), to.unwrap_or_default()),
+            ));
+        }
+        if let Some(modality) = non_empty(&request.modality) {
+            query.push(("ModalitiesInStudy", modality.to_ascii_uppercase()));
+        }
+        if let Some(limit) = request.limit {
+            query.push(("limit", limit.to_string()));
+        }
+
+        let studies = self.query("/studies", &query).await?;
+        studies
+            .iter()
+            .map(|study| {
+                Ok(models::StudySummary {
+                    study_uid: required_string(study, STUDY_INSTANCE_UID)?,
+                    patient_id: tag_string(study, PATIENT_ID),
+                    patient_name: tag_string(study, PATIENT_NAME),
+                    study_date: tag_string(study, STUDY_DATE).map(|date| iso_date(&date)),
+                    description: tag_string(study, STUDY_DESCRIPTION),
+                    modalities: tag_strings(study, MODALITIES_IN_STUDY),
+                    series_count: tag_u32(study, STUDY_SERIES_COUNT),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn search_series(&self, study_uid: &str) -> Result<Vec<models::SeriesSummary>, AppError> {
+        let query = [
+            ("includefield", "SeriesDescription".to_string()),
+            ("includefield", "NumberOfSeriesRelatedInstances".to_string()),
+        ];
+        let series = self
+            .query(&format!("/studies/{}/series", study_uid), &query)
+            .await?;
+        series
+            .iter()
+            .map(|series| {
+                Ok(models::SeriesSummary {
+                    series_uid: required_string(series, SERIES_INSTANCE_UID)?,
+                    modality: tag_string(series, MODALITY),
+                    description: tag_string(series, SERIES_DESCRIPTION),
+                    instance_count: tag_u32(series, SERIES_INSTANCE_COUNT),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the instances of a series ordered by instance number.
+
+    pub async fn search_instances(
+        &self,
+        study_uid: &str,
+        series_uid: &str,
+    ) -> Result<Vec<DicomReference>, AppError> {
+        let query = [
+            ("includefield", "PatientID".to_string()),
+            ("includefield", "PatientName".to_string()),
+        ];
+        let instances = self
+            .query(&format!("/studies/{}/series/{}/instances", study_uid, series_uid), &query)
+            .await?;
+        let mut instances = instances
+            .iter()
+            .map(|instance| {
+                let reference = DicomReference {
+                    study_uid: study_uid.to_string(),
+                    series_uid: series_uid.to_string(),
+                    sop_instance_uid: required_string(instance, SOP_INSTANCE_UID)?,
+                    sop_class_uid: required_string(instance, SOP_CLASS_UID)?,
+                    patient_id: tag_string(instance, PATIENT_ID),
+                    patient_name: tag_string(instance, PATIENT_NAME),
+                };
+                Ok((tag_u32(instance, INSTANCE_NUMBER).unwrap_or(u32::MAX), reference))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+        instances.sort_by_key(|(number, _)| *number);
+        Ok(instances.into_iter().map(|(_, reference)| reference).collect())
+    }
+
+    /// Retrieves an instance rendered as PNG.
+
+    pub async fn retrieve_rendered(
+        &self,
+        study_uid: &str,
+        series_uid: &str,
+        instance_uid: &str,
+    ) -> Result<Vec<u8>, AppError> {
+        let path = format!(
+            "/studies/{}/series/{}/instances/{}/rendered",
+            study_uid, series_uid, instance_uid
+        );
+        let response = self
+            .request(&path)
+            .header(ACCEPT, "image/png")
+            .send()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to reach DICOMweb server: {}", e)))?;
+        let response = error_for_status(response).await?;
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to read instance {}: {}", instance_uid, e)))?;
+        Ok(bytes.to_vec())
+    }
+
+    /// Stores DICOM instances in a study through STOW-RS.
+
+    pub async fn store_instances(&self, study_uid: &str, instances: &[Vec<u8>]) -> Result<(), AppError> {
+        let boundary = format!("scanlytics-{}", Id::rand().to_raw());
+        let mut body = Vec::new();
+        for instance in instances {
+            body.extend_from_slice(
+                format!("--{}\r\nContent-Type: application/dicom\r\n\r\n", boundary).as_bytes(),
+            );
+            body.extend_from_slice(instance);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+        let content_type = format!(
+            "multipart/related; type=\"application/dicom\"; boundary={}",
+            boundary
+        );
+        let response = self
+            .post(&format!("/studies/{}", study_uid))
+            .header(CONTENT_TYPE, content_type)
+            .header(ACCEPT, DICOM_JSON)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to reach DICOMweb server: {}", e)))?;
+        if response.status() == StatusCode::ACCEPTED {
+            return Err(AppError::OperationFailed(
+                "DICOMweb server stored only some of the instances".to_string(),
+            ));
+        }
+        error_for_status(response).await?;
+        Ok(())
+    }
+
+    async fn query(&self, path: &str, query: &[(&str, String)]) -> Result<Vec<Value>, AppError> {
+        let response = self
+            .request(path)
+            .header(ACCEPT, DICOM_JSON)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to reach DICOMweb server: {}", e)))?;
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(Vec::new());
+        }
+        let response = error_for_status(response).await?;
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::OperationFailed(format!("Invalid DICOM JSON response: {}", e)))
+    }
+
+    fn request(&self, path: &str) -> reqwest::RequestBuilder {
+        self.authorize(self.http.get(format!("{}{}", self.base_url, path)))
+    }
+
+    fn post(&self, path: &str) -> reqwest::RequestBuilder {
+        self.authorize(self.http.post(format!("{}{}", self.base_url, path)))
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+async fn error_for_status(response: reqwest::Response) -> Result<reqwest::Response, AppError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(AppError::Network(format!("DICOMweb server returned {}: {}", status, body.trim())))
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// Returns the values of a DICOM JSON attribute as strings.
+///
+/// Person names are returned in their alphabetic representation.
+
+fn tag_strings(dataset: &Value, tag: &str) -> Vec<String> {
+    let Some(values) = dataset[tag]["Value"].as_array() else {
+        return Vec::new();
+    };
+    values
+        .iter()
+        .filter_map(|value| match value {
+            Value::String(value) => Some(value.clone()),
+            Value::Number(value) => Some(value.to_string()),
+            Value::Object(_) => value["Alphabetic"].as_str().map(str::to_string),
+            _ => None,
+        })
+        .collect()
+}
+
+fn tag_string(dataset: &Value, tag: &str) -> Option<String> {
+    tag_strings(dataset, tag).into_iter().next()
+}
+
+fn tag_u32(dataset: &Value, tag: &str) -> Option<u32> {
+    tag_string(dataset, tag).and_then(|value| value.trim().parse().ok())
+}
+
+fn required_string(dataset: &Value, tag: &str) -> Result<String, AppError> {
+    tag_string(dataset, tag).ok_or_else(|| {
+        AppError::OperationFailed(format!("DICOM JSON response is missing tag {}", tag))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::session::models::ADMIN_ROLE;
+    use crate::patients::services::load_identifiers;
+    use crate::users::models::UserResponse;
+    use image::{DynamicImage, ImageFormat, RgbImage};
+    use serde_json::json;
+    use std::io::Cursor;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn setup(base_url: &str) -> (DbConnection, AuthenticatedUser, String) {
+        let db_conn = scanlytics_db::init_db(None, true).await.unwrap();
+        scanlytics_db::define_db_on_startup(db_conn.clone()).await.unwrap();
+
+        let (user, patient_id) = {
+            let db = db_conn.get().lock().await;
+            let mut created = db
+                .query("CREATE ONLY User SET name = 'Test Doctor', email = 'doctor@test.com', role = 'admin'")
+                .query(
+                    "CREATE ONLY Organization SET name = 'General Hospital', address = '1 Main St',
+                        email = 'info@hospital.test'",
+                )
+                .await
+                .unwrap();
+            let user: Option<UserResponse> = created.take(0).unwrap();
+            let organization: Option<Thing> = created.take((1, "id")).unwrap();
+            let user = user.unwrap();
+
+            let mut patient = db
+                .query(
+                    "CREATE ONLY Patient SET name = 'Test Patient', date_of_birth = time::now(),
+                        gender = 'male', contact_number = '1234567890', address = 'Test Address'
+                        RETURN VALUE id",
+                )
+                .await
+                .unwrap();
+            let patient: Option<Thing> = patient.take(0).unwrap();
+
+            let admin = AuthenticatedUser {
+                id: user.id,
+                name: user.name,
+                email: "doctor@test.com".to_string(),
+                role: ADMIN_ROLE.to_string(),
+                organization,
+            };
+            let settings = models::DicomwebSettings {
+                base_url: format!("{}/dicom-web/", base_url),
+                auth_token: Some("secret".to_string()),
+                timeout_secs: 5,
+            };
+            update_dicomweb_settings_service(&db, settings, &admin).await.unwrap();
+            (admin, patient.unwrap().id.to_raw())
+        };
+        (db_conn, user, patient_id)
+    }
+
+    fn png() -> Vec<u8> {
+        let mut data = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(2, 2))
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    async fn mount_series(server: &MockServer, modality: &str) {
+        Mock::given(method("GET"))
+            .and(path("/dicom-web/studies/1.2.3/series"))
+            .and(header("authorization", "Bearer secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "0020000E": { "vr": "UI", "Value": ["1.2.3.4"] },
+                "00080060": { "vr": "CS", "Value": [modality] },
+                "0008103E": { "vr": "LO", "Value": ["Chest PA"] },
+                "00201209": { "vr": "IS", "Value": [2] }
+            }])))
+            .mount(server)
+            .await;
+    }
+
+    #[test]
+    fn test_modal_type_mapping() {
+        assert_eq!(modal_type("DX").unwrap(), "xray");
+        assert_eq!(modal_type("cr").unwrap(), "xray");
+        assert_eq!(modal_type("CT").unwrap(), "ct");
+        assert_eq!(modal_type("MR").unwrap(), "mri");
+        assert!(modal_type("US").is_err());
+    }
+
+    #[test]
+    fn test_date_conversion() {
+        assert_eq!(dicom_date("2024-03-07").unwrap(), "20240307");
+        assert!(dicom_date("07.03.2024").is_err());
+        assert_eq!(iso_date("20240307"), "2024-03-07");
+    }
+
+    #[tokio::test]
+    async fn test_token_is_kept_out_of_the_database() {
+        let server = MockServer::start().await;
+        let (db_conn, user, _) = setup(&server.uri()).await;
+        let db = db_conn.get().lock().await;
+
+        let stored: Vec<Value> = db
+            .query("SELECT VALUE dicomweb FROM Organization")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert!(!serde_json::to_string(&stored).unwrap().contains("secret"));
+
+        let settings = require_dicomweb_settings(&db, &user).await.unwrap();
+        assert_eq!(settings.auth_token.as_deref(), Some("secret"));
+        assert!(!serde_json::to_string(&settings).unwrap().contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_search_studies() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dicom-web/studies"))
+            .and(header("authorization", "Bearer secret"))
+            .and(query_param("PatientID", "MRN-1"))
+            .and(query_param("StudyDate", "20240101-20241231"))
+            .and(query_param("ModalitiesInStudy", "DX"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "0020000D": { "vr": "UI", "Value": ["1.2.3"] },
+                "00100010": { "vr": "PN", "Value": [{ "Alphabetic": "Doe^Jane" }] },
+                "00100020": { "vr": "LO", "Value": ["MRN-1"] },
+                "00080020": { "vr": "DA", "Value": ["20240315"] },
+                "00081030": { "vr": "LO", "Value": ["Chest X-ray"] },
+                "00080061": { "vr": "CS", "Value": ["DX", "SR"] },
+                "00201206": { "vr": "IS", "Value": ["2"] }
+            }])))
+            .mount(&server)
+            .await;
+
+        let (db_conn, user, _) = setup(&server.uri()).await;
+        let request = models::StudySearchRequest {
+            patient_id: Some("MRN-1".to_string()),
+            date_from: Some("2024-01-01".to_string()),
+            date_to: Some("2024-12-31".to_string()),
+            modality: Some("dx".to_string()),
+            ..Default::default()
+        };
+        let studies = search_studies_service(&db_conn, request, &user).await.unwrap();
+
+        assert_eq!(
+            studies,
+            vec![models::StudySummary {
+                study_uid: "1.2.3".to_string(),
+                patient_id: Some("MRN-1".to_string()),
+                patient_name: Some("Doe^Jane".to_string()),
+                study_date: Some("2024-03-15".to_string()),
+                description: Some("Chest X-ray".to_string()),
+                modalities: vec!["DX".to_string(), "SR".to_string()],
+                series_count: Some(2),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_without_matches_or_settings() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/dicom-web/studies"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let (db_conn, user, _) = setup(&server.uri()).await;
+        let studies = search_studies_service(&db_conn, Default::default(), &user).await.unwrap();
+        assert!(studies.is_empty());
+
+        let without_organization = AuthenticatedUser { organization: None, ..user };
+        let err = search_studies_service(&db_conn, Default::default(), &without_organization)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::OperationFailed(message) if message.contains("No DICOMweb server")));
+    }
+
+    #[tokio::test]
+    async fn test_import_series_stores_images() {
+        let server = MockServer::start().await;
+        mount_series(&server, "DX").await;
+        Mock::given(method("GET"))
+            .and(path("/dicom-web/studies/1.2.3/series/1.2.3.4/instances"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "00080016": { "vr": "UI", "Value": ["1.2.840.10008.5.1.4.1.1.1.1"] },
+                    "00080018": { "vr": "UI", "Value": ["1.2.3.4.2"] },
+                    "00100020": { "vr": "LO", "Value": ["MRN-1"] },
+                    "00200013": { "vr": "IS", "Value": [2] }
+                },
+                {
+                    "00080016": { "vr": "UI", "Value": ["1.2.840.10008.5.1.4.1.1.1.1"] },
+                    "00080018": { "vr": "UI", "Value": ["1.2.3.4.1"] },
+                    "00100020": { "vr": "LO", "Value": ["MRN-1"] },
+                    "00200013": { "vr": "IS", "Value": [1] }
+                }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dicom-web/studies/1.2.3/series/1.2.3.4/instances/1.2.3.4.1/rendered"))
+            .and(header("accept", "image/png"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(png()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dicom-web/studies/1.2.3/series/1.2.3.4/instances/1.2.3.4.2/rendered"))
+            .and(header("accept", "image/png"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(png()))
+            .mount(&server)
+            .await;
+
+        let (db_conn, user, patient_id) = setup(&server.uri()).await;
+        let save_dir = tempfile::tempdir().unwrap();
+        let request = models::PullSeriesRequest {
+            patient_id: patient_id.clone(),
+            study_uid: "1.2.3".to_string(),
+            series_uid: "1.2.3.4".to_string(),
+            model_name: "body_part".to_string(),
+        };
+        let (images, files) = import_series(&db_conn, &request, &user, save_dir.path())
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = images.iter().map(|image| image.name.as_str()).collect();
+        assert_eq!(names, vec!["1.2.3.4.1.png", "1.2.3.4.2.png"]);
+        assert_eq!(files.len(), 2);
+        assert!(images.iter().all(|image| image.modal_type == "xray"));
+        assert!(images.iter().all(|image| Path::new(&image.path).exists()));
+
+        let db = db_conn.get().lock().await;
+        let stored: Vec<ImageRecord> = db
+            .query("SELECT * FROM Image WHERE patient = type::thing('Patient', $id)")
+            .bind(("id", patient_id.clone()))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+        let reference = stored.iter().find_map(|image| image.dicom.clone()).unwrap();
+        assert_eq!(reference.study_uid, "1.2.3");
+        assert_eq!(reference.patient_id.as_deref(), Some("MRN-1"));
+
+        let patient = Thing::from(("Patient", patient_id.as_str()));
+        let identifiers = load_identifiers(&db, &patient).await.unwrap();
+        assert_eq!(identifiers.len(), 1);
+        assert_eq!(identifiers[0].kind, IdentifierKind::DicomPatientId);
+        assert_eq!(identifiers[0].value, "MRN-1");
+    }
+
+    #[tokio::test]
+    async fn test_store_instances_posts_multipart() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/dicom-web/studies/1.2.3"))
+            .and(header("authorization", "Bearer secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = DicomwebClient::new(&models::DicomwebSettings {
+            base_url: format!("{}/dicom-web", server.uri()),
+            auth_token: Some("secret".to_string()),
+            timeout_secs: 5,
+        })
+        .unwrap();
+        client
+            .store_instances("1.2.3", &[b"first".to_vec(), b"second".to_vec()])
+            .await
+            .unwrap();
+
+        let request = &server.received_requests().await.unwrap()[0];
+        let content_type = request.headers["content-type"].to_str().unwrap();
+        assert!(content_type.starts_with("multipart/related; type=\"application/dicom\""));
+        let body = String::from_utf8_lossy(&request.body);
+        assert_eq!(body.matches("Content-Type: application/dicom").count(), 2);
+        assert!(body.contains("second"));
+    }
+
+    #[tokio::test]
+    async fn test_import_series_rejects_unsupported_modality() {
+        let server = MockServer::start().await;
+        mount_series(&server, "US").await;
+
+        let (db_conn, user, patient_id) = setup(&server.uri()).await;
+        let save_dir = tempfile::tempdir().unwrap();
+        let request = models::PullSeriesRequest {
+            patient_id,
+            study_uid: "1.2.3".to_string(),
+            series_uid: "1.2.3.4".to_string(),
+            model_name: "body_part".to_string(),
+        };
+        let err = import_series(&db_conn, &request, &user, save_dir.path())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::OperationFailed(message) if message.contains("not supported")));
+    }
+    #[tokio::test]
+    async fn test_failed_retrieval_assigns_no_identifiers() {
+        let server = MockServer::start().await;
+        mount_series(&server, "DX").await;
+        Mock::given(method("GET"))
+            .and(path("/dicom-web/studies/1.2.3/series/1.2.3.4/instances"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "00080016": { "vr": "UI", "Value": ["1.2.840.10008.5.1.4.1.1.1.1"] },
+                "00080018": { "vr": "UI", "Value": ["1.2.3.4.1"] },
+                "00100020": { "vr": "LO", "Value": ["MRN-1"] }
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/dicom-web/studies/1.2.3/series/1.2.3.4/instances/1.2.3.4.1/rendered"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let (db_conn, user, patient_id) = setup(&server.uri()).await;
+        let save_dir = tempfile::tempdir().unwrap();
+        let request = models::PullSeriesRequest {
+            patient_id: patient_id.clone(),
+            study_uid: "1.2.3".to_string(),
+            series_uid: "1.2.3.4".to_string(),
+            model_name: "body_part".to_string(),
+        };
+        assert!(import_series(&db_conn, &request, &user, save_dir.path()).await.is_err());
+
+        let db = db_conn.get().lock().await;
+        let patient = Thing::from(("Patient", patient_id.as_str()));
+        assert!(load_identifiers(&db, &patient).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,410 @@
+//! # Command Errors
+//!
+//! [`AppError`] is the error returned by every Tauri command. It serializes
+//! to an object the frontend can branch on:
+//!
+//! ```json
+//! {
+//!   "code": "validation",
+//!   "message": "Validation error: name is required",
+//!   "fields": [{ "field": "name", "message": "name is required" }],
+//!   "retryable": false
+//! }
+//! ```
+//!
+//! Services return [`AppError`] directly and pick the variant where the
+//! failure happens. SurrealDB errors are classified on conversion: errors
+//! raised with `THROW` and unique index violations reject the request and
+//! are [`AppError::OperationFailed`], schema `TYPE` and `ASSERT` failures are
+//! [`AppError::Validation`] for the field, and only datastore and transaction
+//! failures that may pass on a retry are [`AppError::Database`]. The
+//! SurrealDB error text is logged rather than sent to the frontend.
+
+use crate::auth::login::models::AuthError;
+use crate::auth::logout::models::LogoutError;
+use crate::auth::session::models::SessionError;
+use crate::auth::signup::models::SignupError;
+use crate::auth::validate::models::TokenError;
+use crate::image_analysis::ml_models::models::ModelError;
+use crate::validation::models::{FieldError, ValidationErrors};
+
+use scanlytics_db::{DbError, Response};
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+
+
+/// Kind of an [`AppError`], sent to the frontend as `code`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotAuthenticated,
+    SessionExpired,
+    Forbidden,
+    NotFound,
+    InvalidInput,
+    Validation,
+    Network,
+    Keyring,
+    Database,
+    Model,
+    OperationFailed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    /// No session, or the credentials or token were rejected
+    NotAuthenticated(String),
+    /// The session or token expired; logging in again resolves it
+    SessionExpired(String),
+    /// The user may not perform the action
+    Forbidden(String),
+    /// The record doesn't exist or isn't visible to the user
+    NotFound(String),
+    /// A command argument couldn't be parsed
+    InvalidInput(String),
+    /// One or more request fields are invalid
+    Validation(ValidationErrors),
+    /// A remote server couldn't be reached
+    Network(String),
+    /// The system keyring couldn't be accessed
+    Keyring(String),
+    /// The local database is temporarily unavailable
+    Database(String),
+    /// Loading or running an analysis model failed
+    Model(String),
+    /// A service rejected the request or failed; the message explains why
+    OperationFailed(String),
+}
+
+impl AppError {
+    /// Error for a command argument that isn't valid JSON for its type.
+    pub fn invalid_input(argument: &str, error: impl std::fmt::Display) -> Self {
+        AppError::InvalidInput(format!("Failed to parse {}: {}", argument, error))
+    }
+
+    /// Error for a single invalid request field.
+    pub fn validation(field: &str, message: impl Into<String>) -> Self {
+        let mut errors = ValidationErrors::new();
+        errors.push(field, message);
+        AppError::Validation(errors)
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::NotAuthenticated(_) => ErrorCode::NotAuthenticated,
+            AppError::SessionExpired(_) => ErrorCode::SessionExpired,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::InvalidInput(_) => ErrorCode::InvalidInput,
+            AppError::Validation(_) => ErrorCode::Validation,
+            AppError::Network(_) => ErrorCode::Network,
+            AppError::Keyring(_) => ErrorCode::Keyring,
+            AppError::Database(_) => ErrorCode::Database,
+            AppError::Model(_) => ErrorCode::Model,
+            AppError::OperationFailed(_) => ErrorCode::OperationFailed,
+        }
+    }
+
+    /// Field-level errors; empty unless the error is a validation error.
+    pub fn field_errors(&self) -> &[FieldError] {
+        match self {
+            AppError::Validation(errors) => &errors.errors,
+            _ => &[],
+        }
+    }
+
+    /// Whether repeating the same command may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, AppError::Network(_) | AppError::Database(_))
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Validation(errors) => write!(f, "{}", errors),
+            AppError::NotAuthenticated(msg)
+            | AppError::SessionExpired(msg)
+            | AppError::Forbidden(msg)
+            | AppError::NotFound(msg)
+            | AppError::InvalidInput(msg)
+            | AppError::Network(msg)
+            | AppError::Keyring(msg)
+            | AppError::Database(msg)
+            | AppError::Model(msg)
+            | AppError::OperationFailed(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("code", &self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("fields", self.field_errors())?;
+        state.serialize_field("retryable", &self.is_retryable())?;
+        state.end()
+    }
+}
+
+impl From<scanlytics_db::Error> for AppError {
+    fn from(error: scanlytics_db::Error) -> Self {
+        let error = match error {
+            scanlytics_db::Error::Db(error) => error,
+            error => {
+                log::error!("Database request failed: {}", error);
+                return AppError::OperationFailed("The database request failed".to_string());
+            }
+        };
+        match error {
+            DbError::Thrown(message) => AppError::OperationFailed(message),
+            DbError::IndexExists { .. } | DbError::RecordExists { .. } => {
+                log::warn!("Database constraint violated: {}", error);
+                AppError::OperationFailed("A record with the same values already exists".to_string())
+            }
+            DbError::FieldCheck { ref field, .. } | DbError::FieldValue { ref field, .. } => {
+                log::warn!("Database schema check failed: {}", error);
+                let field = field.to_string();
+                let message = format!("{} has an invalid value", field);
+                AppError::validation(&field, message)
+            }
+            DbError::Ds(_)
+            | DbError::Tx(_)
+            | DbError::TxFailure
+            | DbError::TxConditionNotMet
+            | DbError::TxKeyAlreadyExists
+            | DbError::QueryTimedout
+            | DbError::QueryCancelled => {
+                log::error!("Database unavailable: {}", error);
+                AppError::Database("The database is temporarily unavailable".to_string())
+            }
+            error => {
+                log::error!("Database request failed: {}", error);
+                AppError::OperationFailed("The database request failed".to_string())
+            }
+        }
+    }
+}
+
+/// Checks the results of a query run as one transaction.
+///
+/// When a statement fails, SurrealDB reports the other statements of the
+/// transaction as not executed. The error of the failing statement, such as
+/// one raised with `THROW`, is returned in preference to those, so the caller
+/// learns why the transaction failed.
+
+pub fn check_transaction(mut response: Response) -> Result<Response, AppError> {
+    let errors = response.take_errors();
+    let mut errors: Vec<(usize, scanlytics_db::Error)> = errors.into_iter().collect();
+    errors.sort_by_key(|(index, _)| *index);
+    let failed = errors
+        .iter()
+        .position(|(_, error)| !matches!(error, scanlytics_db::Error::Db(DbError::QueryNotExecuted)))
+        .unwrap_or_default();
+    if errors.is_empty() {
+        return Ok(response);
+    }
+    Err(AppError::from(errors.swap_remove(failed).1))
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        AppError::Validation(errors)
+    }
+}
+
+impl From<SessionError> for AppError {
+    fn from(error: SessionError) -> Self {
+        let message = error.to_string();
+        match error {
+            SessionError::Expired(_) => AppError::SessionExpired(message),
+            SessionError::Database(_) => AppError::Database(message),
+            SessionError::NotAuthenticated
+            | SessionError::InvalidToken(_)
+            | SessionError::UserNotFound(_) => AppError::NotAuthenticated(message),
+        }
+    }
+}
+
+impl From<AuthError> for AppError {
+    fn from(error: AuthError) -> Self {
+        let message = error.to_string();
+        match error {
+            AuthError::Authentication(_) => AppError::NotAuthenticated(message),
+            AuthError::Network(_) => AppError::Network(message),
+            AuthError::Parse(_) => AppError::InvalidInput(message),
+            AuthError::Keyring(_) => AppError::Keyring(message),
+        }
+    }
+}
+
+impl From<SignupError> for AppError {
+    fn from(error: SignupError) -> Self {
+        let message = error.to_string();
+        match error {
+            SignupError::ParseError(_) => AppError::InvalidInput(message),
+            SignupError::ValidationError(_) => AppError::InvalidInput(message),
+            SignupError::PasswordMismatch => {
+                let mut errors = ValidationErrors::new();
+                errors.push("confirm_password", message);
+                AppError::Validation(errors)
+            }
+            SignupError::WeakPassword(_) => {
+                let mut errors = ValidationErrors::new();
+                errors.push("password", message);
+                AppError::Validation(errors)
+            }
+            SignupError::NetworkError(_) => AppError::Network(message),
+            SignupError::DatabaseError(_) => AppError::Database(message),
+            SignupError::ServerError(_) => AppError::OperationFailed(message),
+        }
+    }
+}
+
+impl From<TokenError> for AppError {
+    fn from(error: TokenError) -> Self {
+        let message = error.to_string();
+        match error {
+            TokenError::KeyringAccess(_) | TokenError::KeyringStore(_) => {
+                AppError::Keyring(message)
+            }
+            TokenError::Expired => AppError::SessionExpired(message),
+            TokenError::KeyUnavailable(_) => AppError::Network(message),
+            TokenError::ServerError(_) => AppError::OperationFailed(message),
+            TokenError::ValidationError(_)
+            | TokenError::ParseError(_)
+            | TokenError::InvalidTokenType
+            | TokenError::InvalidSignature(_)
+            | TokenError::ClaimsMismatch(_) => AppError::NotAuthenticated(message),
+        }
+    }
+}
+
+impl From<LogoutError> for AppError {
+    fn from(error: LogoutError) -> Self {
+        AppError::Keyring(error.to_string())
+    }
+}
+
+impl From<ModelError> for AppError {
+    fn from(error: ModelError) -> Self {
+        let message = error.to_string();
+        match error {
+            ModelError::Auth(_) => AppError::NotAuthenticated(message),
+            ModelError::Network(_) => AppError::Network(message),
+            ModelError::Database(_) => AppError::Database(message),
+            ModelError::FileSystem(_)
+            | ModelError::Processing(_)
+            | ModelError::Image(_)
+            | ModelError::Serialization(_) => AppError::Model(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_database_errors_keep_their_kind() {
+        let db_conn = scanlytics_db::init_db(None, true).await.unwrap();
+        let db = db_conn.get().lock().await.clone();
+
+        let error = db.query("SELECT * FROM ").await.unwrap_err();
+        let error = AppError::from(error);
+        assert_eq!(error.code(), ErrorCode::OperationFailed);
+        assert!(!error.is_retryable());
+        assert!(!error.to_string().contains("SELECT"));
+
+        scanlytics_db::define_db_on_startup(db_conn.clone()).await.unwrap();
+        let create = "CREATE PatientIdentifier SET patient = Patient:one, kind = $kind,
+            system = 'urn:hospital', value = $value";
+        db.query(create)
+            .bind(("kind", "mrn"))
+            .bind(("value", "42"))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let error = db
+            .query(create)
+            .bind(("kind", "mrn"))
+            .bind(("value", "42"))
+            .await
+            .and_then(|response| response.check())
+            .unwrap_err();
+        let error = AppError::from(error);
+        assert_eq!(error, AppError::OperationFailed("A record with the same values already exists".to_string()));
+        assert!(!error.is_retryable());
+
+        let error = db
+            .query(create)
+            .bind(("kind", "passport"))
+            .bind(("value", "43"))
+            .await
+            .and_then(|response| response.check())
+            .unwrap_err();
+        let error = AppError::from(error);
+        assert_eq!(error.code(), ErrorCode::Validation);
+        assert_eq!(error.field_errors()[0].field, "kind");
+        assert!(!error.is_retryable());
+
+        let response = db
+            .query(
+                "BEGIN TRANSACTION;
+                CREATE Note SET text = 'kept';
+                THROW 'The report is no longer a draft';
+                COMMIT TRANSACTION;",
+            )
+            .await
+            .unwrap();
+        let error = check_transaction(response).unwrap_err();
+        assert_eq!(error, AppError::OperationFailed("The report is no longer a draft".to_string()));
+        assert!(!error.is_retryable());
+
+        let response = db.query("RETURN 1").await.unwrap();
+        assert!(check_transaction(response).is_ok());
+
+        let error = AppError::validation("title", "Letterhead title must not be empty");
+        assert_eq!(error.field_errors()[0].field, "title");
+    }
+
+    #[test]
+    fn test_serializes_code_fields_and_retryability() {
+        let mut errors = ValidationErrors::new();
+        errors.push("name", "name is required");
+        let value = serde_json::to_value(AppError::from(errors)).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "code": "validation",
+                "message": "Validation error: name is required",
+                "fields": [{ "field": "name", "message": "name is required" }],
+                "retryable": false
+            })
+        );
+
+        let error = AppError::from(SessionError::Database("locked".into()));
+        let value = serde_json::to_value(error).unwrap();
+        assert_eq!(value["code"], "database");
+        assert_eq!(value["retryable"], true);
+        assert_eq!(value["fields"], json!([]));
+    }
+
+    #[test]
+    fn test_auth_errors_keep_their_kind() {
+        assert_eq!(AppError::from(TokenError::Expired).code(), ErrorCode::SessionExpired);
+        assert_eq!(
+            AppError::from(SignupError::WeakPassword("too short".into())).field_errors()[0].field,
+            "password"
+        );
+        assert_eq!(
+            AppError::from(SessionError::NotAuthenticated).code(),
+            ErrorCode::NotAuthenticated
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use super::models;
+use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+
+/// Exports a patient's full record as a FHIR R4 bundle.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `patient_id` - Unique identifier of the patient
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Bundle)` - Collection bundle with the patient, notes, reports and images
+/// * `Err(AppError)` - Error message if the export fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The patient isn't visible to the session user
+/// * Database operations fail
+
+#[tauri::command]
+pub async fn export_patient_fhir(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    patient_id: String,
+) -> Result<models::Bundle, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::export_patient_bundle_service(&db, patient_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Exports a single report with its patient and images as a FHIR R4 bundle.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Bundle)` - Collection bundle with the report, its patient and images
+/// * `Err(AppError)` - Error message if the export fails
+
+#[tauri::command]
+pub async fn export_report_fhir(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+) -> Result<models::Bundle, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::export_report_bundle_service(&db, report_id, &user)
+            .await
+    })
+    .await
+}
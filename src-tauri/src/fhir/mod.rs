@@ -0,0 +1,23 @@
+//! # FHIR Module
+//! 
+//! This module exchanges clinical records with hospital systems as HL7 FHIR R4, including:
+//! - Patients as `Patient` resources
+//! - Reports as `DiagnosticReport` resources
+//! - Images as `Media` resources
+//! - Patient notes as `ClinicalImpression` resources
+//! 
+//! ## Components
+//! 
+//! - [`controller`]: Tauri command handlers for FHIR export
+//! - [`services`]: Mapping between Scanlytics records and FHIR resources
+//! - [`models`]: FHIR R4 resource data structures
+//! 
+//! ## Main Features
+//! 
+//! - Export of a patient's full record as a collection bundle
+//! - Export of a single report with its patient and images
+//! - Scanlytics-specific fields carried as extensions so bundles round-trip
+
+pub mod controller;
+pub mod models;
+pub mod services;
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use scanlytics_db::Thing;
+
+/// Base URL of the extensions for Scanlytics fields that FHIR has no element for.
+pub const EXTENSION_BASE: &str = "https://scanlytics.app/fhir/StructureDefinition";
+
+/// Code system of the HL7 v2 identifier types used for `Identifier.type`.
+pub const IDENTIFIER_TYPE_SYSTEM: &str = "http://terminology.hl7.org/CodeSystem/v2-0203";
+
+/// A FHIR R4 `Bundle` of type `collection`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    pub resource_type: String,
+    #[serde(rename = "type")]
+    pub bundle_type: String,
+    pub timestamp: String,
+    pub entry: Vec<BundleEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleEntry {
+    pub resource: Resource,
+}
+
+/// The FHIR resources Scanlytics exports, tagged by `resourceType`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "resourceType")]
+pub enum Resource {
+    Patient(Patient),
+    DiagnosticReport(DiagnosticReport),
+    Media(Media),
+    ClinicalImpression(ClinicalImpression),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Patient {
+    pub id: String,
+    pub name: Vec<HumanName>,
+    pub gender: String,
+    pub birth_date: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identifier: Vec<Identifier>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub telecom: Vec<ContactPoint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub address: Vec<Address>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticReport {
+    pub id: String,
+    pub status: String,
+    pub code: CodeableConcept,
+    pub subject: Reference,
+    pub effective_date_time: String,
+    pub issued: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub performer: Vec<Reference>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub results_interpreter: Vec<Reference>,
+    pub conclusion: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<DiagnosticReportMedia>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extension: Vec<Extension>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticReportMedia {
+    pub link: Reference,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Media {
+    pub id: String,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modality: Option<CodeableConcept>,
+    pub subject: Reference,
+    pub created_date_time: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<Reference>,
+    pub content: Attachment,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClinicalImpression {
+    pub id: String,
+    pub status: String,
+    pub subject: Reference,
+    pub date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assessor: Option<Reference>,
+    pub description: String,
+    pub summary: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub note: Vec<Annotation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extension: Vec<Extension>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeableConcept {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coding: Vec<Coding>,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Coding {
+    pub system: String,
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Identifier {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub identifier_type: Option<CodeableConcept>,
+    pub system: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HumanName {
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactPoint {
+    pub system: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Address {
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub content_type: String,
+    /// Base64 encoded file content, left out if the file couldn't be read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    pub text: String,
+}
+
+/// A FHIR extension holding one string, boolean or integer value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Extension {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_string: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_boolean: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_integer: Option<i64>,
+}
+
+/// An `Images_Reports_Join` edge linking an image to a report.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImageLink {
+    #[serde(rename = "in")]
+    pub image: Thing,
+    #[serde(rename = "out")]
+    pub report: Thing,
+}
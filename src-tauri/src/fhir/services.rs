@@ -0,0 +1,698 @@
+use super::models::{self, Resource};
+use crate::audit::models::AuditAction;
+use crate::audit::services::record_audit;
+use crate::auth::session::models::AuthenticatedUser;
+use crate::notes::models::PatientNoteResponse;
+use crate::patients::models::{
+    IdentifierKind, ImportIdentifier, PatientIdentifier, PatientImportRow, PatientResponse,
+};
+use crate::patients::services::{can_access_patient, load_identifiers};
+use crate::reports::models::{CreateReportResponse, ImageResponse, ReportStatus};
+use crate::reports::services::load_visible_report;
+use crate::error::AppError;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use scanlytics_db::{Surreal, Any, Datetime, Thing};
+use serde_json::Value;
+
+
+/// Exports a patient's full record as a FHIR R4 collection bundle.
+///
+/// The bundle contains the patient, their active notes, their current
+/// reports (versions replaced by a signed amendment are left out) and the
+/// images attached to those reports. The export is recorded in the audit
+/// log as a share of the patient.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient_id` - Unique identifier of the patient
+/// * `user` - Authenticated user exporting the record
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Bundle)` - Bundle of Patient, ClinicalImpression, DiagnosticReport and Media resources
+/// * `Err(AppError)` - Error message if the export fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The patient doesn't exist or isn't visible to the user
+/// * Database operations fail
+
+pub async fn export_patient_bundle_service(
+    db: &Surreal<Any>,
+    patient_id: String,
+    user: &AuthenticatedUser,
+) -> Result<models::Bundle, AppError> {
+    let patient = Thing::from(("Patient", patient_id.as_str()));
+    if !can_access_patient(db, &patient, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
+
+    let query = "
+        LET $reports = SELECT VALUE id FROM Report
+            WHERE patient = $patient AND deleted_at IS NONE AND superseded_by IS NONE;
+        SELECT * FROM $patient;
+        SELECT * FROM PatientNote WHERE patient = $patient AND deleted_at IS NONE ORDER BY created_at;
+        SELECT * FROM Report WHERE id INSIDE $reports ORDER BY created_at;
+        SELECT in, out FROM Images_Reports_Join WHERE out INSIDE $reports;
+        SELECT * FROM Image
+            WHERE id INSIDE (SELECT VALUE in FROM Images_Reports_Join WHERE out INSIDE $reports)
+            ORDER BY created_at;
+    ";
+    let mut response = db
+        .query(query)
+        .bind(("patient", patient))
+        .await?;
+
+    let record: Option<PatientResponse> = response.take(1)?;
+    let record = record.ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+    let notes: Vec<PatientNoteResponse> = response.take(2)?;
+    let reports: Vec<CreateReportResponse> = response.take(3)?;
+    let links: Vec<models::ImageLink> = response.take(4)?;
+    let images: Vec<ImageResponse> = response.take(5)?;
+
+    let identifiers = load_identifiers(db, &record.id).await?;
+    let mut resources = vec![patient_to_fhir(&record, &identifiers)];
+    resources.extend(notes.iter().map(note_to_fhir));
+    for report in &reports {
+        let report_images: Vec<Thing> = links
+            .iter()
+            .filter(|link| link.report == report.id)
+            .map(|link| link.image.clone())
+            .collect();
+        resources.push(report_to_fhir(report, &report_images));
+    }
+    resources.extend(images.iter().map(image_resource));
+
+    record_audit(db, user, AuditAction::Share, &record.id, Some(&record.id), None).await?;
+
+    Ok(bundle(resources))
+}
+
+/// Exports a single report with its patient and images as a FHIR R4 bundle.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user exporting the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Bundle)` - Bundle of Patient, DiagnosticReport and Media resources
+/// * `Err(AppError)` - Error message if the export fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report doesn't exist or isn't visible to the user
+/// * Database operations fail
+
+pub async fn export_report_bundle_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    user: &AuthenticatedUser,
+) -> Result<models::Bundle, AppError> {
+    let report = load_visible_report(db, &report_id, user).await?;
+
+    let query = "
+        SELECT * FROM $patient;
+        SELECT * FROM Image
+            WHERE id INSIDE (SELECT VALUE in FROM Images_Reports_Join WHERE out = $report)
+            ORDER BY created_at;
+    ";
+    let mut response = db
+        .query(query)
+        .bind(("patient", report.patient.clone()))
+        .bind(("report", report.id.clone()))
+        .await?;
+
+    let patient: Option<PatientResponse> = response.take(0)?;
+    let patient = patient.ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+    let images: Vec<ImageResponse> = response.take(1)?;
+
+    let image_ids: Vec<Thing> = images.iter().map(|image| image.id.clone()).collect();
+    let identifiers = load_identifiers(db, &patient.id).await?;
+    let mut resources = vec![
+        patient_to_fhir(&patient, &identifiers),
+        report_to_fhir(&report, &image_ids),
+    ];
+    resources.extend(images.iter().map(image_resource));
+
+    record_audit(db, user, AuditAction::Share, &report.id, Some(&report.patient), None).await?;
+
+    Ok(bundle(resources))
+}
+
+/// Maps a patient and their external identifiers to a FHIR `Patient`.
+///
+/// Genders other than FHIR's `male`, `female` and `unknown` are exported as
+/// `other`. Identifier kinds are carried as HL7 v2 identifier type codes.
+
+pub fn patient_to_fhir(patient: &PatientResponse, identifiers: &[PatientIdentifier]) -> Resource {
+    let gender = match patient.gender.trim().to_lowercase().as_str() {
+        "male" => "male",
+        "female" => "female",
+        "" | "unknown" => "unknown",
+        _ => "other",
+    };
+
+    let mut telecom = Vec::new();
+    if !patient.contact_number.trim().is_empty() {
+        telecom.push(models::ContactPoint {
+            system: "phone".to_string(),
+            value: patient.contact_number.clone(),
+        });
+    }
+    let mut address = Vec::new();
+    if !patient.address.trim().is_empty() {
+        address.push(models::Address { text: patient.address.clone() });
+    }
+
+    let identifier = identifiers
+        .iter()
+        .map(|identifier| models::Identifier {
+            identifier_type: identifier.kind.type_code().map(|code| models::CodeableConcept {
+                coding: vec![models::Coding {
+                    system: models::IDENTIFIER_TYPE_SYSTEM.to_string(),
+                    code: code.to_string(),
+                }],
+                text: code.to_string(),
+            }),
+            system: identifier.system.clone(),
+            value: identifier.value.clone(),
+        })
+        .collect();
+
+    Resource::Patient(models::Patient {
+        id: patient.id.id.to_raw(),
+        name: vec![models::HumanName { text: patient.name.clone() }],
+        gender: gender.to_string(),
+        birth_date: fhir_date(&patient.date_of_birth),
+        identifier,
+        telecom,
+        address,
+    })
+}
+
+/// Maps a patient note to a FHIR `ClinicalImpression`.
+///
+/// Symptoms become the description, the diagnosis the summary and the
+/// treatment a note. Severity and urgency are carried as extensions.
+
+pub fn note_to_fhir(note: &PatientNoteResponse) -> Resource {
+    let date = note
+        .created_at
+        .as_ref()
+        .map(fhir_datetime)
+        .unwrap_or_default();
+
+    Resource::ClinicalImpression(models::ClinicalImpression {
+        id: note.id.id.to_raw(),
+        status: "completed".to_string(),
+        subject: reference("Patient", &note.patient),
+        date,
+        assessor: Some(reference("Practitioner", &note.user_owner)),
+        description: note.symptoms.clone(),
+        summary: note.diagnosis.clone(),
+        note: vec![models::Annotation { text: note.treatment.clone() }],
+        extension: vec![
+            string_extension("note-severity", &note.severity),
+            models::Extension {
+                url: extension_url("note-urgent"),
+                value_string: None,
+                value_boolean: Some(note.is_urgent),
+                value_integer: None,
+            },
+        ],
+    })
+}
+
+/// Maps a report to a FHIR `DiagnosticReport` linked to its images.
+///
+/// Drafts are exported as `registered`. The report version and body part
+/// are carried as extensions.
+
+pub fn report_to_fhir(report: &CreateReportResponse, images: &[Thing]) -> Resource {
+    let status = match report.status {
+        ReportStatus::Draft => "registered",
+        ReportStatus::Preliminary => "preliminary",
+        ReportStatus::Final => "final",
+        ReportStatus::Amended => "amended",
+    };
+
+    let mut extension = vec![models::Extension {
+        url: extension_url("report-version"),
+        value_string: None,
+        value_boolean: None,
+        value_integer: Some(i64::from(report.version)),
+    }];
+    if let Some(body_part) = &report.body_part {
+        extension.push(string_extension("body-part", body_part));
+    }
+
+    Resource::DiagnosticReport(models::DiagnosticReport {
+        id: report.id.id.to_raw(),
+        status: status.to_string(),
+        code: models::CodeableConcept {
+            coding: Vec::new(),
+            text: match &report.body_part {
+                Some(body_part) => format!("Imaging report: {}", body_part),
+                None => "Imaging report".to_string(),
+            },
+        },
+        subject: reference("Patient", &report.patient),
+        effective_date_time: fhir_datetime(&report.created_at),
+        issued: fhir_datetime(report.signed_at.as_ref().unwrap_or(&report.updated_at)),
+        performer: vec![reference("Practitioner", &report.user_owner)],
+        results_interpreter: report
+            .signed_by
+            .iter()
+            .map(|signer| reference("Practitioner", signer))
+            .collect(),
+        conclusion: report.report_text.clone(),
+        media: images
+            .iter()
+            .map(|image| models::DiagnosticReportMedia { link: reference("Media", image) })
+            .collect(),
+        extension,
+    })
+}
+
+/// Maps an image to a FHIR `Media` embedding the file content.
+///
+/// The content is embedded as base64 `data`, since paths on this machine
+/// mean nothing to the receiver. Without content the attachment only
+/// carries the content type and title.
+
+pub fn image_to_fhir(image: &ImageResponse, content: Option<&[u8]>) -> Resource {
+    let content_type = match image.file_type.to_lowercase().as_str() {
+        "jpg" | "jpeg" => "image/jpeg".to_string(),
+        other => format!("image/{}", other),
+    };
+
+    Resource::Media(models::Media {
+        id: image.id.id.to_raw(),
+        status: "completed".to_string(),
+        modality: Some(models::CodeableConcept {
+            coding: Vec::new(),
+            text: image.modal_type.clone(),
+        }),
+        subject: reference("Patient", &image.patient),
+        created_date_time: fhir_datetime(&image.created_at),
+        operator: Some(reference("Practitioner", &image.user)),
+        content: models::Attachment {
+            content_type,
+            data: content.map(|bytes| STANDARD.encode(bytes)),
+            size: content.map(|bytes| bytes.len() as u64),
+            title: image.name.clone(),
+        },
+    })
+}
+
+/// Reads the patients of a FHIR bundle, or of a single `Patient`, for import.
+///
+/// Entries that aren't `Patient` resources are skipped. Rows are numbered by
+/// their position in the bundle, starting at 1. Names and addresses given as
+/// parts rather than text are joined. Identifiers without a system or value
+/// are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the content isn't JSON or isn't a Bundle or Patient.
+
+pub fn parse_patient_bundle(content: &str) -> Result<Vec<(usize, PatientImportRow)>, AppError> {
+    let value: Value = serde_json::from_str(content)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid FHIR JSON: {}", e)))?;
+
+    match value["resourceType"].as_str() {
+        Some("Patient") => Ok(vec![(1, patient_import_row(&value))]),
+        Some("Bundle") => Ok(value["entry"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, entry)| entry["resource"]["resourceType"] == "Patient")
+            .map(|(index, entry)| (index + 1, patient_import_row(&entry["resource"])))
+            .collect()),
+        _ => Err(AppError::InvalidInput(
+            "Expected a FHIR Bundle or Patient resource".to_string(),
+        )),
+    }
+}
+
+fn patient_import_row(resource: &Value) -> PatientImportRow {
+    let name = resource["name"].as_array().and_then(|names| names.first()).and_then(|name| {
+        if let Some(text) = name["text"].as_str() {
+            return Some(text.to_string());
+        }
+        let mut parts: Vec<&str> = name["given"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        parts.extend(name["family"].as_str());
+        (!parts.is_empty()).then(|| parts.join(" "))
+    });
+
+    let contact_number = resource["telecom"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|telecom| telecom["system"] == "phone")
+        .and_then(|telecom| telecom["value"].as_str())
+        .map(str::to_string);
+
+    let address = resource["address"].as_array().and_then(|addresses| addresses.first()).and_then(
+        |address| {
+            if let Some(text) = address["text"].as_str() {
+                return Some(text.to_string());
+            }
+            let mut parts: Vec<&str> = address["line"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            for key in ["postalCode", "city", "country"] {
+                parts.extend(address[key].as_str());
+            }
+            (!parts.is_empty()).then(|| parts.join(", "))
+        },
+    );
+
+    let gender = resource["gender"].as_str().map(|gender| match gender {
+        "unknown" => "other".to_string(),
+        gender => gender.to_string(),
+    });
+
+    let identifiers = resource["identifier"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|identifier| identifier["system"].is_string() && identifier["value"].is_string())
+        .map(|identifier| {
+            let kind = identifier["type"]["coding"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|coding| coding["code"].as_str())
+                .find(|code| IdentifierKind::parse(code).is_some());
+            ImportIdentifier {
+                kind: kind.map(str::to_string),
+                system: identifier["system"].as_str().map(str::to_string),
+                value: identifier["value"].as_str().map(str::to_string),
+            }
+        })
+        .collect();
+
+    PatientImportRow {
+        name,
+        date_of_birth: resource["birthDate"].as_str().map(str::to_string),
+        gender,
+        contact_number,
+        address,
+        identifiers,
+    }
+}
+
+fn image_resource(image: &ImageResponse) -> Resource {
+    let content = std::fs::read(&image.path).ok();
+    image_to_fhir(image, content.as_deref())
+}
+
+fn bundle(resources: Vec<Resource>) -> models::Bundle {
+    models::Bundle {
+        resource_type: "Bundle".to_string(),
+        bundle_type: "collection".to_string(),
+        timestamp: fhir_datetime(&Datetime::default()),
+        entry: resources
+            .into_iter()
+            .map(|resource| models::BundleEntry { resource })
+            .collect(),
+    }
+}
+
+fn reference(resource_type: &str, thing: &Thing) -> models::Reference {
+    models::Reference {
+        reference: format!("{}/{}", resource_type, thing.id.to_raw()),
+        display: None,
+    }
+}
+
+fn extension_url(name: &str) -> String {
+    format!("{}/{}", models::EXTENSION_BASE, name)
+}
+
+fn string_extension(name: &str, value: &str) -> models::Extension {
+    models::Extension {
+        url: extension_url(name),
+        value_string: Some(value.to_string()),
+        value_boolean: None,
+        value_integer: None,
+    }
+}
+
+fn fhir_date(datetime: &Datetime) -> String {
+    datetime.0.format("%Y-%m-%d").to_string()
+}
+
+fn fhir_datetime(datetime: &Datetime) -> String {
+    datetime.0.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::models::PatientNoteRequest;
+    use crate::notes::services::create_patient_note_service;
+    use crate::patients::models::{ImportFormat, PatientRequest};
+    use crate::patients::services::{
+        assign_identifiers, create_patient_service, get_patient_identifiers_service,
+        get_patient_service, import_patients_service,
+    };
+    use crate::test_utils::{create_test_user, setup_test_db};
+    use std::path::Path;
+
+    /// Creates a patient with a note and a report with one image stored at `image_path`.
+    async fn create_test_record(
+        db: &Surreal<Any>,
+        doctor: &AuthenticatedUser,
+        image_path: &Path,
+    ) -> (Thing, Thing) {
+        let request = PatientRequest {
+            name: "Test Patient".to_string(),
+            date_of_birth: Datetime::default(),
+            gender: "diverse".to_string(),
+            contact_number: "1234567890".to_string(),
+            address: "Test Address".to_string(),
+            notes: None,
+            reports: None,
+            images: None,
+            primary_doctor: doctor.id.id.to_raw(),
+        };
+        let patient = create_patient_service(db, request, doctor).await.unwrap().id;
+
+        let note = PatientNoteRequest {
+            patient_id: patient.id.to_raw(),
+            symptoms: "Cough".to_string(),
+            diagnosis: "Bronchitis".to_string(),
+            treatment: "Rest".to_string(),
+            severity: "medium".to_string(),
+            is_urgent: true,
+        };
+        create_patient_note_service(db, note, doctor).await.unwrap();
+
+        let report: Option<Thing> = db
+            .query(
+                "
+                LET $image = CREATE ONLY Image SET name = 'scan', path = $path,
+                    patient = $patient, user = $user, file_type = 'png', modal_type = 'xray';
+                LET $report = CREATE ONLY Report SET report_text = 'Findings', body_part = 'thorax',
+                    patient = $patient, user_owner = $user;
+                RELATE ($image.id) -> Images_Reports_Join -> ($report.id);
+                RETURN $report.id;
+                ",
+            )
+            .bind(("patient", patient.clone()))
+            .bind(("user", doctor.id.clone()))
+            .bind(("path", image_path.display().to_string()))
+            .await
+            .unwrap()
+            .take(3)
+            .unwrap();
+        (patient, report.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_patient_bundle_round_trip() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let dir = tempfile::tempdir().unwrap();
+        let (patient, _) = create_test_record(&db, &doctor, &dir.path().join("scan.png")).await;
+        let mrn = PatientIdentifier {
+            kind: IdentifierKind::Mrn,
+            system: "urn:oid:1.2.3.4".to_string(),
+            value: "MRN-1".to_string(),
+        };
+        assign_identifiers(&db, &patient, &[mrn], &doctor).await.unwrap();
+
+        let bundle = export_patient_bundle_service(&db, patient.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let parsed: models::Bundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, bundle);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["resourceType"], "Bundle");
+        assert_eq!(value["type"], "collection");
+        let types: Vec<&str> = value["entry"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["resource"]["resourceType"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, vec!["Patient", "ClinicalImpression", "DiagnosticReport", "Media"]);
+
+        let patient_resource = &value["entry"][0]["resource"];
+        assert_eq!(patient_resource["gender"], "other");
+        assert_eq!(patient_resource["telecom"][0]["system"], "phone");
+        assert_eq!(patient_resource["identifier"][0]["value"], "MRN-1");
+        assert_eq!(patient_resource["identifier"][0]["type"]["coding"][0]["code"], "MR");
+
+        let rows = parse_patient_bundle(&json).unwrap();
+        assert_eq!(
+            rows[0].1.identifiers,
+            vec![ImportIdentifier {
+                kind: Some("MR".to_string()),
+                system: Some("urn:oid:1.2.3.4".to_string()),
+                value: Some("MRN-1".to_string()),
+            }]
+        );
+
+        let impression = &value["entry"][1]["resource"];
+        assert_eq!(impression["summary"], "Bronchitis");
+        assert_eq!(impression["extension"][1]["valueBoolean"], true);
+
+        let report = &value["entry"][2]["resource"];
+        let media = &value["entry"][3]["resource"];
+        assert_eq!(report["status"], "registered");
+        assert_eq!(
+            report["media"][0]["link"]["reference"],
+            format!("Media/{}", media["id"].as_str().unwrap())
+        );
+        assert_eq!(media["content"]["contentType"], "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_report_bundle_requires_visibility() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+        let dir = tempfile::tempdir().unwrap();
+        let (_, report) = create_test_record(&db, &doctor, &dir.path().join("scan.png")).await;
+
+        assert!(export_report_bundle_service(&db, report.id.to_raw(), &other)
+            .await
+            .is_err());
+
+        let bundle = export_report_bundle_service(&db, report.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(bundle.entry.len(), 3);
+        assert!(matches!(bundle.entry[1].resource, Resource::DiagnosticReport(_)));
+
+        let json = serde_json::to_value(&bundle).unwrap();
+        let parsed: models::Bundle = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, bundle);
+    }
+
+    #[tokio::test]
+    async fn test_exported_bundle_imports() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let dir = tempfile::tempdir().unwrap();
+        let image_path = dir.path().join("scan.png");
+        std::fs::write(&image_path, b"\x89PNG scan").unwrap();
+        let (patient, _) = create_test_record(&db, &doctor, &image_path).await;
+        let mrn = PatientIdentifier {
+            kind: IdentifierKind::Mrn,
+            system: "urn:oid:1.2.3.4".to_string(),
+            value: "MRN-1".to_string(),
+        };
+        assign_identifiers(&db, &patient, &[mrn], &doctor).await.unwrap();
+
+        let bundle = export_patient_bundle_service(&db, patient.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        let json = serde_json::to_string(&bundle).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        for (entry, exported) in value["entry"].as_array().unwrap().iter().zip(&bundle.entry) {
+            let resource: Resource = serde_json::from_value(entry["resource"].clone()).unwrap();
+            assert_eq!(resource, exported.resource);
+        }
+
+        let Resource::Media(media) = &bundle.entry[3].resource else {
+            panic!("expected a Media resource");
+        };
+        let data = STANDARD.decode(media.content.data.as_ref().unwrap()).unwrap();
+        assert_eq!(data, b"\x89PNG scan");
+        assert_eq!(media.content.size, Some(data.len() as u64));
+        assert!(value["entry"][3]["resource"]["content"].get("url").is_none());
+
+        let target = setup_test_db().await;
+        let receiver = create_test_user(&target, "receiver@test.com", None).await;
+        let summary = import_patients_service(&target, ImportFormat::Fhir, json, &receiver)
+            .await
+            .unwrap();
+        assert_eq!((summary.imported, summary.duplicates, summary.invalid), (1, 0, 0));
+        assert_eq!(summary.rows.len(), 1);
+
+        let imported = get_patient_service(&target, &receiver).await.unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].name, "Test Patient");
+        assert_eq!(imported[0].gender, "other");
+        assert_eq!(imported[0].contact_number, "1234567890");
+        assert_eq!(imported[0].address, "Test Address");
+        assert_eq!(fhir_date(&imported[0].date_of_birth), fhir_date(&Datetime::default()));
+
+        let identifiers =
+            get_patient_identifiers_service(&target, imported[0].id.id.to_raw(), &receiver)
+                .await
+                .unwrap();
+        assert_eq!(identifiers.len(), 1);
+        assert_eq!(identifiers[0].kind, IdentifierKind::Mrn);
+        assert_eq!(identifiers[0].system, "urn:oid:1.2.3.4");
+        assert_eq!(identifiers[0].value, "MRN-1");
+    }
+
+    #[test]
+    fn test_image_without_content_has_no_data() {
+        let image = ImageResponse {
+            id: Thing::from(("Image", "scan")),
+            name: "scan".to_string(),
+            path: "/missing/scan.jpg".to_string(),
+            patient: Thing::from(("Patient", "one")),
+            user: Thing::from(("User", "one")),
+            file_type: "JPG".to_string(),
+            modal_type: "xray".to_string(),
+            created_at: Datetime::default(),
+            updated_at: Datetime::default(),
+        };
+        let Resource::Media(media) = image_resource(&image) else {
+            panic!("expected a Media resource");
+        };
+        assert_eq!(media.content.content_type, "image/jpeg");
+        assert_eq!((media.content.data, media.content.size), (None, None));
+    }
+}
@@ -0,0 +1,109 @@
+use super::models;
+use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+
+/// Generates the HL7 ORU^R01 message for a final report without sending it.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Hl7Message)` - The message and its control id
+/// * `Err(AppError)` - Error message if the report isn't final or visible
+
+#[tauri::command]
+pub async fn generate_report_hl7(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+) -> Result<models::Hl7Message, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::generate_report_oru_service(&db, report_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Sends a final report as an HL7 ORU^R01 message over MLLP.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Hl7Ack)` - The receiver's acknowledgement
+/// * `Err(AppError)` - Error message if sending fails or the message is rejected
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * No HL7 receiver is configured for the organization
+/// * The report isn't final or visible
+/// * The receiver is unreachable, times out or rejects the message
+
+#[tauri::command]
+pub async fn send_report_hl7(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+) -> Result<models::Hl7Ack, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        services::send_report_oru_service(db_connection, report_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Sets the HL7 receiver of the admin's organization.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `settings` - JSON string containing the receiver host, port and MSH identification
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Hl7Settings)` - The stored settings
+/// * `Err(AppError)` - Error message if the update fails
+///
+/// Only admins can call this command.
+
+#[tauri::command]
+pub async fn update_hl7_settings(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    settings: String,
+) -> Result<models::Hl7Settings, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let settings: models::Hl7Settings = serde_json::from_str(&settings)
+            .map_err(|e| AppError::invalid_input("HL7 settings", e))?;
+
+        services::update_hl7_settings_service(&db, settings, &user)
+            .await
+    })
+    .await
+}
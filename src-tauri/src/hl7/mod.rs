@@ -0,0 +1,22 @@
+//! # HL7 v2 Module
+//! 
+//! This module sends finalized reports to RIS/HIS systems as HL7 v2 results, including:
+//! - ORU^R01 message generation with PID, OBR and OBX segments
+//! - HL7 escaping of delimiters in patient and report data
+//! - MLLP transport with acknowledgement handling
+//! 
+//! ## Components
+//! 
+//! - [`controller`]: Tauri command handlers for HL7 messaging
+//! - [`services`]: Message generation and MLLP sending
+//! - [`models`]: HL7 settings and acknowledgement structures
+//! 
+//! ## Main Features
+//! 
+//! - Preview of the ORU^R01 message for a final report
+//! - Sending results to the receiver configured for the organization
+//! - Per-organization receiver, application and facility settings
+
+pub mod controller;
+pub mod models;
+pub mod services;
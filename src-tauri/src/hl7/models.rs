@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// Application name used in MSH-3 when the organization hasn't set one.
+pub const DEFAULT_SENDING_APPLICATION: &str = "SCANLYTICS";
+
+/// HL7 receiver and identification settings of an organization.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hl7Settings {
+    pub host: String,
+    pub port: u16,
+    pub sending_application: String,
+    pub sending_facility: String,
+    pub receiving_application: String,
+    pub receiving_facility: String,
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrganizationHl7 {
+    pub hl7: Option<Hl7Settings>,
+}
+
+/// A generated HL7 message and its MSH-10 control id.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Hl7Message {
+    pub control_id: String,
+    pub message: String,
+}
+
+/// The MSA segment of an acknowledgement.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Hl7Ack {
+    pub code: String,
+    pub control_id: String,
+    pub text: Option<String>,
+}
+
+impl Hl7Ack {
+    /// Whether the receiver accepted the message (`AA` or `CA`).
+    pub fn is_accepted(&self) -> bool {
+        matches!(self.code.as_str(), "AA" | "CA")
+    }
+}
@@ -0,0 +1,601 @@
+use super::models;
+use crate::audit::models::AuditAction;
+use crate::audit::services::record_audit;
+use crate::auth::session::models::AuthenticatedUser;
+use crate::reports::models::{CreateReportResponse, ReportDocumentInfo, ReportStatus};
+use crate::reports::services::{load_document_info, load_visible_report};
+use crate::error::AppError;
+use crate::validation::models::ValidationErrors;
+use std::time::Duration;
+
+use scanlytics_db::{Surreal, Any, Datetime, DbConnection, Id};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const START_BLOCK: u8 = 0x0b;
+const END_BLOCK: u8 = 0x1c;
+const CARRIAGE_RETURN: u8 = 0x0d;
+
+
+/// Generates the ORU^R01 message for a final report without sending it.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Hl7Message)` - The message and its control id
+/// * `Err(AppError)` - Error message if the report can't be sent as a result
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report isn't visible to the user
+/// * The report isn't final
+/// * Database operations fail
+
+pub async fn generate_report_oru_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    user: &AuthenticatedUser,
+) -> Result<models::Hl7Message, AppError> {
+    let settings = load_hl7_settings(db, user).await?.unwrap_or_default();
+    let (report, info) = load_final_report(db, &report_id, user).await?;
+
+    Ok(new_message(&report, &info, &settings))
+}
+
+/// Sends a final report as an ORU^R01 message to the organization's receiver.
+///
+/// The database lock is released while waiting for the receiver, so a slow
+/// or unreachable receiver doesn't block the rest of the application. An
+/// accepted message is recorded in the audit log as a share of the report.
+///
+/// # Arguments
+///
+/// * `db_connection` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user sending the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Hl7Ack)` - The receiver's acknowledgement
+/// * `Err(AppError)` - Error message if sending fails or the message is rejected
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The organization has no HL7 receiver configured
+/// * The report isn't visible to the user or isn't final
+/// * The receiver is unreachable, times out or rejects the message
+
+pub async fn send_report_oru_service(
+    db_connection: &DbConnection,
+    report_id: String,
+    user: &AuthenticatedUser,
+) -> Result<models::Hl7Ack, AppError> {
+    let (settings, report, message) = {
+        let db = db_connection.get().lock().await;
+        let settings = load_hl7_settings(&db, user)
+            .await?
+            .ok_or_else(|| {
+                AppError::OperationFailed(
+                    "No HL7 receiver is configured for the organization".to_string(),
+                )
+            })?;
+        let (report, info) = load_final_report(&db, &report_id, user).await?;
+        let message = new_message(&report, &info, &settings);
+        (settings, report, message)
+    };
+
+    let response = send_mllp(
+        &settings.host,
+        settings.port,
+        Duration::from_secs(settings.timeout_secs),
+        &message.message,
+    )
+    .await?;
+    let ack = parse_ack(&response)?;
+
+    if ack.control_id != message.control_id {
+        return Err(AppError::OperationFailed(format!(
+            "Acknowledgement is for message {} instead of {}",
+            ack.control_id, message.control_id
+        )));
+    }
+    if !ack.is_accepted() {
+        return Err(AppError::OperationFailed(format!(
+            "Receiver rejected the message ({}): {}",
+            ack.code,
+            ack.text.as_deref().unwrap_or("no reason given")
+        )));
+    }
+
+    let db = db_connection.get().lock().await;
+    record_audit(&db, user, AuditAction::Share, &report.id, Some(&report.patient), None).await?;
+
+    Ok(ack)
+}
+
+/// Sets the HL7 receiver of the admin's organization.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `settings` - Receiver address and MSH identification
+/// * `user` - Authenticated admin
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Hl7Settings)` - The stored settings
+/// * `Err(AppError)` - Error message if the update fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The user is not an admin or has no organization
+/// * The host or port is missing, or the timeout is zero
+
+pub async fn update_hl7_settings_service(
+    db: &Surreal<Any>,
+    settings: models::Hl7Settings,
+    user: &AuthenticatedUser,
+) -> Result<models::Hl7Settings, AppError> {
+    if !user.is_admin() {
+        return Err(AppError::Forbidden("Only admins can change the HL7 settings".to_string()));
+    }
+    let organization = user
+        .organization
+        .clone()
+        .ok_or_else(|| AppError::Forbidden("User doesn't belong to an organization".to_string()))?;
+    let mut errors = ValidationErrors::new();
+    if settings.host.trim().is_empty() {
+        errors.push("host", "HL7 receiver host is required");
+    }
+    if settings.port == 0 {
+        errors.push("port", "HL7 receiver port is required");
+    }
+    if settings.timeout_secs == 0 {
+        errors.push("timeout_secs", "HL7 acknowledgement timeout must be at least one second");
+    }
+    errors.into_result(())?;
+
+    db.query("UPDATE $organization SET hl7 = $settings")
+        .bind(("organization", organization))
+        .bind(("settings", settings.clone()))
+        .await?
+        .check()?;
+
+    Ok(settings)
+}
+
+/// Builds an ORU^R01 message (HL7 v2.5.1) for a report.
+///
+/// Each line of the report text becomes a TX observation. Amendments are
+/// sent with result status `C` (corrected), other reports with `F`.
+
+pub fn build_oru_r01(
+    report: &CreateReportResponse,
+    info: &ReportDocumentInfo,
+    settings: &models::Hl7Settings,
+    control_id: &str,
+    timestamp: &Datetime,
+) -> String {
+    let sending_application = match settings.sending_application.trim() {
+        "" => models::DEFAULT_SENDING_APPLICATION,
+        application => application,
+    };
+    let result_status = if report.amends.is_some() { "C" } else { "F" };
+
+    let msh = format!(
+        "MSH|^~\\&|{}|{}|{}|{}|{}||ORU^R01^ORU_R01|{}|P|2.5.1||||||UNICODE UTF-8",
+        escape(sending_application),
+        escape(&settings.sending_facility),
+        escape(&settings.receiving_application),
+        escape(&settings.receiving_facility),
+        hl7_datetime(timestamp),
+        escape(control_id)
+    );
+
+    let patient = &info.patient;
+    let sex = match patient.gender.to_lowercase().as_str() {
+        "male" => "M",
+        "female" => "F",
+        "other" => "O",
+        _ => "U",
+    };
+    let mut pid = fields("PID", 13);
+    pid[1] = "1".to_string();
+    let mut patient_ids = vec![format!(
+        "{}^^^{}^MR",
+        escape(&patient.id.id.to_raw()),
+        models::DEFAULT_SENDING_APPLICATION
+    )];
+    patient_ids.extend(patient.identifiers.iter().map(|identifier| {
+        format!(
+            "{}^^^{}^{}",
+            escape(&identifier.value),
+            escape(&identifier.system),
+            identifier.kind.type_code().unwrap_or_default()
+        )
+    }));
+    pid[3] = patient_ids.join("~");
+    pid[5] = person_name(&patient.name, '^');
+    pid[7] = patient.date_of_birth.0.format("%Y%m%d").to_string();
+    pid[8] = sex.to_string();
+    pid[11] = escape(patient.address.as_deref().unwrap_or_default());
+    pid[13] = escape(patient.contact_number.as_deref().unwrap_or_default());
+
+    let service = match &report.body_part {
+        Some(body_part) => format!("IMG^Imaging report {}^L", escape(body_part)),
+        None => "IMG^Imaging report^L".to_string(),
+    };
+    let mut obr = fields("OBR", 32);
+    obr[1] = "1".to_string();
+    obr[3] = format!("{}^{}", escape(&report.id.id.to_raw()), models::DEFAULT_SENDING_APPLICATION);
+    obr[4] = service.clone();
+    obr[7] = hl7_datetime(&report.created_at);
+    obr[22] = hl7_datetime(report.signed_at.as_ref().unwrap_or(&report.updated_at));
+    obr[25] = result_status.to_string();
+    if let Some(signer) = &info.signer {
+        obr[32] = format!("&{}", person_name(signer, '&'));
+    }
+
+    let mut segments = vec![msh, pid.join("|"), obr.join("|")];
+    for (index, line) in report.report_text.lines().enumerate() {
+        let mut obx = fields("OBX", 11);
+        obx[1] = (index + 1).to_string();
+        obx[2] = "TX".to_string();
+        obx[3] = service.clone();
+        obx[5] = escape(line);
+        obx[11] = result_status.to_string();
+        segments.push(obx.join("|"));
+    }
+
+    segments.join("\r")
+}
+
+/// Escapes HL7 delimiters in a field value.
+///
+/// Line breaks, which would end the segment, are replaced with spaces.
+
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\E\\"),
+            '|' => escaped.push_str("\\F\\"),
+            '^' => escaped.push_str("\\S\\"),
+            '&' => escaped.push_str("\\T\\"),
+            '~' => escaped.push_str("\\R\\"),
+            '\r' | '\n' => escaped.push(' '),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Sends a message over MLLP and returns the acknowledgement message.
+///
+/// # Errors
+///
+/// Returns an error if the connection fails, the receiver closes the
+/// connection before acknowledging, or no acknowledgement arrives in time.
+
+pub async fn send_mllp(
+    host: &str,
+    port: u16,
+    timeout: Duration,
+    message: &str,
+) -> Result<String, AppError> {
+    let exchange = async {
+        let mut stream = TcpStream::connect((host, port))
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to connect to {}:{}: {}", host, port, e)))?;
+
+        let mut frame = Vec::with_capacity(message.len() + 3);
+        frame.push(START_BLOCK);
+        frame.extend_from_slice(message.as_bytes());
+        frame.extend_from_slice(&[END_BLOCK, CARRIAGE_RETURN]);
+        stream
+            .write_all(&frame)
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to send message: {}", e)))?;
+
+        let mut response = Vec::new();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = stream
+                .read(&mut buffer)
+                .await
+                .map_err(|e| AppError::Network(format!("Failed to read acknowledgement: {}", e)))?;
+            if read == 0 {
+                return Err(AppError::Network(
+                    "Connection closed before acknowledgement".to_string(),
+                ));
+            }
+            response.extend_from_slice(&buffer[..read]);
+
+            if let Some(end) = response
+                .windows(2)
+                .position(|window| window == [END_BLOCK, CARRIAGE_RETURN])
+            {
+                let start = response
+                    .iter()
+                    .position(|byte| *byte == START_BLOCK)
+                    .map_or(0, |start| start + 1);
+                return String::from_utf8(response[start.min(end)..end].to_vec())
+                    .map_err(|e| {
+                        AppError::OperationFailed(format!(
+                            "Acknowledgement is not valid UTF-8: {}",
+                            e
+                        ))
+                    });
+            }
+        }
+    };
+
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| {
+            AppError::Network(format!(
+                "No acknowledgement from {}:{} within {:?}",
+                host, port, timeout
+            ))
+        })?
+}
+
+/// Reads the MSA segment of an acknowledgement message.
+
+pub fn parse_ack(message: &str) -> Result<models::Hl7Ack, AppError> {
+    let msa = message
+        .split(['\r', '\n'])
+        .find(|segment| segment.starts_with("MSA|"))
+        .ok_or_else(|| AppError::OperationFailed("Acknowledgement has no MSA segment".to_string()))?;
+    let fields: Vec<&str> = msa.split('|').collect();
+
+    Ok(models::Hl7Ack {
+        code: fields.get(1).copied().unwrap_or_default().to_string(),
+        control_id: fields.get(2).copied().unwrap_or_default().to_string(),
+        text: fields
+            .get(3)
+            .filter(|text| !text.is_empty())
+            .map(|text| text.to_string()),
+    })
+}
+
+/// Loads a visible, final report with its document information.
+
+async fn load_final_report(
+    db: &Surreal<Any>,
+    report_id: &str,
+    user: &AuthenticatedUser,
+) -> Result<(CreateReportResponse, ReportDocumentInfo), AppError> {
+    let report = load_visible_report(db, report_id, user).await?;
+    if report.status != ReportStatus::Final {
+        return Err(AppError::OperationFailed(format!(
+            "A {} report cannot be sent as a result",
+            report.status
+        )));
+    }
+    let info = load_document_info(db, &report.id).await?;
+    Ok((report, info))
+}
+
+async fn load_hl7_settings(
+    db: &Surreal<Any>,
+    user: &AuthenticatedUser,
+) -> Result<Option<models::Hl7Settings>, AppError> {
+    let Some(organization) = user.organization.clone() else {
+        return Ok(None);
+    };
+    let organization: Option<models::OrganizationHl7> = db
+        .query("SELECT hl7 FROM $organization")
+        .bind(("organization", organization))
+        .await?
+        .take(0)?;
+    Ok(organization.and_then(|organization| organization.hl7))
+}
+
+fn new_message(
+    report: &CreateReportResponse,
+    info: &ReportDocumentInfo,
+    settings: &models::Hl7Settings,
+) -> models::Hl7Message {
+    let control_id = Id::rand().to_raw();
+    let message = build_oru_r01(report, info, settings, &control_id, &Datetime::default());
+    models::Hl7Message { control_id, message }
+}
+
+/// A segment with `count` empty fields after the segment name.
+fn fields(segment: &str, count: usize) -> Vec<String> {
+    let mut fields = vec![String::new(); count + 1];
+    fields[0] = segment.to_string();
+    fields
+}
+
+/// Formats a full name as family and given name components.
+fn person_name(name: &str, separator: char) -> String {
+    let mut parts: Vec<&str> = name.split_whitespace().collect();
+    let family = parts.pop().unwrap_or_default();
+    format!("{}{}{}", escape(family), separator, escape(&parts.join(" ")))
+}
+
+fn hl7_datetime(datetime: &Datetime) -> String {
+    datetime.0.format("%Y%m%d%H%M%S+0000").to_string()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::session::models::ADMIN_ROLE;
+    use crate::patients::models::{IdentifierKind, PatientIdentifier};
+    use crate::reports::models::PatientDemographics;
+    use crate::test_utils::{create_test_user, setup_test_db};
+    use scanlytics_db::Thing;
+    use tokio::net::TcpListener;
+
+    fn final_report(text: &str) -> (CreateReportResponse, ReportDocumentInfo) {
+        let patient = Thing::from(("Patient", "p1"));
+        let report = CreateReportResponse {
+            id: Thing::from(("Report", "r1")),
+            patient: patient.clone(),
+            user_owner: Thing::from(("User", "u1")),
+            report_text: text.to_string(),
+            body_part: Some("thorax".to_string()),
+            sections: None,
+            status: ReportStatus::Final,
+            version: 1,
+            signed_by: Some(Thing::from(("User", "u1"))),
+            signed_at: Some(Datetime::default()),
+            amends: None,
+            superseded_by: None,
+            created_at: Datetime::default(),
+            updated_at: Datetime::default(),
+            deleted_at: None,
+        };
+        let info = ReportDocumentInfo {
+            author: "Jane Doe".to_string(),
+            signer: Some("Jane Doe".to_string()),
+            patient: PatientDemographics {
+                id: patient,
+                name: "Max O'Neil Muster".to_string(),
+                date_of_birth: Datetime::try_from("1975-06-01T00:00:00Z").unwrap(),
+                gender: "male".to_string(),
+                contact_number: Some("555-0100".to_string()),
+                address: Some("1 Main St | Apt 2".to_string()),
+                identifiers: Vec::new(),
+            },
+        };
+        (report, info)
+    }
+
+    #[test]
+    fn test_escape_delimiters() {
+        assert_eq!(escape(r"a|b^c&d~e\f"), r"a\F\b\S\c\T\d\R\e\E\f");
+        assert_eq!(escape("line\r\nbreak"), "line  break");
+    }
+
+    #[test]
+    fn test_oru_r01_segments() {
+        let (report, info) = final_report("No fracture.\nFollow-up in 2^3 weeks");
+        let settings = models::Hl7Settings {
+            sending_facility: "HOSP".to_string(),
+            receiving_application: "RIS".to_string(),
+            ..Default::default()
+        };
+        let message = build_oru_r01(&report, &info, &settings, "CTRL1", &report.created_at);
+        let segments: Vec<&str> = message.split('\r').collect();
+
+        assert_eq!(segments.len(), 5);
+        assert!(segments[0].starts_with("MSH|^~\\&|SCANLYTICS|HOSP|RIS||"));
+        assert!(segments[0].contains("|ORU^R01^ORU_R01|CTRL1|P|2.5.1|"));
+
+        let pid: Vec<&str> = segments[1].split('|').collect();
+        assert_eq!(pid[3], "p1^^^SCANLYTICS^MR");
+        assert_eq!(pid[5], "Muster^Max O'Neil");
+        assert_eq!(pid[7], "19750601");
+        assert_eq!(pid[8], "M");
+        assert_eq!(pid[11], "1 Main St \\F\\ Apt 2");
+
+        let obr: Vec<&str> = segments[2].split('|').collect();
+        assert_eq!(obr.len(), 33);
+        assert_eq!(obr[25], "F");
+        assert_eq!(obr[32], "&Doe&Jane");
+
+        assert_eq!(segments[3], "OBX|1|TX|IMG^Imaging report thorax^L||No fracture.||||||F");
+        assert!(segments[4].contains("|Follow-up in 2\\S\\3 weeks|"));
+    }
+
+    #[test]
+    fn test_oru_r01_patient_identifiers() {
+        let (report, mut info) = final_report("No fracture.");
+        info.patient.identifiers = vec![PatientIdentifier {
+            kind: IdentifierKind::Mrn,
+            system: "HOSP".to_string(),
+            value: "MRN-1".to_string(),
+        }];
+        let message = build_oru_r01(
+            &report,
+            &info,
+            &models::Hl7Settings::default(),
+            "CTRL1",
+            &report.created_at,
+        );
+
+        let pid: Vec<&str> = message.split('\r').nth(1).unwrap().split('|').collect();
+        assert_eq!(pid[3], "p1^^^SCANLYTICS^MR~MRN-1^^^HOSP^MR");
+    }
+
+    #[tokio::test]
+    async fn test_mllp_exchange_with_local_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let receiver = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !received.ends_with(&[END_BLOCK, CARRIAGE_RETURN]) {
+                let read = socket.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..read]);
+            }
+            let ack = b"\x0bMSH|^~\\&|RIS||SCANLYTICS||20240101000000||ACK^R01|A1|P|2.5.1\rMSA|AA|CTRL1\x1c\x0d";
+            socket.write_all(ack).await.unwrap();
+            received
+        });
+
+        let response = send_mllp("127.0.0.1", port, Duration::from_secs(5), "MSH|test")
+            .await
+            .unwrap();
+        let ack = parse_ack(&response).unwrap();
+        assert!(ack.is_accepted());
+        assert_eq!(ack.control_id, "CTRL1");
+
+        let received = receiver.await.unwrap();
+        assert_eq!(received, b"\x0bMSH|test\x1c\x0d");
+    }
+
+    #[tokio::test]
+    async fn test_mllp_times_out_without_ack() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let _receiver = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            drop(socket);
+        });
+
+        let result = send_mllp("127.0.0.1", port, Duration::from_millis(200), "MSH|test").await;
+        assert!(matches!(result.unwrap_err(), AppError::Network(message) if message.contains("No acknowledgement")));
+    }
+
+    #[tokio::test]
+    async fn test_settings_require_host_port_and_timeout() {
+        let db = setup_test_db().await;
+        let admin = AuthenticatedUser {
+            role: ADMIN_ROLE.to_string(),
+            ..create_test_user(&db, "admin@test.com", Some("hospital")).await
+        };
+
+        let settings = models::Hl7Settings {
+            timeout_secs: 0,
+            ..Default::default()
+        };
+        let error = update_hl7_settings_service(&db, settings, &admin).await.unwrap_err();
+        let fields: Vec<&str> = error
+            .field_errors()
+            .iter()
+            .map(|error| error.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["host", "port", "timeout_secs"]);
+    }
+}
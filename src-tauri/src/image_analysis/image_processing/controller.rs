@@ -1,5 +1,8 @@
 use super::models;
 use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
 use tauri::State;
 use scanlytics_db::DbConnection;
 
@@ -9,45 +12,45 @@ use scanlytics_db::DbConnection;
 /// # Arguments
 ///
 /// * `image_data` - JSON string containing image data
-/// * `user_name` - Authenticated user's name
 /// * `model_name` - Name of the ML model to use
 /// * `app_handle` - Tauri application handle
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(AnalysisResponse)` - Analysis results and statements
-/// * `Err(String)` - Error message if processing fails
+/// * `Err(AppError)` - Error message if processing fails
 ///
 /// # Security
 ///
-/// Requires valid authentication token for model access
+/// Requires an active session; the session user's token is used for model access
 #[tauri::command]
-pub async fn process_images<'a>(
+pub async fn process_images(
     image_data: String,
-    user_name: String,
     model_name: String,   
     app_handle: tauri::AppHandle,
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
-) -> Result<models::AnalysisResponse, String> {
-    let db = db_connection.get().lock().await;
-    
-    let user_name = serde_json::from_str(&user_name)
-        .map_err(|e| format!("Tauri: Failed to parse report request : {}", e))?;
-    
-    let model_name = serde_json::from_str(&model_name)
-        .map_err(|e| format!("Tauri: Failed to parse report request : {}", e))?;
+) -> Result<models::AnalysisResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
 
-    let response: models::AnalysisResponse = services::process_images_service(
-        image_data, 
-        user_name, 
-        model_name, 
-        app_handle,
-        &db
-    )
+        let model_name = serde_json::from_str(&model_name)
+            .map_err(|e| AppError::invalid_input("model name", e))?;
+
+        let response: models::AnalysisResponse = services::process_images_service(
+            image_data, 
+            user.email, 
+            model_name, 
+            app_handle,
+            &db
+        )
+        .await?;
+
+        Ok(response)
+    })
     .await
-    .map_err(|e| e.to_string())?;
-    
-    Ok(response)
-}
\ No newline at end of file
+}
@@ -9,20 +9,20 @@ pub struct ImageData {
     pub data: Vec<u8>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AnalysisResponse {
     pub results: Vec<ImageResult>,
     pub statements: Vec<StatementResponse>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ImageResult {
     pub filename: String,
     pub image_type: String,
     pub confidence: f32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StatementResponse {
     pub indication: String,
     pub statement: String,
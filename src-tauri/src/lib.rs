@@ -11,6 +11,18 @@
 //! - **Notes**: Patient notes and management
 //! - **Reports**: Medical report management
 //! - **Image Analysis**: Image analysis and processing
+//! - **Audit**: Audit trail of access to patient data
+//! - **Trash**: Soft deletion, restore and purge of clinical records
+//! - **FHIR**: HL7 FHIR R4 exchange with hospital systems
+//! - **HL7 v2**: ORU^R01 results over MLLP
+//! - **DICOMweb**: PACS study search and series retrieval
+//! - **DICOM**: SR and Secondary Capture export of AI results
+//! - **Search**: Full-text search over notes and reports
+//! - **Timeline**: Chronological view of a patient's case
+//! - **Validation**: Field-level validation of patient and note input
+//! 
+//! Every command returns [`error::AppError`] on failure, a serializable error
+//! with a code the frontend can branch on.
 //! 
 //! ## Architecture
 //! 
@@ -23,12 +35,26 @@
 #[macro_use]
 pub mod commands;
 
+pub mod error;
+
 pub mod users;
 pub mod auth;
 pub mod patients;
 pub mod notes;
 pub mod reports;
 pub mod image_analysis;
+pub mod audit;
+pub mod trash;
+pub mod fhir;
+pub mod hl7;
+pub mod dicomweb;
+pub mod dicom;
+pub mod search;
+pub mod timeline;
+pub mod validation;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
 
 
 
@@ -37,12 +63,16 @@ pub mod image_analysis;
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .manage(auth::session::models::SessionState::default())
         .setup(|app| {
             tauri::async_runtime::block_on(async {
                 if let Err(e) = scanlytics_db::setup_database(app).await {
                     eprintln!("Failed to setup database: {:?}", e);
                 }
             });
+            auth::validate::services::init_jwks_store(app.handle());
+            auth::session::services::spawn_session_watcher(app.handle().clone());
+            trash::services::spawn_purge_job(app.handle().clone());
             Ok(())
         })
         .invoke_handler(get_commands!()) 
@@ -1,6 +1,11 @@
 use super::models;
 use super::services;
 
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::validation::services::validate_note_request;
+use crate::error::AppError;
+
 use tauri::State;
 use scanlytics_db::DbConnection;
 
@@ -10,6 +15,7 @@ use scanlytics_db::DbConnection;
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 /// * `patient_note_request` - JSON string containing note data
 ///
@@ -17,57 +23,71 @@ use scanlytics_db::DbConnection;
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(PatientNoteResponse)` - Successfully created note
-/// * `Err(String)` - Error message if creation fails
+/// * `Err(AppError)` - Error message if creation fails
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// * The note request JSON is invalid
+/// * A field is invalid, reported with its field errors
 /// * Database operations fail
-/// * Patient or user references are invalid
+/// * The patient reference is invalid
+///
+/// The note owner is always the authenticated session user.
 #[tauri::command]
 pub async fn create_patient_note(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
     patient_note_request: String,
-) -> Result<models::PatientNoteResponse, String> {
-    let db = db_connection.get().lock().await;
-    let patient_note_request: models::PatientNoteRequest =
-        serde_json::from_str(&patient_note_request)
-            .map_err(|e| format!("Failed to parse patient note request: {}", e))?;
-
-    let  note: models::PatientNoteResponse =
-        services::create_patient_note_service(&db, patient_note_request).await?;
-
-    Ok(note)
+) -> Result<models::PatientNoteResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let patient_note_request: models::PatientNoteRequest =
+            serde_json::from_str(&patient_note_request)
+                .map_err(|e| AppError::invalid_input("patient note request", e))?;
+        let patient_note_request = validate_note_request(patient_note_request)?;
+
+        let note: models::PatientNoteResponse =
+            services::create_patient_note_service(&db, patient_note_request, &user).await?;
+
+        Ok(note)
+    })
+    .await
 }
 
-/// Retrieves all patient notes with associated patient information.
+/// Retrieves the patient notes visible to the session user with associated patient information.
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Vec<PatientNoteWithPatientResponse>)` - List of notes with patient details
-/// * `Err(String)` - Error message if retrieval fails
+/// * `Err(AppError)` - Error message if retrieval fails
 #[tauri::command]
 pub async fn get_patient_notes(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>
-) -> Result<Vec<models::PatientNoteWithPatientResponse>, String> {
-    let db = db_connection.get().lock().await;
-    let response = services::get_patient_notes_service(&db)
-        .await
-        .map_err(|e| e.to_string())?;
+) -> Result<Vec<models::PatientNoteWithPatientResponse>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let response = services::get_patient_notes_service(&db, &user).await?;
 
-    Ok(response)
+        Ok(response)
+    })
+    .await
 }
 
 /// Updates an existing patient note.
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 /// * `id` - Unique identifier of the note to update
 /// * `patient_note_request` - JSON string containing updated note data
@@ -76,88 +96,104 @@ pub async fn get_patient_notes(
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(PatientNoteResponse)` - Updated note record
-/// * `Err(String)` - Error message if update fails
+/// * `Err(AppError)` - Error message if update fails
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// * The note request JSON is invalid
+/// * A field is invalid, reported with its field errors
 /// * The specified note ID doesn't exist
+/// * The session user does not own the note
 /// * Database operations fail
 
 #[tauri::command]
 pub async fn update_patient_note(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
     id: String,
     patient_note_request: String,
-) -> Result<models::PatientNoteResponse, String> {
-    let db = db_connection.get().lock().await;
-
-    let patient_note_request: models::PatientNoteRequest =
-        serde_json::from_str(&patient_note_request)
-            .map_err(|e| format!("Failed to parse patient note request: {}", e))?;
-
-    let updated_record =
-        services::update_patient_note_service(&db, id, patient_note_request).await?;
-
-    if let Some(record) = updated_record {
-        let response = models::PatientNoteResponse {
-            id: record.id,
-            patient: record.patient,
-            symptoms: record.symptoms,
-            diagnosis: record.diagnosis,
-            treatment: record.treatment,
-            is_urgent: record.is_urgent,
-            severity: record.severity,
-            user_owner: record.user_owner,
-            created_at: record.created_at,
-            updated_at: record.updated_at,
-        };
-
-        Ok(response)
-    } else {
-        Err("No record updated".to_string())
-    }
+) -> Result<models::PatientNoteResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+
+        let patient_note_request: models::PatientNoteRequest =
+            serde_json::from_str(&patient_note_request)
+                .map_err(|e| AppError::invalid_input("patient note request", e))?;
+        let patient_note_request = validate_note_request(patient_note_request)?;
+
+        let updated_record =
+            services::update_patient_note_service(&db, id, patient_note_request, &user).await?;
+
+        if let Some(record) = updated_record {
+            let response = models::PatientNoteResponse {
+                id: record.id,
+                patient: record.patient,
+                symptoms: record.symptoms,
+                diagnosis: record.diagnosis,
+                treatment: record.treatment,
+                is_urgent: record.is_urgent,
+                severity: record.severity,
+                user_owner: record.user_owner,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+                deleted_at: record.deleted_at,
+            };
+
+            Ok(response)
+        } else {
+            Err(AppError::NotFound("No record updated".to_string()))
+        }
+    })
+    .await
 }
 
 
-/// Deletes a patient note from the system.
+/// Moves a patient note to the trash.
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 /// * `id` - Unique identifier of the note to delete
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
-/// * `Ok(PatientNoteResponse)` - Deleted note record
-/// * `Err(String)` - Error message if deletion fails
+/// * `Ok(PatientNoteResponse)` - Trashed note record
+/// * `Err(AppError)` - Error message if deletion fails
 
 #[tauri::command]
 pub async fn delete_patient_note(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
     id: String,
-) -> Result<models::PatientNoteResponse, String> {
-    let db = db_connection.get().lock().await;
-    let deleted_record = services::delete_patient_note_service(&db, id).await?;
-
-    if let Some(record) = deleted_record {
-        let response = models::PatientNoteResponse {
-            id: record.id,
-            patient: record.patient,
-            symptoms: record.symptoms,
-            diagnosis: record.diagnosis,
-            treatment: record.treatment,
-            is_urgent: record.is_urgent,
-            severity: record.severity,
-            user_owner: record.user_owner,
-            created_at: record.created_at,
-            updated_at: record.updated_at,
-        };
-
-        Ok(response)
-    } else {
-        Err("No record deleted".to_string())
-    }
+) -> Result<models::PatientNoteResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let deleted_record = services::delete_patient_note_service(&db, id, &user).await?;
+
+        if let Some(record) = deleted_record {
+            let response = models::PatientNoteResponse {
+                id: record.id,
+                patient: record.patient,
+                symptoms: record.symptoms,
+                diagnosis: record.diagnosis,
+                treatment: record.treatment,
+                is_urgent: record.is_urgent,
+                severity: record.severity,
+                user_owner: record.user_owner,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+                deleted_at: record.deleted_at,
+            };
+
+            Ok(response)
+        } else {
+            Err(AppError::NotFound("No record deleted".to_string()))
+        }
+    })
+    .await
 }
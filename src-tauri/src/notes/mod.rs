@@ -17,7 +17,7 @@
 //! 
 //! - Complete CRUD operations for patient notes
 //! - Urgent note flagging
-//! - Severity tracking
+//! - Severity tracking with the levels low, medium and high
 //! - Integration with patient records
 //! - User ownership tracking
 
@@ -9,7 +9,6 @@ pub struct PatientNoteRequest {
     pub treatment: String,
     pub severity: String,
     pub is_urgent: bool,
-    pub user_owner: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -25,6 +24,7 @@ pub struct PatientResponse {
     pub images: Option<Vec<Thing>>,
     pub created_at: Datetime,
     pub updated_at: Datetime,
+    pub deleted_at: Option<Datetime>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -58,6 +58,7 @@ pub struct PatientNoteResponse {
     pub user_owner: Thing,
     pub created_at: Option<Datetime>,
     pub updated_at: Option<Datetime>,
+    pub deleted_at: Option<Datetime>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
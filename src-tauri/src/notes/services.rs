@@ -1,48 +1,54 @@
 use super::models::{
     PatientNoteRecord, PatientNoteRequest, PatientNoteResponse, PatientNoteWithPatientResponse,
-    PatientResponse, UserResponse,
+    PatientResponse,
 };
+use crate::audit::models::AuditAction;
+use crate::audit::services::{audit_changes, audit_entry, merge_changes, record_reads};
+use crate::auth::session::models::AuthenticatedUser;
+use crate::patients::services::can_access_patient;
+use crate::error::AppError;
 
-use scanlytics_db::Error as SurrealError;
-use scanlytics_db::{Any, Surreal, Thing};
+use scanlytics_db::{Any, Datetime, Id, Surreal, Thing};
 
 /// Creates a new patient note with associated relationships.
 ///
+/// The note is always owned by the authenticated user.
+///
 /// # Arguments
 ///
 /// * `db` - Database connection
 /// * `data` - Note creation request data
+/// * `user` - Authenticated user creating the note
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(PatientNoteResponse)` - Successfully created note
-/// * `Err(String)` - Error message if creation fails
+/// * `Err(AppError)` - Error message if creation fails
 ///
 /// # Errors
 ///
 /// This function will return an error if:
-/// * Referenced patient doesn't exist
-/// * Referenced user doesn't exist
+/// * Referenced patient doesn't exist or isn't treated by the user
 /// * Note creation fails
 /// * Relationship updates fail
 
 pub async fn create_patient_note_service(
     db: &Surreal<Any>,
     data: PatientNoteRequest,
-) -> Result<PatientNoteResponse, String> {
+    user: &AuthenticatedUser,
+) -> Result<PatientNoteResponse, AppError> {
     let patient: Option<PatientResponse> = db
         .select(("Patient", &data.patient_id))
-        .await
-        .map_err(|e| e.to_string())?;
-    let patient = patient.ok_or_else(|| "Patient not found".to_string())?;
-
-    let user_owner: Option<UserResponse> = db
-        .select(("User", &data.user_owner))
-        .await
-        .map_err(|e| e.to_string())?;
-    let user_owner = user_owner.ok_or_else(|| "User not found".to_string())?;
+        .await?;
+    let patient = patient
+        .filter(|patient| patient.deleted_at.is_none())
+        .ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+    if !can_access_patient(db, &patient.id, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
 
+    let note_id = Thing::from(("PatientNote", Id::rand()));
     let patient_note_record = PatientNoteRecord {
         patient: patient.id.clone(),
         symptoms: data.symptoms,
@@ -50,53 +56,55 @@ pub async fn create_patient_note_service(
         treatment: data.treatment,
         severity: data.severity,
         is_urgent: data.is_urgent,
-        user_owner: user_owner.id.clone(),
-
+        user_owner: user.id.clone(),
     };
-
-    let note: PatientNoteResponse = db
-        .create("PatientNote")
-        .content(patient_note_record)
-        .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Failed to create patient note".to_string())?;
-
-    let patient_id = patient.id.clone();
-    let note_id = note.id.clone();
-    let user_owner_id = data.user_owner.clone();
-
-    db.query("UPDATE type::thing($table, $id) SET notes += $note")
-        .bind(("table", "Patient"))
-        .bind(("id", patient_id))
+    let changes = audit_changes(None::<&PatientNoteRecord>, Some(&patient_note_record));
+    let audit = vec![audit_entry(user, AuditAction::Create, &note_id, Some(&patient.id), changes)];
+
+    let create = "
+        BEGIN TRANSACTION;
+        CREATE $note CONTENT $record;
+        UPDATE $patient SET notes += $note;
+        UPDATE $user SET notes += $note;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    db.query(create)
         .bind(("note", note_id.clone()))
-        .await
-        .map_err(|e| e.to_string())?;
-
-    db.query("UPDATE type::thing($table, $id) SET notes += $note")
-        .bind(("table", "User"))
-        .bind(("id", user_owner_id))
-        .bind(("note", note_id))
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(note)
+        .bind(("record", patient_note_record))
+        .bind(("patient", patient.id.clone()))
+        .bind(("user", user.id.clone()))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
+
+    let note: Option<PatientNoteResponse> = db
+        .select(("PatientNote", note_id.id.to_raw()))
+        .await?;
+    note.ok_or_else(|| AppError::OperationFailed("Failed to create patient note".to_string()))
 }
 
-/// Retrieves all patient notes with associated patient information.
+/// Retrieves the patient notes visible to the user with associated patient information.
+///
+/// A note is visible when the user owns it or treats the note's patient.
+/// Admins see every note. Notes in the trash, or whose patient is in the
+/// trash, are excluded.
 ///
 /// # Arguments
 ///
 /// * `db` - Database connection
+/// * `user` - Authenticated user
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Vec<PatientNoteWithPatientResponse>)` - List of notes with patient details
-/// * `Err(SurrealError)` - Database error if query fails
+/// * `Err(AppError)` - Error message if the query fails
 
 pub async fn get_patient_notes_service(
     db: &Surreal<Any>,
-) -> Result<Vec<PatientNoteWithPatientResponse>, SurrealError> {
+    user: &AuthenticatedUser,
+) -> Result<Vec<PatientNoteWithPatientResponse>, AppError> {
     let query = "
         SELECT
             id,
@@ -110,31 +118,52 @@ pub async fn get_patient_notes_service(
             created_at,
             updated_at
         FROM PatientNote
+        WHERE deleted_at IS NONE
+            AND patient.deleted_at IS NONE
+            AND ($is_admin
+                OR user_owner = $user
+                OR $user INSIDE patient->Treated_By->User)
         FETCH patient, user_owner;
     ";
-    let result: Vec<PatientNoteWithPatientResponse> = db.query(query).await?.take(0)?;
+    let result: Vec<PatientNoteWithPatientResponse> = db
+        .query(query)
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?
+        .take(0)?;
+
+    let reads = result
+        .iter()
+        .map(|note| (note.id.clone(), Some(note.patient.id.clone())))
+        .collect();
+    record_reads(db, user, reads).await?;
+
     Ok(result)
 }
 
 /// Updates an existing patient note.
 ///
+/// Only the note owner, or a user whose role permits it, may edit a note.
+/// Ownership is never transferred by an update.
+///
 /// # Arguments
 ///
 /// * `db` - Database connection
 /// * `id` - Unique identifier of the note
 /// * `data` - Updated note data
+/// * `user` - Authenticated user performing the update
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Option<PatientNoteResponse>)` - Updated note if found
-/// * `Err(String)` - Error message if update fails
+/// * `Err(AppError)` - Error message if update fails
 ///
 /// # Errors
 ///
 /// This function will return an error if:
+/// * The user is not permitted to edit the note
 /// * Referenced patient doesn't exist
-/// * Referenced user doesn't exist
 /// * Note update fails
 
 
@@ -142,265 +171,280 @@ pub async fn update_patient_note_service(
     db: &Surreal<Any>,
     id: String,
     data: PatientNoteRequest,
-) -> Result<Option<PatientNoteResponse>, String> {
-    let patient: Option<Thing> = db
-        .select(("Patient", &data.patient_id))
-        .await
-        .map_err(|e| e.to_string())?;
-    let patient = patient.ok_or_else(|| "Patient not found".to_string())?;
+    user: &AuthenticatedUser,
+) -> Result<Option<PatientNoteResponse>, AppError> {
+    let existing: Option<PatientNoteResponse> = db
+        .select(("PatientNote", &id))
+        .await?;
+    let Some(existing) = existing.filter(|note| note.deleted_at.is_none()) else {
+        return Ok(None);
+    };
+    ensure_note_access(&existing, user)?;
 
-    let user_owner: Option<Thing> = db
-        .select(("User", &data.user_owner))
-        .await
-        .map_err(|e| e.to_string())?;
-    let user_owner = user_owner.ok_or_else(|| "Patient not found".to_string())?;
+    let patient: Option<PatientResponse> = db
+        .select(("Patient", &data.patient_id))
+        .await?;
+    let patient = patient
+        .filter(|patient| patient.deleted_at.is_none())
+        .ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+    if !can_access_patient(db, &patient.id, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
 
     let updated_note = PatientNoteRecord {
-        patient: patient,
+        patient: patient.id.clone(),
         symptoms: data.symptoms,
         diagnosis: data.diagnosis,
         treatment: data.treatment,
         severity: data.severity,
         is_urgent: data.is_urgent,
-        user_owner: user_owner,
-      
-
+        user_owner: existing.user_owner.clone(),
     };
 
-    let updated: Option<PatientNoteResponse> = db
-        .update(("PatientNote", id))
-        .merge(updated_note)
-        .await
-        .map_err(|e| e.to_string())?;
+    let changes = merge_changes(&existing, &updated_note);
+    let audit = vec![audit_entry(user, AuditAction::Update, &existing.id, Some(&patient.id), changes)];
 
-    Ok(updated)
+    let update = "
+        BEGIN TRANSACTION;
+        UPDATE $note MERGE $record;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    db.query(update)
+        .bind(("note", existing.id.clone()))
+        .bind(("record", updated_note))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
+
+    db.select(("PatientNote", id)).await.map_err(AppError::from)
 }
 
-/// Deletes a patient note from the system.
+/// Moves a patient note to the trash.
+///
+/// Only the note owner, or a user whose role permits it, may delete a note.
+/// The note can be restored from the trash until it is purged.
 ///
 /// # Arguments
 ///
 /// * `db` - Database connection
 /// * `id` - Unique identifier of the note to delete
+/// * `user` - Authenticated user performing the deletion
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
-/// * `Ok(Option<PatientNoteResponse>)` - Deleted note if found
-/// * `Err(String)` - Error message if deletion fails
+/// * `Ok(Option<PatientNoteResponse>)` - Trashed note if found
+/// * `Err(AppError)` - Error message if deletion fails
 
 pub async fn delete_patient_note_service(
     db: &Surreal<Any>,
     id: String,
-) -> Result<Option<PatientNoteResponse>, String> {
-    let deleted: Option<PatientNoteResponse> = db
-        .delete(("PatientNote", id))
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(deleted)
+    user: &AuthenticatedUser,
+) -> Result<Option<PatientNoteResponse>, AppError> {
+    let existing: Option<PatientNoteResponse> = db
+        .select(("PatientNote", &id))
+        .await?;
+    let Some(existing) = existing.filter(|note| note.deleted_at.is_none()) else {
+        return Ok(None);
+    };
+    ensure_note_access(&existing, user)?;
+
+    let now = Datetime::default();
+    let changes = merge_changes(&existing, &serde_json::json!({ "deleted_at": now }));
+    let audit = vec![audit_entry(user, AuditAction::Delete, &existing.id, Some(&existing.patient), changes)];
+
+    let delete = "
+        BEGIN TRANSACTION;
+        UPDATE $note SET deleted_at = $now, deleted_by = $user;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    db.query(delete)
+        .bind(("note", existing.id.clone()))
+        .bind(("now", now))
+        .bind(("user", user.id.clone()))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
+
+    db.select(("PatientNote", id)).await.map_err(AppError::from)
+}
+
+/// Rejects access to notes the user neither owns nor may manage.
+
+fn ensure_note_access(note: &PatientNoteResponse, user: &AuthenticatedUser) -> Result<(), AppError> {
+    if user.can_modify(&note.user_owner) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("Not permitted to modify a note owned by another user".to_string()))
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use scanlytics_db::{Datetime, Thing};
-    use serde_json::json;
+    use crate::auth::session::models::ADMIN_ROLE;
+    use crate::test_utils::{create_test_patient, create_test_user, note_request, setup_test_db};
+    use scanlytics_db::Thing;
 
-    async fn setup_test_db() -> Surreal<Any> {
-        let db_conn = scanlytics_db::init_db(None, true).await.unwrap();
-        let db = db_conn.get().lock().await;
-        db.clone()
-    }
+    #[tokio::test]
+    async fn test_delete_patient_note() {
+        let db = setup_test_db().await;
+        let user = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_test_patient(&db, &user).await;
 
-    async fn create_test_user(db: &Surreal<Any>) -> (Thing, String) {
-        let user_data = json!({
-            "name": "Test Doctor",
-            "email": "doctor@test.com",
-            "role": "user",
-            "organization": null,
-            "patients": [],
-            "notes": [],
-            "statements": [],
-            "images": [],
-            "reports": [],
-            "created_at": Datetime::default(),
-            "updated_at": Datetime::default()
-        });
-
-        let created: UserResponse = db
-            .create("User")
-            .content(user_data.clone())
+        let created_note = create_patient_note_service(&db, note_request(&patient), &user)
             .await
-            .unwrap()
             .unwrap();
+        let note_id = created_note.id.id.to_raw();
+
+        let result = delete_patient_note_service(&db, note_id, &user).await;
+        assert!(result.is_ok());
 
+        let notes = get_patient_notes_service(&db, &user).await.unwrap();
+        assert!(notes.iter().all(|note| note.id != created_note.id));
+    }
 
-        let id_str = created.id.to_string();
-        let id_only = id_str.split(':').nth(1).unwrap_or("").to_string();
+    #[tokio::test]
+    async fn test_create_patient_note_invalid_patient() {
+        let db = setup_test_db().await;
+        let user = create_test_user(&db, "doctor@test.com", None).await;
 
-        (created.id.clone(), id_only)
+        let patient = Thing::from(("Patient", "nonexistent_patient"));
+        let result = create_patient_note_service(&db, note_request(&patient), &user).await;
+        assert_eq!(result.unwrap_err(), AppError::NotFound("Patient not found".to_string()));
     }
 
-    async fn create_test_patient(db: &Surreal<Any>) -> (Thing, String) {
-        let patient_data = json!({
-            "name": "Test Patient",
-            "date_of_birth": Datetime::default(),
-            "gender": "M",
-            "contact_number": "1234567890",
-            "address": "Test Address",
-            "notes": [],
-            "reports": [],
-            "images": [],
-            "created_at": Datetime::default(),
-            "updated_at": Datetime::default()
-        });
-
-        let created: PatientResponse = db
-            .create("Patient")
-            .content(patient_data.clone())
+    #[tokio::test]
+    async fn test_note_owner_comes_from_session() {
+        let db = setup_test_db().await;
+        let user = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_test_patient(&db, &user).await;
+
+        let note = create_patient_note_service(&db, note_request(&patient), &user)
             .await
-            .unwrap()
             .unwrap();
 
-
-        let id_str = created.id.to_string();
-        let id_only = id_str.split(':').nth(1).unwrap_or("").to_string();
-
-        (created.id.clone(), id_only)
+        assert_eq!(note.user_owner, user.id);
     }
-   
 
     #[tokio::test]
-    async fn test_delete_patient_note() {
+    async fn test_update_note_of_other_user_rejected() {
         let db = setup_test_db().await;
-        let (_, user_id) = create_test_user(&db).await;
-        let (_, patient_id) = create_test_patient(&db).await;
-
-    
-        let note_request = PatientNoteRequest {
-            patient_id,
-            symptoms: "Test symptoms".to_string(),
-            diagnosis: "Test diagnosis".to_string(),
-            treatment: "Test treatment".to_string(),
-            severity: "Mild".to_string(),
-            is_urgent: false,
-            user_owner: user_id,
+        let owner = create_test_user(&db, "owner@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+        let admin = AuthenticatedUser {
+            role: ADMIN_ROLE.to_string(),
+            ..create_test_user(&db, "admin@test.com", None).await
         };
+        let patient = create_test_patient(&db, &owner).await;
 
-        let created_note = create_patient_note_service(&db, note_request).await.unwrap();
-        let note_id = created_note.id.to_string().split(':').nth(1).unwrap_or("").to_string();
+        let note = create_patient_note_service(&db, note_request(&patient), &owner)
+            .await
+            .unwrap();
+        let note_id = note.id.id.to_raw();
+        let urgent = || PatientNoteRequest {
+            severity: "high".to_string(),
+            is_urgent: true,
+            ..note_request(&patient)
+        };
 
-     
-        let result = delete_patient_note_service(&db, note_id).await;
-        assert!(result.is_ok());
+        let result = update_patient_note_service(&db, note_id.clone(), urgent(), &other).await;
+        assert!(result.is_err());
 
-  
-        let notes = get_patient_notes_service(&db).await.unwrap();
-        assert!(notes.iter().all(|note| note.id != created_note.id));
+        let deleted = delete_patient_note_service(&db, note_id.clone(), &other).await;
+        assert!(deleted.is_err());
+
+        let updated = update_patient_note_service(&db, note_id, urgent(), &admin)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.user_owner, owner.id);
+        assert!(updated.is_urgent);
     }
 
     #[tokio::test]
-    async fn test_create_patient_note_invalid_patient() {
+    async fn test_notes_scoped_to_owner() {
         let db = setup_test_db().await;
-        let (_, user_id) = create_test_user(&db).await;
-
-        let note_request = PatientNoteRequest {
-            patient_id: "nonexistent_patient".to_string(),
-            symptoms: "Test symptoms".to_string(),
-            diagnosis: "Test diagnosis".to_string(),
-            treatment: "Test treatment".to_string(),
-            severity: "Mild".to_string(),
-            is_urgent: false,
-            user_owner: user_id,
-        };
+        let owner = create_test_user(&db, "owner@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+        let patient = create_test_patient(&db, &owner).await;
 
-        let result = create_patient_note_service(&db, note_request).await;
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Patient not found");
+        create_patient_note_service(&db, note_request(&patient), &owner)
+            .await
+            .unwrap();
+
+        let result = create_patient_note_service(&db, note_request(&patient), &other).await;
+        assert_eq!(result.unwrap_err(), AppError::NotFound("Patient not found".to_string()));
+
+        let owner_notes = get_patient_notes_service(&db, &owner).await.unwrap();
+        assert_eq!(owner_notes.len(), 1);
+
+        let other_notes = get_patient_notes_service(&db, &other).await.unwrap();
+        assert!(other_notes.is_empty());
     }
 
     #[tokio::test]
-    async fn test_create_patient_note_invalid_user() {
+    async fn test_invalid_severity_rejected_by_schema() {
         let db = setup_test_db().await;
-        let (_, patient_id) = create_test_patient(&db).await;
-
-        let note_request = PatientNoteRequest {
-            patient_id,
-            symptoms: "Test symptoms".to_string(),
-            diagnosis: "Test diagnosis".to_string(),
-            treatment: "Test treatment".to_string(),
-            severity: "Mild".to_string(),
-            is_urgent: false,
-            user_owner: "nonexistent_user".to_string(),
-        };
+        let user = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_test_patient(&db, &user).await;
 
-        let result = create_patient_note_service(&db, note_request).await;
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "User not found");
+        let request = PatientNoteRequest {
+            severity: "critical".to_string(),
+            ..note_request(&patient)
+        };
+        let error = create_patient_note_service(&db, request, &user).await.unwrap_err();
+        assert_eq!(error.field_errors()[0].field, "severity");
     }
 
     #[tokio::test]
     async fn test_delete_nonexistent_note() {
         let db = setup_test_db().await;
-        
-        let result = delete_patient_note_service(&db, "nonexistent_note".to_string()).await;
-        assert!(result.is_ok());
+        let user = create_test_user(&db, "doctor@test.com", None).await;
+
+        let result = delete_patient_note_service(&db, "nonexistent_note".to_string(), &user).await;
         assert!(result.unwrap().is_none());
     }
 
     #[tokio::test]
     async fn test_get_patient_notes_empty() {
         let db = setup_test_db().await;
-        
-        let result = get_patient_notes_service(&db).await;
-        assert!(result.is_ok());
-        
-        let notes = result.unwrap();
+        let user = create_test_user(&db, "doctor@test.com", None).await;
+
+        let notes = get_patient_notes_service(&db, &user).await.unwrap();
         assert!(notes.is_empty());
     }
 
     #[tokio::test]
     async fn test_multiple_notes_for_patient() {
         let db = setup_test_db().await;
-        let (_, user_id) = create_test_user(&db).await;
-        let (_, patient_id) = create_test_patient(&db).await;
+        let user = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_test_patient(&db, &user).await;
 
-     
-        let note_request1 = PatientNoteRequest {
-            patient_id: patient_id.clone(),
+        let first = PatientNoteRequest {
             symptoms: "First symptoms".to_string(),
             diagnosis: "First diagnosis".to_string(),
             treatment: "First treatment".to_string(),
-            severity: "Mild".to_string(),
-            is_urgent: false,
-            user_owner: user_id.clone(),
+            ..note_request(&patient)
         };
-
-        let note_request2 = PatientNoteRequest {
-            patient_id,
+        let second = PatientNoteRequest {
             symptoms: "Second symptoms".to_string(),
             diagnosis: "Second diagnosis".to_string(),
             treatment: "Second treatment".to_string(),
-            severity: "Severe".to_string(),
+            severity: "high".to_string(),
             is_urgent: true,
-            user_owner: user_id,
+            ..note_request(&patient)
         };
 
-        create_patient_note_service(&db, note_request1).await.unwrap();
-        create_patient_note_service(&db, note_request2).await.unwrap();
+        create_patient_note_service(&db, first, &user).await.unwrap();
+        create_patient_note_service(&db, second, &user).await.unwrap();
 
-        let result = get_patient_notes_service(&db).await;
-        assert!(result.is_ok());
-
-        let notes = result.unwrap();
+        let notes = get_patient_notes_service(&db, &user).await.unwrap();
         assert_eq!(notes.len(), 2);
-        
-  
-        let has_mild_note = notes.iter().any(|note| note.severity == "Mild");
-        let has_severe_note = notes.iter().any(|note| note.severity == "Severe");
-        assert!(has_mild_note);
-        assert!(has_severe_note);
+        assert!(notes.iter().any(|note| note.severity == "low"));
+        assert!(notes.iter().any(|note| note.severity == "high"));
     }
-
 }
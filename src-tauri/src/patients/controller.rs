@@ -1,5 +1,9 @@
 use super::models;
 use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::validation::services::validate_patient_request;
+use crate::error::AppError;
 
 use scanlytics_db::DbConnection;
 use tauri::State;
@@ -9,6 +13,7 @@ use tauri::State;
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 /// * `patient_request` - JSON string containing patient data
 ///
@@ -16,64 +21,111 @@ use tauri::State;
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(PatientResponse)` - Successfully created patient record
-/// * `Err(String)` - Error message if creation fails
+/// * `Err(AppError)` - Error message if creation fails
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// * The patient request JSON is invalid
+/// * A field is invalid, reported with its field errors
 /// * Database operations fail
 /// * Required relationships cannot be established
 
 #[tauri::command]
 pub async fn create_patient(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
     patient_request: String,
-) -> Result<models::PatientResponse, String> {
-    let db = db_connection.get().lock().await;
-    let patient_request: models::PatientRequest = serde_json::from_str(&patient_request)
-        .map_err(|e| format!("Failed to parse patient request: {}", e))?;
-    
-    let response: models::PatientResponse =
-        services::create_patient_service(&db, patient_request).await?;
-    Ok(response)
+) -> Result<models::PatientResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let patient_request: models::PatientRequest = serde_json::from_str(&patient_request)
+            .map_err(|e| AppError::invalid_input("patient request", e))?;
+        let patient_request = validate_patient_request(patient_request)?;
+
+        let response: models::PatientResponse =
+            services::create_patient_service(&db, patient_request, &user).await?;
+        Ok(response)
+    })
+    .await
 }
 
-/// Retrieves all patient records from the system.
+/// Retrieves the patient records visible to the session user.
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
-/// * `Ok(Vec<PatientResponse>)` - List of all patient records
-/// * `Err(String)` - Error message if retrieval fails
+/// * `Ok(Vec<PatientResponse>)` - List of visible patient records
+/// * `Err(AppError)` - Error message if retrieval fails
 
 #[tauri::command]
 pub async fn get_patients(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
-) -> Result<Vec<models::PatientResponse>, String> {
-    let db = db_connection.get().lock().await;
-    let response: Vec<models::PatientResponse> = services::get_patient_service(&db)
-        .await?
-        .into_iter()
-        .map(|record| models::PatientResponse {
-            id: record.id,
-            name: record.name,
-            date_of_birth: record.date_of_birth,
-            gender: record.gender,
-            contact_number: record.contact_number,
-            address: record.address,
-            notes: record.notes,
-            reports: record.reports,
-            images: record.images,
-            created_at: record.created_at,
-            updated_at: record.updated_at,
-        })
-        .collect();
-    Ok(response)
+) -> Result<Vec<models::PatientResponse>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let response: Vec<models::PatientResponse> = services::get_patient_service(&db, &user)
+            .await?
+            .into_iter()
+            .map(|record| models::PatientResponse {
+                id: record.id,
+                name: record.name,
+                date_of_birth: record.date_of_birth,
+                gender: record.gender,
+                contact_number: record.contact_number,
+                address: record.address,
+                notes: record.notes,
+                reports: record.reports,
+                images: record.images,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+                deleted_at: record.deleted_at,
+            })
+            .collect();
+        Ok(response)
+    })
+    .await
+}
+
+
+/// Searches the patient records visible to the session user.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `request` - JSON string containing filters, sort order, page size and cursor
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PatientSearchResponse)` - One page of matching patients and the next cursor
+/// * `Err(AppError)` - Error message if the search fails
+
+#[tauri::command]
+pub async fn search_patients(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    request: String,
+) -> Result<models::PatientSearchResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let request: models::PatientSearchRequest = serde_json::from_str(&request)
+            .map_err(|e| AppError::invalid_input("patient search", e))?;
+
+        services::search_patients_service(&db, request, &user)
+            .await
+    })
+    .await
 }
 
 
@@ -81,6 +133,7 @@ pub async fn get_patients(
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 /// * `id` - Unique identifier of the patient to update
 /// * `patient_request` - JSON string containing updated patient data
@@ -89,94 +142,386 @@ pub async fn get_patients(
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(PatientResponse)` - Updated patient record
-/// * `Err(String)` - Error message if update fails
+/// * `Err(AppError)` - Error message if update fails
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// * The patient request JSON is invalid
+/// * A field is invalid, reported with its field errors
 /// * The specified patient ID doesn't exist
 /// * Database operations fail
 
 #[tauri::command]
 pub async fn update_patient(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
     id: String,
     patient_request: String,
-) -> Result<models::PatientResponse, String> {
-    let db = db_connection.get().lock().await;
-    let patient_request: models::PatientRequest = serde_json::from_str(&patient_request)
-        .map_err(|e| format!("Failed to parse patient request: {}", e))?;
-
-   
-    let updated_record = services::update_patient_service(&db, id, patient_request).await?;
-
-    if let Some(record) = updated_record {
-        let response = models::PatientResponse {
-            id: record.id,
-            name: record.name,
-            date_of_birth: record.date_of_birth,
-            gender: record.gender,
-            contact_number: record.contact_number,
-            address: record.address,
-            notes: record.notes,
-            reports: record.reports,
-            images: record.images,
-            created_at: record.created_at,
-            updated_at: record.updated_at,
-        };
+) -> Result<models::PatientResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let patient_request: models::PatientRequest = serde_json::from_str(&patient_request)
+            .map_err(|e| AppError::invalid_input("patient request", e))?;
+        let patient_request = validate_patient_request(patient_request)?;
 
-        Ok(response)
-    } else {
-        Err("No record updated".to_string())
-    }
+        let updated_record =
+            services::update_patient_service(&db, id, patient_request, &user).await?;
+
+        if let Some(record) = updated_record {
+            let response = models::PatientResponse {
+                id: record.id,
+                name: record.name,
+                date_of_birth: record.date_of_birth,
+                gender: record.gender,
+                contact_number: record.contact_number,
+                address: record.address,
+                notes: record.notes,
+                reports: record.reports,
+                images: record.images,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+                deleted_at: record.deleted_at,
+            };
+
+            Ok(response)
+        } else {
+            Err(AppError::NotFound("No record updated".to_string()))
+        }
+    })
+    .await
 }
 
 
-/// Deletes a patient record from the system.
+/// Moves a patient record to the trash.
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 /// * `id` - Unique identifier of the patient to delete
+/// * `policy` - `restrict` to refuse while notes or reports exist, or
+///   `archive` (default) to trash them together with the patient
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
-/// * `Ok(PatientResponse)` - Deleted patient record
-/// * `Err(String)` - Error message if deletion fails
+/// * `Ok(PatientResponse)` - Trashed patient record
+/// * `Err(AppError)` - Error message if deletion fails
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// * The specified patient ID doesn't exist
+/// * The policy is `restrict` and the patient has active notes or reports
 /// * Database operations fail
 
 #[tauri::command]
 pub async fn delete_patient(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
     id: String,
-) -> Result<models::PatientResponse, String> {
-    let db = db_connection.get().lock().await;
-    let deleted_record = services::delete_patient_service(&db, id).await?;
-
-    if let Some(record) = deleted_record {
-        let response = models::PatientResponse {
-            id: record.id,
-            name: record.name,
-            date_of_birth: record.date_of_birth,
-            gender: record.gender,
-            contact_number: record.contact_number,
-            address: record.address,
-            notes: record.notes,
-            reports: record.reports,
-            images: record.images,
-            created_at: record.created_at,
-            updated_at: record.updated_at,
-        };
+    policy: Option<models::CascadePolicy>,
+) -> Result<models::PatientResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let deleted_record =
+            services::delete_patient_service(&db, id, policy.unwrap_or_default(), &user).await?;
 
-        Ok(response)
-    } else {
-        Err("No record deleted".to_string())
-    }
+        if let Some(record) = deleted_record {
+            let response = models::PatientResponse {
+                id: record.id,
+                name: record.name,
+                date_of_birth: record.date_of_birth,
+                gender: record.gender,
+                contact_number: record.contact_number,
+                address: record.address,
+                notes: record.notes,
+                reports: record.reports,
+                images: record.images,
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+                deleted_at: record.deleted_at,
+            };
+
+            Ok(response)
+        } else {
+            Err(AppError::NotFound("No record deleted".to_string()))
+        }
+    })
+    .await
+}
+
+
+/// Shares a patient with another user of the same organization.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `patient_id` - Unique identifier of the patient to share
+/// * `user_id` - Unique identifier of the user to share with
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(())` - Patient shared, or already shared
+/// * `Err(AppError)` - Error message if sharing fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The patient isn't visible to the session user
+/// * The target user doesn't exist or belongs to another organization
+/// * Database operations fail
+
+#[tauri::command]
+pub async fn share_patient(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    patient_id: String,
+    user_id: String,
+) -> Result<(), AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::share_patient_service(&db, patient_id, user_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Imports patients in bulk from a FHIR bundle or a CSV file.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `format` - `fhir` or `csv`
+/// * `content` - FHIR JSON or CSV text
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(ImportSummary)` - Imported, duplicate and invalid rows with per-row errors
+/// * `Err(AppError)` - Error message if the content can't be read
+///
+/// Imported patients are linked to the session user via `Treated_By`.
+
+#[tauri::command]
+pub async fn import_patients(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    format: models::ImportFormat,
+    content: String,
+) -> Result<models::ImportSummary, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::import_patients_service(&db, format, content, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Lists pairs of patients that are likely duplicates.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<DuplicateCandidate>)` - Candidate pairs with their score and matching signals
+/// * `Err(AppError)` - Error message if the patients can't be loaded
+
+#[tauri::command]
+pub async fn find_duplicate_patients(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+) -> Result<Vec<models::DuplicateCandidate>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::find_duplicate_patients_service(&db, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Merges a duplicate patient into another patient.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `survivor_id` - Unique identifier of the patient to keep
+/// * `duplicate_id` - Unique identifier of the patient to merge away
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PatientMergeSummary)` - The surviving patient and the moved records
+/// * `Err(AppError)` - Error message if the merge fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * Either patient isn't visible to the session user
+/// * Database operations fail
+
+#[tauri::command]
+pub async fn merge_patients(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    survivor_id: String,
+    duplicate_id: String,
+) -> Result<models::PatientMergeSummary, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::merge_patients_service(&db, survivor_id, duplicate_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Retrieves the external identifiers of a patient.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `patient_id` - Unique identifier of the patient
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<PatientIdentifierResponse>)` - The patient's identifiers
+/// * `Err(AppError)` - Error message if the patient isn't visible
+
+#[tauri::command]
+pub async fn get_patient_identifiers(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    patient_id: String,
+) -> Result<Vec<models::PatientIdentifierResponse>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::get_patient_identifiers_service(&db, patient_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Adds an external identifier such as an MRN to a patient.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `patient_id` - Unique identifier of the patient
+/// * `identifier` - JSON string containing the kind, system and value
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PatientIdentifierResponse)` - The stored identifier
+/// * `Err(AppError)` - Error message if the identifier can't be added
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The patient isn't visible to the session user
+/// * The identifier already belongs to another patient
+
+#[tauri::command]
+pub async fn add_patient_identifier(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    patient_id: String,
+    identifier: String,
+) -> Result<models::PatientIdentifierResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let identifier: models::PatientIdentifier = serde_json::from_str(&identifier)
+            .map_err(|e| AppError::invalid_input("patient identifier", e))?;
+
+        services::add_patient_identifier_service(&db, patient_id, identifier, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Removes an external identifier from a patient.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `patient_id` - Unique identifier of the patient
+/// * `identifier_id` - Unique identifier of the identifier record
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or failure
+
+#[tauri::command]
+pub async fn remove_patient_identifier(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    patient_id: String,
+    identifier_id: String,
+) -> Result<(), AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::remove_patient_identifier_service(&db, patient_id, identifier_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Finds the patient holding an external identifier.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `system` - System that issued the identifier
+/// * `value` - Identifier value
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Option<PatientResponse>)` - The patient, if one is visible to the session user
+/// * `Err(AppError)` - Error message if the lookup fails
+
+#[tauri::command]
+pub async fn find_patient_by_identifier(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    system: String,
+    value: String,
+) -> Result<Option<models::PatientResponse>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::find_patient_by_identifier_service(&db, system, value, &user)
+            .await
+    })
+    .await
 }
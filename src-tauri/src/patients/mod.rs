@@ -6,6 +6,9 @@
 //! - Patient record updates
 //! - Patient record deletion
 //! - Relationship management with doctors
+//! - Bulk import from FHIR bundles and CSV files
+//! - Duplicate detection and merging
+//! - External identifiers such as MRNs, PACS patient IDs and insurance numbers
 //! 
 //! ## Components
 //! 
@@ -17,6 +20,9 @@
 //! 
 //! - Complete CRUD operations for patient records
 //! - Doctor-patient relationship management
+//! - Validated, de-duplicated bulk import with per-row results
+//! - Merging of duplicate patients with their records and audit history
+//! - Lookup by external identifier, unique within each issuing system
 //! - Integration with medical records
 
 pub mod controller;
@@ -39,6 +39,7 @@ pub struct PatientResponse {
     pub images: Option<Vec<Thing>>,
     pub created_at: Datetime,
     pub updated_at: Datetime,
+    pub deleted_at: Option<Datetime>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -56,3 +57,256 @@ pub struct UserResponse {
     pub created_at: Datetime,
     pub updated_at: Datetime,
 }
+
+/// How deleting a patient treats their active notes and reports.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CascadePolicy {
+    /// Refuse to delete a patient who still has active notes or reports
+    Restrict,
+    /// Move the patient and all active notes and reports to the trash together
+    #[default]
+    Archive,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DependentRecord {
+    pub id: Thing,
+}
+
+/// Source format of a bulk patient import.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// A FHIR R4 bundle of `Patient` resources, or a single `Patient`
+    Fhir,
+    /// CSV with a header row naming the patient fields
+    Csv,
+}
+
+/// Patient fields read from one row of an import, before validation.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatientImportRow {
+    pub name: Option<String>,
+    pub date_of_birth: Option<String>,
+    pub gender: Option<String>,
+    pub contact_number: Option<String>,
+    pub address: Option<String>,
+    #[serde(default)]
+    pub identifiers: Vec<ImportIdentifier>,
+}
+
+/// Identifier read from an import, before validation.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportIdentifier {
+    /// Kind name or HL7 identifier type code
+    pub kind: Option<String>,
+    pub system: Option<String>,
+    pub value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    Imported,
+    Duplicate,
+    Invalid,
+}
+
+/// Outcome of importing one row.
+///
+/// `patient` is the created patient for imported rows and the existing
+/// patient for duplicates.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportRowResult {
+    pub row: usize,
+    pub status: ImportRowStatus,
+    pub patient: Option<Thing>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+    pub rows: Vec<ImportRowResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PatientIdentity {
+    pub id: Thing,
+    pub name: String,
+    pub date_of_birth: Datetime,
+    /// Whether the importing user may see the patient
+    #[serde(default)]
+    pub visible: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PatientSortField {
+    #[default]
+    Name,
+    DateOfBirth,
+    CreatedAt,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Criteria for searching patients; all filters are optional.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PatientSearchRequest {
+    /// Name prefix; names are matched fuzzily when no name starts with it
+    pub name: Option<String>,
+    pub born_from: Option<Datetime>,
+    pub born_to: Option<Datetime>,
+    pub gender: Option<String>,
+    /// Only patients treated by this user
+    pub doctor_id: Option<String>,
+    /// Words found in the symptoms, diagnosis or treatment of a note
+    pub note_text: Option<String>,
+    #[serde(default)]
+    pub sort: PatientSortField,
+    #[serde(default)]
+    pub direction: SortDirection,
+    /// Page size, 50 by default
+    pub limit: Option<u32>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PatientSearchResponse {
+    pub patients: Vec<PatientResponse>,
+    /// Cursor of the next page, if there is one
+    pub next_cursor: Option<String>,
+}
+
+/// Position after the last patient of a page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PatientCursor {
+    pub sort: PatientSortField,
+    pub direction: SortDirection,
+    /// Sort value of the last patient
+    pub value: String,
+    pub id: String,
+    /// Whether the search fell back to fuzzy name matching
+    #[serde(default)]
+    pub fuzzy: bool,
+}
+
+/// Evidence that two patients may be the same person.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    SimilarName,
+    SameDateOfBirth,
+    SameContactNumber,
+}
+
+/// A pair of patients that are likely duplicates.
+///
+/// `first` is the older record and the suggested survivor of a merge.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DuplicateCandidate {
+    pub first: PatientResponse,
+    pub second: PatientResponse,
+    /// Likelihood of a duplicate between 0 and 1
+    pub score: f32,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// Records moved onto the surviving patient by a merge.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PatientMergeSummary {
+    pub patient: PatientResponse,
+    pub notes: usize,
+    pub reports: usize,
+    pub images: usize,
+    /// Users who now treat the surviving patient through the merge
+    pub treated_by: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MergeDependents {
+    pub notes: Vec<Thing>,
+    pub reports: Vec<Thing>,
+    pub images: Vec<Thing>,
+    pub doctors: Vec<Thing>,
+}
+
+/// Kind of an external patient identifier.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierKind {
+    /// Medical record number assigned by a hospital
+    Mrn,
+    /// Patient ID used by a PACS
+    DicomPatientId,
+    /// Health insurance member number
+    Insurance,
+    #[default]
+    Other,
+}
+
+impl IdentifierKind {
+    /// Identifier type code from HL7 table 0203, as used by HL7 v2 and FHIR.
+    pub fn type_code(self) -> Option<&'static str> {
+        match self {
+            IdentifierKind::Mrn => Some("MR"),
+            IdentifierKind::DicomPatientId => Some("PI"),
+            IdentifierKind::Insurance => Some("MB"),
+            IdentifierKind::Other => None,
+        }
+    }
+
+    /// Parses a kind from its name or its HL7 type code.
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.trim().to_lowercase().as_str() {
+            "mrn" | "mr" => Some(IdentifierKind::Mrn),
+            "dicom_patient_id" | "pi" => Some(IdentifierKind::DicomPatientId),
+            "insurance" | "mb" | "sn" => Some(IdentifierKind::Insurance),
+            "other" | "" => Some(IdentifierKind::Other),
+            _ => None,
+        }
+    }
+}
+
+/// An identifier of a patient in an external system.
+///
+/// `system` names the issuer, for example the URI of a hospital's MRN
+/// namespace; a value is unique within its system.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PatientIdentifier {
+    #[serde(default)]
+    pub kind: IdentifierKind,
+    pub system: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IdentifierOwner {
+    pub patient: Thing,
+    pub system: String,
+    pub value: String,
+    /// Whether the importing user may see the patient
+    #[serde(default)]
+    pub visible: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PatientIdentifierResponse {
+    pub id: Thing,
+    pub patient: Thing,
+    pub kind: IdentifierKind,
+    pub system: String,
+    pub value: String,
+    pub created_at: Datetime,
+}
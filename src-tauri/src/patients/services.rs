@@ -1,19 +1,43 @@
-use super::models::{PatientRecord, PatientRequest, PatientResponse, UserResponse};
+use super::models::{
+    CascadePolicy, DependentRecord, DuplicateCandidate, DuplicateReason, IdentifierKind,
+    IdentifierOwner, ImportFormat, ImportIdentifier, ImportRowResult, ImportRowStatus,
+    ImportSummary, MergeDependents, PatientCursor, PatientIdentifier, PatientIdentifierResponse, PatientIdentity, PatientImportRow,
+    PatientMergeSummary, PatientRecord, PatientRequest, PatientResponse, PatientSearchRequest,
+    PatientSearchResponse, PatientSortField, SortDirection, UserResponse,
+};
+use crate::audit::models::{AuditAction, AuditRecord, FieldChange};
+use crate::audit::services::{
+    audit_changes, audit_entries, audit_entry, merge_changes, record_reads,
+};
+use crate::auth::session::models::AuthenticatedUser;
+use crate::fhir::services::parse_patient_bundle;
+use crate::validation::services::{check_birth_date, check_contact_number, check_gender, check_name};
+use crate::error::{check_transaction, AppError};
+use std::collections::{BTreeSet, HashMap};
 
-use scanlytics_db::{Surreal, Any};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use scanlytics_db::{Surreal, Any, Datetime, Id, Thing};
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
 
 /// Creates a new patient record with associated doctor relationship.
 ///
+/// The creating user is also related to the patient so the new record
+/// stays visible to them.
+///
 /// # Arguments
 ///
 /// * `db` - Database connection
 /// * `data` - Patient creation request data
+/// * `user` - Authenticated user creating the patient
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(PatientResponse)` - Successfully created patient record
-/// * `Err(String)` - Error message if creation fails
+/// * `Err(AppError)` - Error message if creation fails
 ///
 /// # Errors
 ///
@@ -25,13 +49,29 @@ use scanlytics_db::{Surreal, Any};
 pub async fn create_patient_service(
     db: &Surreal<Any>,
     data: PatientRequest,
-) -> Result<PatientResponse, String> {
+    user: &AuthenticatedUser,
+) -> Result<PatientResponse, AppError> {
+    insert_patient(db, data, &[], user).await
+}
+
+/// Creates a patient together with their external identifiers.
+///
+/// The patient, the doctor relationships, the identifiers and the audit
+/// entries are stored in a single transaction, so a conflicting identifier
+/// leaves no patient behind.
+
+async fn insert_patient(
+    db: &Surreal<Any>,
+    data: PatientRequest,
+    identifiers: &[PatientIdentifier],
+    user: &AuthenticatedUser,
+) -> Result<PatientResponse, AppError> {
     let doctor: Option<UserResponse> = db
         .select(("User", &data.primary_doctor))
-        .await
-        .map_err(|e| e.to_string())?;
-    let doctor = doctor.ok_or_else(|| "Doctor not found".to_string())?;
+        .await?;
+    let doctor = doctor.ok_or_else(|| AppError::NotFound("Doctor not found".to_string()))?;
 
+    let patient_id = Thing::from(("Patient", Id::rand()));
     let patient_record = PatientRecord {
         name: data.name,
         date_of_birth: data.date_of_birth,
@@ -43,41 +83,287 @@ pub async fn create_patient_service(
         images: data.images,
     };
 
-    let created: PatientResponse = db
-        .create("Patient")
-        .content(patient_record)
-        .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Failed to create patient".to_string())?;
+    let mut doctors = vec![doctor.id];
+    if doctors[0] != user.id {
+        doctors.push(user.id.clone());
+    }
+    let identifiers = new_identifiers(db, &patient_id, identifiers).await?;
+    let changes = audit_changes(None::<&PatientRecord>, Some(&patient_record));
+    let mut audit = vec![audit_entry(user, AuditAction::Create, &patient_id, Some(&patient_id), changes)];
+    if !identifiers.is_empty() {
+        audit.push(identifiers_audit(user, &patient_id, &identifiers));
+    }
 
-    let patient_id = created.id.clone();
-    let doctor_id = doctor.id.clone();
+    let create = format!(
+        "
+        BEGIN TRANSACTION;
+        CREATE $patient CONTENT $record;
+        FOR $doctor IN $doctors {{
+            RELATE $patient -> Treated_By -> $doctor;
+        }};
+        {}
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+        ",
+        CREATE_IDENTIFIERS
+    );
+    db.query(create)
+        .bind(("patient", patient_id.clone()))
+        .bind(("record", patient_record))
+        .bind(("doctors", doctors))
+        .bind(("identifiers", identifiers))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
 
-    db.query("RELATE $patient -> Treated_By -> $doctor")
-        .bind(("patient", patient_id))
-        .bind(("doctor", doctor_id))
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(created)
+    let created: Option<PatientResponse> = db
+        .select(("Patient", patient_id.id.to_raw()))
+        .await?;
+    created.ok_or_else(|| AppError::OperationFailed("Failed to create patient".to_string()))
 }
 
-/// Retrieves all patient records from the database.
+/// Retrieves the patient records visible to the user.
+///
+/// A patient is visible when the user treats them through a `Treated_By`
+/// edge, either as their doctor or because the patient was shared with them.
+/// Admins see every patient. Patients in the trash are excluded.
 ///
 /// # Arguments
 ///
 /// * `db` - Database connection
+/// * `user` - Authenticated user
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
-/// * `Ok(Vec<PatientResponse>)` - List of all patient records
-/// * `Err(String)` - Error message if retrieval fails
+/// * `Ok(Vec<PatientResponse>)` - List of visible patient records
+/// * `Err(AppError)` - Error message if retrieval fails
+
+pub async fn get_patient_service(
+    db: &Surreal<Any>,
+    user: &AuthenticatedUser,
+) -> Result<Vec<PatientResponse>, AppError> {
+    let query = "
+        SELECT * FROM Patient
+        WHERE deleted_at IS NONE
+            AND ($is_admin OR $user INSIDE ->Treated_By->User);
+    ";
+    let records: Vec<PatientResponse> = db
+        .query(query)
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?
+        .take(0)?;
+
+    let reads = records
+        .iter()
+        .map(|record| (record.id.clone(), Some(record.id.clone())))
+        .collect();
+    record_reads(db, user, reads).await?;
 
-pub async fn get_patient_service( db: &Surreal<Any>,) -> Result<Vec<PatientResponse>, String> {
-    let records: Vec<PatientResponse> = db.select("Patient").await.map_err(|e| e.to_string())?;
     Ok(records)
 }
+
+/// Searches the patient records visible to the user.
+///
+/// Filters are combined with AND. Results are sorted by the requested field,
+/// with the record id breaking ties, and returned a page at a time; the
+/// `next_cursor` of a page continues the search after its last patient.
+///
+/// A name is matched as a prefix of the patient's name, ignoring case. If no
+/// patient matches the prefix, the search is repeated with a fuzzy match.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `request` - Filters, sort order, page size and cursor
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PatientSearchResponse)` - One page of matching patients
+/// * `Err(AppError)` - Error message if the search fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The cursor is invalid or was issued for a different sort order
+/// * Database operations fail
+
+pub async fn search_patients_service(
+    db: &Surreal<Any>,
+    mut request: PatientSearchRequest,
+    user: &AuthenticatedUser,
+) -> Result<PatientSearchResponse, AppError> {
+    let limit = request.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE) as usize;
+    let cursor = request.cursor.as_deref().map(decode_cursor).transpose()?;
+    if let Some(cursor) = &cursor {
+        if cursor.sort != request.sort || cursor.direction != request.direction {
+            return Err(AppError::InvalidInput(
+                "Cursor was issued for a different sort order".to_string(),
+            ));
+        }
+    }
+
+    let normalize = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_lowercase())
+            .filter(|value| !value.is_empty())
+    };
+    request.name = normalize(request.name);
+    request.gender = normalize(request.gender);
+    request.note_text = normalize(request.note_text);
+
+    let mut fuzzy = cursor.as_ref().is_some_and(|cursor| cursor.fuzzy);
+    let mut records = find_patients(db, &request, cursor.as_ref(), fuzzy, limit + 1, user).await?;
+    if records.is_empty() && cursor.is_none() && request.name.is_some() {
+        fuzzy = true;
+        records = find_patients(db, &request, None, fuzzy, limit + 1, user).await?;
+    }
+
+    let next_cursor = if records.len() > limit {
+        records.truncate(limit);
+        records.last().map(|last| {
+            encode_cursor(&PatientCursor {
+                sort: request.sort,
+                direction: request.direction,
+                value: match request.sort {
+                    PatientSortField::Name => last.name.clone(),
+                    PatientSortField::DateOfBirth => last.date_of_birth.to_raw(),
+                    PatientSortField::CreatedAt => last.created_at.to_raw(),
+                },
+                id: last.id.id.to_raw(),
+                fuzzy,
+            })
+        })
+    } else {
+        None
+    };
+
+    let reads = records
+        .iter()
+        .map(|record| (record.id.clone(), Some(record.id.clone())))
+        .collect();
+    record_reads(db, user, reads).await?;
+
+    Ok(PatientSearchResponse {
+        patients: records,
+        next_cursor,
+    })
+}
+
+/// Runs one page of a patient search.
+///
+/// Only the active filters are added to the WHERE clause, so each can be
+/// served by its index: the name prefix by a range on `name_key`, dates and
+/// gender by their own indexes, and doctor, visibility and note text by
+/// looking up the matching patients first. The fuzzy name match is the only
+/// filter evaluated row by row.
+
+async fn find_patients(
+    db: &Surreal<Any>,
+    request: &PatientSearchRequest,
+    cursor: Option<&PatientCursor>,
+    fuzzy: bool,
+    limit: usize,
+    user: &AuthenticatedUser,
+) -> Result<Vec<PatientResponse>, AppError> {
+    let (column, cast) = match request.sort {
+        PatientSortField::Name => ("name", ""),
+        PatientSortField::DateOfBirth => ("date_of_birth", "<datetime>"),
+        PatientSortField::CreatedAt => ("created_at", "<datetime>"),
+    };
+    let (order, comparison) = match request.direction {
+        SortDirection::Asc => ("ASC", ">"),
+        SortDirection::Desc => ("DESC", "<"),
+    };
+
+    let mut lookups = Vec::new();
+    let mut conditions = vec!["deleted_at IS NONE".to_string()];
+    if !user.is_admin() {
+        lookups.push("LET $visible = SELECT VALUE in FROM Treated_By WHERE out = $user;");
+        conditions.push("id INSIDE $visible".to_string());
+    }
+    if request.name.is_some() {
+        let condition = if fuzzy {
+            "name ~ $name"
+        } else {
+            "name_key >= $name AND name_key < $name_end"
+        };
+        conditions.push(condition.to_string());
+    }
+    if request.born_from.is_some() {
+        conditions.push("date_of_birth >= $born_from".to_string());
+    }
+    if request.born_to.is_some() {
+        conditions.push("date_of_birth <= $born_to".to_string());
+    }
+    if request.gender.is_some() {
+        conditions.push("gender = $gender".to_string());
+    }
+    if request.doctor_id.is_some() {
+        lookups.push("LET $treated = SELECT VALUE in FROM Treated_By WHERE out = $doctor;");
+        conditions.push("id INSIDE $treated".to_string());
+    }
+    if request.note_text.is_some() {
+        lookups.push(
+            "LET $noted = SELECT VALUE patient FROM PatientNote
+            WHERE (symptoms @1@ $note_text OR diagnosis @2@ $note_text OR treatment @3@ $note_text
+                    OR symptoms_de @4@ $note_text OR diagnosis_de @5@ $note_text
+                    OR treatment_de @6@ $note_text)
+                AND deleted_at IS NONE;",
+        );
+        conditions.push("id INSIDE $noted".to_string());
+    }
+    if cursor.is_some() {
+        conditions.push(format!(
+            "({column} {comparison} {cast}$after_value
+                OR ({column} = {cast}$after_value AND id {comparison} $after_id))"
+        ));
+    }
+
+    let query = format!(
+        "{}
+        SELECT * FROM Patient
+        WHERE {}
+        ORDER BY {column} {order}, id {order}
+        LIMIT $limit;",
+        lookups.join("\n"),
+        conditions.join(" AND ")
+    );
+    db.query(query)
+        .bind(("user", user.id.clone()))
+        .bind(("name", request.name.clone()))
+        .bind(("name_end", request.name.as_ref().map(|name| format!("{}{}", name, char::MAX))))
+        .bind(("born_from", request.born_from.clone()))
+        .bind(("born_to", request.born_to.clone()))
+        .bind(("gender", request.gender.clone()))
+        .bind((
+            "doctor",
+            request.doctor_id.as_ref().map(|id| Thing::from(("User", id.as_str()))),
+        ))
+        .bind(("note_text", request.note_text.clone()))
+        .bind(("after_value", cursor.map(|cursor| cursor.value.clone())))
+        .bind(("after_id", cursor.map(|cursor| Thing::from(("Patient", cursor.id.as_str())))))
+        .bind(("limit", limit))
+        .await?
+        .take(lookups.len())
+        .map_err(AppError::from)
+}
+
+fn encode_cursor(cursor: &PatientCursor) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(cursor).unwrap_or_default())
+}
+
+fn decode_cursor(cursor: &str) -> Result<PatientCursor, AppError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| AppError::InvalidInput("Invalid search cursor".to_string()))
+}
+
 /// Updates an existing patient record.
 ///
 /// # Arguments
@@ -85,19 +371,31 @@ pub async fn get_patient_service( db: &Surreal<Any>,) -> Result<Vec<PatientRespo
 /// * `db` - Database connection
 /// * `id` - Unique identifier of the patient
 /// * `data` - Updated patient data
+/// * `user` - Authenticated user performing the update
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Option<PatientResponse>)` - Updated patient record if found
-/// * `Err(String)` - Error message if update fails
+/// * `Err(AppError)` - Error message if update fails
 
 pub async fn update_patient_service(
     db: &Surreal<Any>,
     id: String,
     data: PatientRequest,
-) -> Result<Option<PatientResponse>, String> {
-    // Get treated by data
+    user: &AuthenticatedUser,
+) -> Result<Option<PatientResponse>, AppError> {
+    let patient = Thing::from(("Patient", id.as_str()));
+    if !can_access_patient(db, &patient, user).await? {
+        return Ok(None);
+    }
+
+    let existing: Option<PatientResponse> = db
+        .select(("Patient", &id))
+        .await?;
+    let Some(existing) = existing else {
+        return Ok(None);
+    };
 
     let patient_record = PatientRecord {
         name: data.name,
@@ -109,35 +407,1922 @@ pub async fn update_patient_service(
         images: data.images,
         reports: data.reports,
     };
+    let changes = merge_changes(&existing, &patient_record);
+    let audit = vec![audit_entry(user, AuditAction::Update, &patient, Some(&patient), changes)];
 
-    let updated: Option<PatientResponse> = db
-        .update(("Patient", id))
-        .merge(patient_record)
-        .await
-        .map_err(|e| e.to_string())?;
+    let update = "
+        BEGIN TRANSACTION;
+        UPDATE $patient MERGE $record;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    db.query(update)
+        .bind(("patient", patient.clone()))
+        .bind(("record", patient_record))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
 
-    Ok(updated)
+    db.select(("Patient", &id)).await.map_err(AppError::from)
 }
-/// Deletes a patient record from the database.
+/// Moves a patient record to the trash according to a cascade policy.
+///
+/// With [`CascadePolicy::Restrict`] the deletion is refused while the patient
+/// still has active notes or reports. With [`CascadePolicy::Archive`] those
+/// notes and reports are trashed together with the patient in a single
+/// transaction, and come back when the patient is restored.
 ///
 /// # Arguments
 ///
 /// * `db` - Database connection
 /// * `id` - Unique identifier of the patient to delete
+/// * `policy` - How to treat the patient's active notes and reports
+/// * `user` - Authenticated user performing the deletion
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
-/// * `Ok(Option<PatientResponse>)` - Deleted patient record if found
-/// * `Err(String)` - Error message if deletion fails
+/// * `Ok(Option<PatientResponse>)` - Trashed patient record if found
+/// * `Err(AppError)` - Error message if deletion fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The policy is `Restrict` and dependent clinical records exist
+/// * Notes or reports of the patient change while it is deleted
+/// * The transaction fails
 
 pub async fn delete_patient_service(
     db: &Surreal<Any>,
     id: String,
-) -> Result<Option<PatientResponse>, String> {
-    let deleted: Option<PatientResponse> = db
-        .delete(("Patient", id))
+    policy: CascadePolicy,
+    user: &AuthenticatedUser,
+) -> Result<Option<PatientResponse>, AppError> {
+    let patient = Thing::from(("Patient", id.as_str()));
+    if !can_access_patient(db, &patient, user).await? {
+        return Ok(None);
+    }
+
+    let existing: Option<PatientResponse> = db
+        .select(("Patient", &id))
+        .await?;
+    let Some(existing) = existing else {
+        return Ok(None);
+    };
+
+    let mut dependents = db
+        .query("SELECT id FROM PatientNote WHERE patient = $patient AND deleted_at IS NONE")
+        .query("SELECT id FROM Report WHERE patient = $patient AND deleted_at IS NONE")
+        .bind(("patient", patient.clone()))
+        .await?;
+    let notes: Vec<DependentRecord> = dependents.take(0)?;
+    let reports: Vec<DependentRecord> = dependents.take(1)?;
+    let archived: Vec<Thing> = notes
+        .into_iter()
+        .chain(reports)
+        .map(|record| record.id)
+        .collect();
+
+    let now = Datetime::default();
+    let changes = merge_changes(&existing, &serde_json::json!({ "deleted_at": now }));
+    let mut audit = vec![audit_entry(user, AuditAction::Delete, &patient, Some(&patient), changes)];
+    audit.extend(audit_entries(
+        Some(user),
+        AuditAction::Delete,
+        archived
+            .iter()
+            .map(|record| (record.clone(), Some(patient.clone())))
+            .collect(),
+    ));
+
+    // The dependents are read again inside the transaction: the restrict
+    // check must see records created since, and the audit entries above
+    // must cover exactly the records archived.
+    let archive = "
+        BEGIN TRANSACTION;
+        LET $notes = (SELECT VALUE id FROM PatientNote WHERE patient = $patient AND deleted_at IS NONE);
+        LET $reports = (SELECT VALUE id FROM Report WHERE patient = $patient AND deleted_at IS NONE);
+        IF $restrict AND array::len($notes) + array::len($reports) > 0 {
+            THROW string::concat(
+                'Patient still has ', <string> array::len($notes), ' active notes and ',
+                <string> array::len($reports), ' active reports'
+            );
+        };
+        IF array::sort(array::concat($notes, $reports)) != array::sort($archived) {
+            THROW 'The patient records changed during the deletion, please try again';
+        };
+        UPDATE PatientNote SET deleted_at = $now, deleted_by = $user, deleted_with = $patient
+            WHERE patient = $patient AND deleted_at IS NONE;
+        UPDATE Report SET deleted_at = $now, deleted_by = $user, deleted_with = $patient
+            WHERE patient = $patient AND deleted_at IS NONE;
+        UPDATE $patient SET deleted_at = $now, deleted_by = $user;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    let response = db
+        .query(archive)
+        .bind(("patient", patient.clone()))
+        .bind(("user", user.id.clone()))
+        .bind(("now", now))
+        .bind(("restrict", policy == CascadePolicy::Restrict))
+        .bind(("archived", archived))
+        .bind(("audit", audit))
+        .await?;
+    check_transaction(response)?;
+
+    db.select(("Patient", &id)).await.map_err(AppError::from)
+}
+
+/// Restores a trashed patient together with the records archived with them.
+///
+/// Notes and reports that were trashed on their own before the patient was
+/// deleted stay in the trash. Patients merged into another patient can't be
+/// restored, since their records now belong to the survivor.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient` - Patient record identifier
+/// * `audit` - Audit entries stored in the same transaction
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or failure
+
+pub async fn restore_patient_service(
+    db: &Surreal<Any>,
+    patient: &Thing,
+    audit: Vec<AuditRecord>,
+) -> Result<(), AppError> {
+    let restore = "
+        BEGIN TRANSACTION;
+        IF $patient.merged_into != NONE {
+            THROW 'Merged patients cannot be restored';
+        };
+        UPDATE PatientNote SET deleted_at = NONE, deleted_by = NONE, deleted_with = NONE
+            WHERE deleted_with = $patient;
+        UPDATE Report SET deleted_at = NONE, deleted_by = NONE, deleted_with = NONE
+            WHERE deleted_with = $patient;
+        UPDATE $patient SET deleted_at = NONE, deleted_by = NONE;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    let response = db
+        .query(restore)
+        .bind(("patient", patient.clone()))
+        .bind(("audit", audit))
+        .await?;
+    check_transaction(response)?;
+
+    Ok(())
+}
+
+/// Shares a patient with another user of the same organization.
+///
+/// Sharing creates a `Treated_By` edge between the patient and the
+/// target user, which makes the patient, their notes and their reports
+/// visible to that user.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient_id` - Unique identifier of the patient
+/// * `target_user_id` - Unique identifier of the user to share with
+/// * `user` - Authenticated user sharing the patient
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or failure
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The patient doesn't exist or isn't visible to the user
+/// * The target user doesn't exist
+/// * The target user belongs to a different organization
+/// * Relationship creation fails
+
+pub async fn share_patient_service(
+    db: &Surreal<Any>,
+    patient_id: String,
+    target_user_id: String,
+    user: &AuthenticatedUser,
+) -> Result<(), AppError> {
+    let patient = Thing::from(("Patient", patient_id.as_str()));
+    if !can_access_patient(db, &patient, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
+
+    let target: Option<UserResponse> = db
+        .select(("User", &target_user_id))
+        .await?;
+    let target = target.ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    if !user.is_admin() && target.organization != user.organization {
+        return Err(AppError::Forbidden("Patients can only be shared within your organization".to_string()));
+    }
+
+    if is_treated_by(db, &patient, &target.id).await? {
+        return Ok(());
+    }
+
+    let changes = vec![FieldChange {
+        field: "treated_by".to_string(),
+        before: None,
+        after: Some(serde_json::Value::String(target.id.to_string())),
+    }];
+    let audit = vec![audit_entry(user, AuditAction::Share, &patient, Some(&patient), Some(changes))];
+
+    let share = "
+        BEGIN TRANSACTION;
+        RELATE $patient -> Treated_By -> $doctor;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    db.query(share)
+        .bind(("patient", patient.clone()))
+        .bind(("doctor", target.id.clone()))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
+
+    Ok(())
+}
+
+/// Imports patients in bulk from a FHIR bundle or a CSV file.
+///
+/// Each row is validated against the patient schema and compared with the
+/// existing patients and the rows imported before it. A row is a duplicate
+/// when a patient holds one of its identifiers, or has the same name and
+/// date of birth. Valid, new rows are created like [`create_patient_service`]
+/// with the importing user as the primary doctor, so each is linked to them
+/// via `Treated_By`, and their identifiers are stored in the same transaction
+/// as the patient.
+/// Rows are imported independently; an invalid row doesn't stop the import.
+///
+/// CSV files need a header row naming the columns `name`, `date_of_birth`,
+/// `gender`, `contact_number` and `address` in any order. Dates are given as
+/// `YYYY-MM-DD`. One identifier per row can be given in the optional
+/// `identifier_kind`, `identifier_system` and `identifier_value` columns.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `format` - Format of the content
+/// * `content` - FHIR JSON or CSV text
+/// * `user` - Authenticated user importing the patients
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(ImportSummary)` - Counts and the outcome of every row
+/// * `Err(AppError)` - Error message if the content can't be read at all
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The content isn't valid FHIR JSON or CSV
+/// * The CSV header lacks a required column
+/// * Loading the existing patients fails
+
+pub async fn import_patients_service(
+    db: &Surreal<Any>,
+    format: ImportFormat,
+    content: String,
+    user: &AuthenticatedUser,
+) -> Result<ImportSummary, AppError> {
+    let rows = match format {
+        ImportFormat::Fhir => parse_patient_bundle(&content)?,
+        ImportFormat::Csv => parse_patient_csv(&content).map_err(AppError::InvalidInput)?,
+    };
+
+    let existing: Vec<PatientIdentity> = db
+        .query(
+            "SELECT id, name, date_of_birth, ($is_admin OR $user INSIDE ->Treated_By->User) AS visible
+            FROM Patient WHERE deleted_at IS NONE",
+        )
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?
+        .take(0)?;
+    let mut known: HashMap<String, Option<Thing>> = existing
+        .into_iter()
+        .map(|patient| {
+            let key = identity_key(&patient.name, &patient.date_of_birth);
+            (key, patient.visible.then_some(patient.id))
+        })
+        .collect();
+
+    let existing_identifiers: Vec<IdentifierOwner> = db
+        .query(
+            "SELECT patient, system, value, ($is_admin OR $user INSIDE patient->Treated_By->User) AS visible
+            FROM PatientIdentifier",
+        )
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?
+        .take(0)?;
+    let mut known_identifiers: HashMap<String, Option<Thing>> = existing_identifiers
+        .into_iter()
+        .map(|owner| {
+            let key = format!("{}|{}", owner.system, owner.value);
+            (key, owner.visible.then_some(owner.patient))
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(rows.len());
+    for (row, fields) in rows {
+        let (request, identifiers) = match validate_import_row(fields, user) {
+            Ok(validated) => validated,
+            Err(errors) => {
+                results.push(ImportRowResult {
+                    row,
+                    status: ImportRowStatus::Invalid,
+                    patient: None,
+                    errors,
+                });
+                continue;
+            }
+        };
+
+        let identifier_keys: Vec<String> = identifiers.iter().map(identifier_key).collect();
+        let key = identity_key(&request.name, &request.date_of_birth);
+        let existing = identifier_keys
+            .iter()
+            .find_map(|key| known_identifiers.get(key))
+            .or_else(|| known.get(&key));
+        if let Some(existing) = existing {
+            results.push(ImportRowResult {
+                row,
+                status: ImportRowStatus::Duplicate,
+                patient: existing.clone(),
+                errors: Vec::new(),
+            });
+            continue;
+        }
+
+        match insert_patient(db, request, &identifiers, user).await {
+            Ok(created) => {
+                known.insert(key, Some(created.id.clone()));
+                for key in identifier_keys {
+                    known_identifiers.insert(key, Some(created.id.clone()));
+                }
+                results.push(ImportRowResult {
+                    row,
+                    status: ImportRowStatus::Imported,
+                    patient: Some(created.id),
+                    errors: Vec::new(),
+                });
+            }
+            Err(e) => results.push(ImportRowResult {
+                row,
+                status: ImportRowStatus::Invalid,
+                patient: None,
+                errors: vec![e.to_string()],
+            }),
+        }
+    }
+
+    let count = |status| results.iter().filter(|result| result.status == status).count();
+    Ok(ImportSummary {
+        imported: count(ImportRowStatus::Imported),
+        duplicates: count(ImportRowStatus::Duplicate),
+        invalid: count(ImportRowStatus::Invalid),
+        rows: results,
+    })
+}
+
+/// Validates an import row and turns it into a patient request.
+///
+/// Returns every problem with the row rather than only the first.
+
+fn validate_import_row(
+    row: PatientImportRow,
+    user: &AuthenticatedUser,
+) -> Result<(PatientRequest, Vec<PatientIdentifier>), Vec<String>> {
+    let mut errors = Vec::new();
+
+    let name = check_name(&row.name.unwrap_or_default()).unwrap_or_else(|e| {
+        errors.push(e);
+        String::new()
+    });
+
+    let date_of_birth = match row.date_of_birth.as_deref().map(str::trim) {
+        None | Some("") => {
+            errors.push("date_of_birth is required".to_string());
+            None
+        }
+        Some(date) => match parse_birth_date(date) {
+            Ok(date) => Some(date),
+            Err(e) => {
+                errors.push(e);
+                None
+            }
+        },
+    };
+
+    let gender = match check_gender(&row.gender.unwrap_or_default()) {
+        Ok(gender) => gender.to_string(),
+        Err(e) => {
+            errors.push(e);
+            String::new()
+        }
+    };
+    let contact_number = check_contact_number(&row.contact_number.unwrap_or_default())
+        .unwrap_or_else(|e| {
+            errors.push(e);
+            String::new()
+        });
+
+    let mut identifiers = Vec::new();
+    for identifier in row.identifiers {
+        let kind = identifier.kind.unwrap_or_default();
+        let Some(kind) = IdentifierKind::parse(&kind) else {
+            errors.push(format!("identifier kind '{}' is not supported", kind.trim()));
+            continue;
+        };
+        let identifier = PatientIdentifier {
+            kind,
+            system: identifier.system.unwrap_or_default(),
+            value: identifier.value.unwrap_or_default(),
+        };
+        match normalize_identifier(identifier) {
+            Ok(identifier) => identifiers.push(identifier),
+            Err(_) => errors.push("identifier system and value are required".to_string()),
+        }
+    }
+
+    match date_of_birth {
+        Some(date_of_birth) if errors.is_empty() => Ok((
+            PatientRequest {
+                name,
+                date_of_birth,
+                gender,
+                contact_number,
+                address: row.address.unwrap_or_default().trim().to_string(),
+                notes: None,
+                reports: None,
+                images: None,
+                primary_doctor: user.id.id.to_raw(),
+            },
+            identifiers,
+        )),
+        _ => Err(errors),
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date of birth within the accepted range.
+
+fn parse_birth_date(date: &str) -> Result<Datetime, String> {
+    let invalid = || format!("date_of_birth '{}' is not a valid YYYY-MM-DD date", date);
+
+    let parts: Vec<&str> = date.split('-').collect();
+    let well_formed = parts.len() == 3
+        && [4, 2, 2].iter().zip(&parts).all(|(len, part)| {
+            part.len() == *len && part.chars().all(|c| c.is_ascii_digit())
+        });
+    if !well_formed {
+        return Err(invalid());
+    }
+
+    let parsed = Datetime::try_from(format!("{}T00:00:00Z", date).as_str()).map_err(|_| invalid())?;
+    check_birth_date(&parsed)?;
+    Ok(parsed)
+}
+
+/// Key identifying an external identifier for de-duplication.
+
+fn identifier_key(identifier: &PatientIdentifier) -> String {
+    format!("{}|{}", identifier.system, identifier.value)
+}
+
+/// Key identifying a person for de-duplication: normalized name and birth date.
+
+fn identity_key(name: &str, date_of_birth: &Datetime) -> String {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    format!("{}|{}", name, date_of_birth.0.format("%Y-%m-%d"))
+}
+
+/// Reads patient rows from CSV text with a header row.
+///
+/// Rows are numbered by their line in the file, so the first data row is 2.
+/// Blank lines are skipped.
+
+fn parse_patient_csv(content: &str) -> Result<Vec<(usize, PatientImportRow)>, String> {
+    let mut records = parse_csv(content)?.into_iter();
+    let (_, header) = records.next().ok_or_else(|| "CSV file is empty".to_string())?;
+    let header: Vec<String> = header
+        .iter()
+        .map(|column| column.trim().trim_start_matches('\u{feff}').to_lowercase())
+        .collect();
+
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let name = column("name").ok_or_else(|| "CSV header is missing the name column".to_string())?;
+    let date_of_birth = column("date_of_birth")
+        .ok_or_else(|| "CSV header is missing the date_of_birth column".to_string())?;
+    let gender = column("gender");
+    let contact_number = column("contact_number");
+    let address = column("address");
+    let identifier_kind = column("identifier_kind");
+    let identifier_system = column("identifier_system");
+    let identifier_value = column("identifier_value");
+
+    let field = |record: &[String], index: Option<usize>| {
+        index.and_then(|index| record.get(index)).cloned()
+    };
+    Ok(records
+        .filter(|(_, record)| record.iter().any(|value| !value.trim().is_empty()))
+        .map(|(line, record)| {
+            let identifier = ImportIdentifier {
+                kind: field(&record, identifier_kind),
+                system: field(&record, identifier_system),
+                value: field(&record, identifier_value),
+            };
+            let has_identifier = [&identifier.system, &identifier.value]
+                .iter()
+                .any(|part| part.as_deref().is_some_and(|part| !part.trim().is_empty()));
+            let row = PatientImportRow {
+                name: field(&record, Some(name)),
+                date_of_birth: field(&record, Some(date_of_birth)),
+                gender: field(&record, gender),
+                contact_number: field(&record, contact_number),
+                address: field(&record, address),
+                identifiers: if has_identifier { vec![identifier] } else { Vec::new() },
+            };
+            (line, row)
+        })
+        .collect())
+}
+
+/// Splits CSV text into records, honouring quoted fields.
+///
+/// Each record is returned with the line it starts on.
+
+fn parse_csv(content: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            '\n' => {
+                line += 1;
+                field.push(c);
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("Unterminated quoted field starting on line {}", record_line));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+/// Lists pairs of visible patients that are likely the same person.
+///
+/// Patients are compared on three signals: similar names (ignoring case,
+/// spacing and word order), the same date of birth and the same contact
+/// number (ignoring formatting and country prefixes). A pair is a candidate
+/// when at least two signals agree, so only patients sharing a date of birth
+/// or a contact number are compared.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<DuplicateCandidate>)` - Candidate pairs, most likely first
+/// * `Err(AppError)` - Error message if the patients can't be loaded
+
+pub async fn find_duplicate_patients_service(
+    db: &Surreal<Any>,
+    user: &AuthenticatedUser,
+) -> Result<Vec<DuplicateCandidate>, AppError> {
+    let patients: Vec<PatientResponse> = db
+        .query(
+            "SELECT * FROM Patient
+            WHERE deleted_at IS NONE AND ($is_admin OR $user INSIDE ->Treated_By->User)
+            ORDER BY created_at",
+        )
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?
+        .take(0)?;
+
+    let mut candidates = Vec::new();
+    for (first, second) in duplicate_pairs(&patients) {
+        let (first, second) = (&patients[first], &patients[second]);
+        if let Some((score, reasons)) = duplicate_score(first, second) {
+            candidates.push(DuplicateCandidate {
+                first: first.clone(),
+                second: second.clone(),
+                score,
+                reasons,
+            });
+        }
+    }
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    Ok(candidates)
+}
+
+/// Merges a duplicate patient into the surviving patient.
+///
+/// Notes, reports, images, external identifiers and `Treated_By` edges of
+/// the duplicate are moved to the survivor, and the survivor's empty contact number and address are
+/// filled from the duplicate. The duplicate is then moved to the trash with
+/// `merged_into` pointing at the survivor. The merge is recorded in the
+/// audit log of both patients.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `survivor_id` - Unique identifier of the patient to keep
+/// * `duplicate_id` - Unique identifier of the patient to merge away
+/// * `user` - Authenticated user performing the merge
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PatientMergeSummary)` - The surviving patient and the moved records
+/// * `Err(AppError)` - Error message if the merge fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * Both identifiers refer to the same patient
+/// * Either patient doesn't exist or isn't visible to the user
+/// * Database operations fail
+
+pub async fn merge_patients_service(
+    db: &Surreal<Any>,
+    survivor_id: String,
+    duplicate_id: String,
+    user: &AuthenticatedUser,
+) -> Result<PatientMergeSummary, AppError> {
+    if survivor_id == duplicate_id {
+        return Err(AppError::OperationFailed("A patient cannot be merged into itself".to_string()));
+    }
+    let survivor = Thing::from(("Patient", survivor_id.as_str()));
+    let duplicate = Thing::from(("Patient", duplicate_id.as_str()));
+    for patient in [&survivor, &duplicate] {
+        if !can_access_patient(db, patient, user).await? {
+            return Err(AppError::NotFound("Patient not found".to_string()));
+        }
+    }
+
+    let existing: Option<PatientResponse> = db
+        .select(("Patient", &survivor_id))
+        .await?;
+    let existing = existing.ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+    let merged_away: Option<PatientResponse> = db
+        .select(("Patient", &duplicate_id))
+        .await?;
+    let merged_away = merged_away.ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+
+    let fill = |current: &str, other: &str| {
+        if current.is_empty() {
+            other.to_string()
+        } else {
+            current.to_string()
+        }
+    };
+    let filled = serde_json::json!({
+        "contact_number": fill(&existing.contact_number, &merged_away.contact_number),
+        "address": fill(&existing.address, &merged_away.address),
+    });
+
+    let mut changes = vec![FieldChange {
+        field: "merged_from".to_string(),
+        before: None,
+        after: Some(serde_json::Value::String(duplicate.to_string())),
+    }];
+    changes.extend(merge_changes(&existing, &filled).unwrap_or_default());
+    let merged_into = vec![FieldChange {
+        field: "merged_into".to_string(),
+        before: None,
+        after: Some(serde_json::Value::String(survivor.to_string())),
+    }];
+    let audit = vec![
+        audit_entry(user, AuditAction::Merge, &survivor, Some(&survivor), Some(changes)),
+        audit_entry(user, AuditAction::Merge, &duplicate, Some(&duplicate), Some(merged_into)),
+    ];
+
+    let merge = "
+        BEGIN TRANSACTION;
+        LET $dependents = {
+            notes: (SELECT VALUE id FROM PatientNote WHERE patient = $duplicate),
+            reports: (SELECT VALUE id FROM Report WHERE patient = $duplicate),
+            images: (SELECT VALUE id FROM Image WHERE patient = $duplicate),
+            doctors: (SELECT VALUE out FROM Treated_By
+                WHERE in = $duplicate AND out NOT INSIDE $survivor->Treated_By->User)
+        };
+        UPDATE PatientNote SET patient = $survivor WHERE patient = $duplicate;
+        UPDATE Report SET patient = $survivor WHERE patient = $duplicate;
+        UPDATE Image SET patient = $survivor WHERE patient = $duplicate;
+        UPDATE AnalysisRun SET patient = $survivor WHERE patient = $duplicate;
+        UPDATE PatientIdentifier SET patient = $survivor WHERE patient = $duplicate;
+        FOR $doctor IN $dependents.doctors {
+            RELATE $survivor -> Treated_By -> $doctor;
+        };
+        DELETE Treated_By WHERE in = $duplicate;
+        UPDATE $survivor MERGE $filled;
+        UPDATE $survivor SET
+            notes = array::union(notes ?? [], $duplicate.notes ?? []),
+            report = array::union(report ?? [], $duplicate.report ?? []),
+            image = array::union(image ?? [], $duplicate.image ?? []);
+        UPDATE $duplicate SET
+            notes = NONE,
+            report = NONE,
+            image = NONE,
+            merged_into = $survivor,
+            deleted_at = time::now(),
+            deleted_by = $user;
+        INSERT INTO AuditLog $audit;
+        RETURN $dependents;
+        COMMIT TRANSACTION;
+    ";
+    let mut response = db
+        .query(merge)
+        .bind(("survivor", survivor.clone()))
+        .bind(("duplicate", duplicate.clone()))
+        .bind(("user", user.id.clone()))
+        .bind(("filled", filled))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
+    let last = response.num_statements() - 1;
+    let dependents: Option<MergeDependents> = response.take(last)?;
+    let dependents = dependents.unwrap_or_default();
+
+    let merged: Option<PatientResponse> = db
+        .select(("Patient", &survivor_id))
+        .await?;
+    let merged = merged.ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+
+    Ok(PatientMergeSummary {
+        patient: merged,
+        notes: dependents.notes.len(),
+        reports: dependents.reports.len(),
+        images: dependents.images.len(),
+        treated_by: dependents.doctors.len(),
+    })
+}
+
+/// Pairs of patients sharing a date of birth or a contact number.
+///
+/// Any pair with two agreeing signals shares at least one of them, so
+/// scoring only these pairs finds every candidate without comparing each
+/// patient with every other. Pairs are indices into `patients`, in order.
+
+fn duplicate_pairs(patients: &[PatientResponse]) -> BTreeSet<(usize, usize)> {
+    let mut by_birth: HashMap<_, Vec<usize>> = HashMap::new();
+    let mut by_contact: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, patient) in patients.iter().enumerate() {
+        by_birth
+            .entry(patient.date_of_birth.0.date_naive())
+            .or_default()
+            .push(index);
+        if let Some(key) = contact_key(&patient.contact_number) {
+            by_contact.entry(key).or_default().push(index);
+        }
+    }
+
+    let mut pairs = BTreeSet::new();
+    for group in by_birth.values().chain(by_contact.values()) {
+        for (position, &first) in group.iter().enumerate() {
+            for &second in &group[position + 1..] {
+                pairs.insert((first, second));
+            }
+        }
+    }
+    pairs
+}
+
+/// Minimum name similarity for two names to count as the same.
+const NAME_SIMILARITY_THRESHOLD: f32 = 0.85;
+
+/// Scores how likely two patients are duplicates.
+///
+/// Returns `None` unless at least two of name, date of birth and contact
+/// number agree.
+
+fn duplicate_score(
+    first: &PatientResponse,
+    second: &PatientResponse,
+) -> Option<(f32, Vec<DuplicateReason>)> {
+    let similarity = name_similarity(&first.name, &second.name);
+    let mut reasons = Vec::new();
+    let mut score = 0.0;
+    if similarity >= NAME_SIMILARITY_THRESHOLD {
+        reasons.push(DuplicateReason::SimilarName);
+        score += 0.5 * similarity;
+    }
+    if first.date_of_birth.0.date_naive() == second.date_of_birth.0.date_naive() {
+        reasons.push(DuplicateReason::SameDateOfBirth);
+        score += 0.3;
+    }
+    if same_contact_number(&first.contact_number, &second.contact_number) {
+        reasons.push(DuplicateReason::SameContactNumber);
+        score += 0.2;
+    }
+    (reasons.len() >= 2).then_some((score, reasons))
+}
+
+/// Similarity of two names between 0 and 1, from their edit distance.
+///
+/// Names are compared case-insensitively with their words sorted, so
+/// "Doe, John" and "john doe" are identical.
+
+fn name_similarity(first: &str, second: &str) -> f32 {
+    let normalize = |name: &str| {
+        let mut words: Vec<String> = name
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        words.sort();
+        words.join(" ").chars().collect::<Vec<char>>()
+    };
+    let (first, second) = (normalize(first), normalize(second));
+    let longest = first.len().max(second.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - edit_distance(&first, &second) as f32 / longest as f32
+}
+
+fn edit_distance(first: &[char], second: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=second.len()).collect();
+    for (i, a) in first.iter().enumerate() {
+        let mut current = vec![i + 1; second.len() + 1];
+        for (j, b) in second.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[second.len()]
+}
+
+/// Compares phone numbers by their digits.
+///
+/// Numbers with at least eight digits match on their last eight, so a
+/// country prefix and a national trunk prefix compare equal.
+
+fn same_contact_number(first: &str, second: &str) -> bool {
+    contact_key(first).is_some_and(|key| contact_key(second) == Some(key))
+}
+
+/// Comparable form of a contact number: its last eight digits, or all of
+/// them for shorter numbers. `None` if the number has no digits.
+
+fn contact_key(number: &str) -> Option<String> {
+    let digits: String = number.chars().filter(char::is_ascii_digit).collect();
+    match digits.len() {
+        0 => None,
+        len if len >= 8 => Some(digits[len - 8..].to_string()),
+        _ => Some(digits),
+    }
+}
+
+/// Retrieves the external identifiers of a patient.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient_id` - Unique identifier of the patient
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<PatientIdentifierResponse>)` - Identifiers ordered by system
+/// * `Err(AppError)` - Error message if the patient isn't visible or the query fails
+
+pub async fn get_patient_identifiers_service(
+    db: &Surreal<Any>,
+    patient_id: String,
+    user: &AuthenticatedUser,
+) -> Result<Vec<PatientIdentifierResponse>, AppError> {
+    let patient = Thing::from(("Patient", patient_id.as_str()));
+    if !can_access_patient(db, &patient, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
+
+    db.query("SELECT * FROM PatientIdentifier WHERE patient = $patient ORDER BY system, value")
+        .bind(("patient", patient))
+        .await?
+        .take(0)
+        .map_err(AppError::from)
+}
+
+/// Adds an external identifier to a patient.
+///
+/// Adding an identifier the patient already has is a no-op.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient_id` - Unique identifier of the patient
+/// * `identifier` - Kind, system and value of the identifier
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PatientIdentifierResponse)` - The stored identifier
+/// * `Err(AppError)` - Error message if the identifier can't be added
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The patient doesn't exist or isn't visible to the user
+/// * The system or value is empty
+/// * The identifier already belongs to another patient
+
+pub async fn add_patient_identifier_service(
+    db: &Surreal<Any>,
+    patient_id: String,
+    identifier: PatientIdentifier,
+    user: &AuthenticatedUser,
+) -> Result<PatientIdentifierResponse, AppError> {
+    let patient = Thing::from(("Patient", patient_id.as_str()));
+    if !can_access_patient(db, &patient, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
+    let identifier = normalize_identifier(identifier).map_err(|e| AppError::validation("identifier", e))?;
+
+    assign_identifiers(db, &patient, std::slice::from_ref(&identifier), user).await?;
+
+    let stored: Option<PatientIdentifierResponse> = db
+        .query("SELECT * FROM ONLY PatientIdentifier WHERE system = $system AND value = $value LIMIT 1")
+        .bind(("system", identifier.system))
+        .bind(("value", identifier.value))
+        .await?
+        .take(0)?;
+    stored.ok_or_else(|| AppError::OperationFailed("Failed to add patient identifier".to_string()))
+}
+
+/// Removes an external identifier from a patient.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient_id` - Unique identifier of the patient
+/// * `identifier_id` - Unique identifier of the identifier record
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or failure
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The patient doesn't exist or isn't visible to the user
+/// * The identifier doesn't belong to the patient
+
+pub async fn remove_patient_identifier_service(
+    db: &Surreal<Any>,
+    patient_id: String,
+    identifier_id: String,
+    user: &AuthenticatedUser,
+) -> Result<(), AppError> {
+    let patient = Thing::from(("Patient", patient_id.as_str()));
+    if !can_access_patient(db, &patient, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
+
+    let removed: Vec<PatientIdentifierResponse> = db
+        .query("SELECT * FROM type::thing('PatientIdentifier', $id) WHERE patient = $patient")
+        .bind(("id", identifier_id))
+        .bind(("patient", patient.clone()))
+        .await?
+        .take(0)?;
+    let removed = removed
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound("Identifier not found".to_string()))?;
+
+    let identifier = PatientIdentifier {
+        kind: removed.kind,
+        system: removed.system,
+        value: removed.value,
+    };
+    let changes = vec![FieldChange {
+        field: "identifiers".to_string(),
+        before: serde_json::to_value(&identifier).ok(),
+        after: None,
+    }];
+    let audit = vec![audit_entry(user, AuditAction::Update, &patient, Some(&patient), Some(changes))];
+
+    let remove = "
+        BEGIN TRANSACTION;
+        DELETE $identifier;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    db.query(remove)
+        .bind(("identifier", removed.id))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
+
+    Ok(())
+}
+
+/// Finds the patient holding an external identifier.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `system` - System that issued the identifier
+/// * `value` - Identifier value
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Option<PatientResponse>)` - The patient, if one holds the identifier and is visible
+/// * `Err(AppError)` - Error message if the lookup fails
+
+pub async fn find_patient_by_identifier_service(
+    db: &Surreal<Any>,
+    system: String,
+    value: String,
+    user: &AuthenticatedUser,
+) -> Result<Option<PatientResponse>, AppError> {
+    let patient: Option<Thing> = db
+        .query("SELECT VALUE patient FROM ONLY PatientIdentifier WHERE system = $system AND value = $value LIMIT 1")
+        .bind(("system", system.trim().to_string()))
+        .bind(("value", value.trim().to_string()))
+        .await?
+        .take(0)?;
+    let Some(patient) = patient else {
+        return Ok(None);
+    };
+    if !can_access_patient(db, &patient, user).await? {
+        return Ok(None);
+    }
+
+    let record: Option<PatientResponse> = db
+        .select(&patient)
+        .await?;
+    if let Some(record) = &record {
+        record_reads(db, user, vec![(record.id.clone(), Some(record.id.clone()))]).await?;
+    }
+    Ok(record)
+}
+
+/// Loads the external identifiers of a patient for exports.
+
+pub(crate) async fn load_identifiers(
+    db: &Surreal<Any>,
+    patient: &Thing,
+) -> Result<Vec<PatientIdentifier>, AppError> {
+    db.query("SELECT kind, system, value FROM PatientIdentifier WHERE patient = $patient ORDER BY system, value")
+        .bind(("patient", patient.clone()))
+        .await?
+        .take(0)
+        .map_err(AppError::from)
+}
+
+/// Assigns identifiers to a patient, skipping those the patient already has.
+///
+/// Nothing is stored if any identifier belongs to another patient. The new
+/// identifiers and their audit entry are stored in one transaction.
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<PatientIdentifier>)` - The newly assigned identifiers
+/// * `Err(AppError)` - Error message naming the first conflicting identifier
+
+pub(crate) async fn assign_identifiers(
+    db: &Surreal<Any>,
+    patient: &Thing,
+    identifiers: &[PatientIdentifier],
+    user: &AuthenticatedUser,
+) -> Result<Vec<PatientIdentifier>, AppError> {
+    let new = new_identifiers(db, patient, identifiers).await?;
+    if new.is_empty() {
+        return Ok(new);
+    }
+
+    let query = format!(
+        "BEGIN TRANSACTION; {} INSERT INTO AuditLog $audit; COMMIT TRANSACTION;",
+        CREATE_IDENTIFIERS
+    );
+    db.query(query)
+        .bind(("patient", patient.clone()))
+        .bind(("identifiers", new.clone()))
+        .bind(("audit", vec![identifiers_audit(user, patient, &new)]))
+        .await?
+        .check()?;
+    Ok(new)
+}
+
+/// Statement creating the identifiers bound as `$identifiers` for `$patient`.
+
+pub(crate) const CREATE_IDENTIFIERS: &str = "
+    FOR $identifier IN $identifiers {
+        CREATE PatientIdentifier SET patient = $patient, kind = $identifier.kind,
+            system = $identifier.system, value = $identifier.value;
+    };
+";
+
+/// Filters out identifiers the patient already has.
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<PatientIdentifier>)` - Identifiers not stored yet, without repeats
+/// * `Err(AppError)` - Error message naming the first identifier of another patient
+
+pub(crate) async fn new_identifiers(
+    db: &Surreal<Any>,
+    patient: &Thing,
+    identifiers: &[PatientIdentifier],
+) -> Result<Vec<PatientIdentifier>, AppError> {
+    let mut new = Vec::new();
+    for identifier in identifiers {
+        let owner: Option<Thing> = db
+            .query("SELECT VALUE patient FROM ONLY PatientIdentifier WHERE system = $system AND value = $value LIMIT 1")
+            .bind(("system", identifier.system.clone()))
+            .bind(("value", identifier.value.clone()))
+            .await?
+            .take(0)?;
+        match owner {
+            Some(owner) if &owner == patient => {}
+            Some(_) => {
+                return Err(AppError::OperationFailed(format!(
+                    "Identifier {} of {} already belongs to another patient",
+                    identifier.value, identifier.system
+                )));
+            }
+            None if !new.contains(identifier) => new.push(identifier.clone()),
+            None => {}
+        }
+    }
+    Ok(new)
+}
+
+/// Audit entry for identifiers added to a patient.
+
+pub(crate) fn identifiers_audit(
+    user: &AuthenticatedUser,
+    patient: &Thing,
+    identifiers: &[PatientIdentifier],
+) -> AuditRecord {
+    let changes = vec![FieldChange {
+        field: "identifiers".to_string(),
+        before: None,
+        after: serde_json::to_value(identifiers).ok(),
+    }];
+    audit_entry(user, AuditAction::Update, patient, Some(patient), Some(changes))
+}
+
+/// Trims an identifier and checks that its system and value are set.
+
+fn normalize_identifier(identifier: PatientIdentifier) -> Result<PatientIdentifier, String> {
+    let system = identifier.system.trim().to_string();
+    let value = identifier.value.trim().to_string();
+    if system.is_empty() || value.is_empty() {
+        return Err("Identifier system and value are required".to_string());
+    }
+    Ok(PatientIdentifier {
+        kind: identifier.kind,
+        system,
+        value,
+    })
+}
+
+/// Checks whether the user may see the given patient.
+///
+/// Patients in the trash are treated as not visible.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient` - Patient record identifier
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(bool)` - Whether the patient is visible to the user
+/// * `Err(AppError)` - Error message if the lookup fails
+
+pub async fn can_access_patient(
+    db: &Surreal<Any>,
+    patient: &Thing,
+    user: &AuthenticatedUser,
+) -> Result<bool, AppError> {
+    let query = "
+        SELECT VALUE id FROM $patient
+        WHERE deleted_at IS NONE
+            AND ($is_admin OR $user INSIDE ->Treated_By->User);
+    ";
+    let visible: Vec<Thing> = db
+        .query(query)
+        .bind(("patient", patient.clone()))
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?
+        .take(0)?;
+    Ok(!visible.is_empty())
+}
+
+/// Checks whether a `Treated_By` edge links the patient to the user.
+
+async fn is_treated_by(db: &Surreal<Any>, patient: &Thing, user: &Thing) -> Result<bool, AppError> {
+    let edges: Vec<Thing> = db
+        .query("SELECT VALUE id FROM Treated_By WHERE in = $patient AND out = $user")
+        .bind(("patient", patient.clone()))
+        .bind(("user", user.clone()))
+        .await?
+        .take(0)?;
+    Ok(!edges.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::models::AuditFilter;
+    use crate::audit::services::get_audit_log_service;
+    use crate::test_utils::{create_test_user, setup_test_db};
+    use scanlytics_db::Datetime;
+
+    fn patient_request(doctor: &AuthenticatedUser) -> PatientRequest {
+        PatientRequest {
+            name: "Test Patient".to_string(),
+            date_of_birth: Datetime::default(),
+            gender: "male".to_string(),
+            contact_number: "1234567890".to_string(),
+            address: "Test Address".to_string(),
+            notes: None,
+            reports: None,
+            images: None,
+            primary_doctor: doctor.id.id.to_raw(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_patients_scoped_to_treating_doctor() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+
+        create_patient_service(&db, patient_request(&doctor), &doctor)
+            .await
+            .unwrap();
+
+        assert_eq!(get_patient_service(&db, &doctor).await.unwrap().len(), 1);
+        assert!(get_patient_service(&db, &other).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_share_patient() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", Some("clinic")).await;
+        let colleague = create_test_user(&db, "colleague@test.com", Some("clinic")).await;
+        let outsider = create_test_user(&db, "outsider@test.com", Some("elsewhere")).await;
+
+        let patient = create_patient_service(&db, patient_request(&doctor), &doctor)
+            .await
+            .unwrap();
+        let patient_id = patient.id.id.to_raw();
+
+        let result = share_patient_service(
+            &db,
+            patient_id.clone(),
+            outsider.id.id.to_raw(),
+            &doctor,
+        )
+        .await;
+        assert!(result.is_err());
+
+        let result = share_patient_service(
+            &db,
+            patient_id.clone(),
+            doctor.id.id.to_raw(),
+            &colleague,
+        )
+        .await;
+        assert!(result.is_err());
+
+        share_patient_service(&db, patient_id, colleague.id.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(get_patient_service(&db, &colleague).await.unwrap().len(), 1);
+        assert!(get_patient_service(&db, &outsider).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_patient_changes_are_audited() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+
+        let patient = create_patient_service(&db, patient_request(&doctor), &doctor)
+            .await
+            .unwrap();
+        let patient_id = patient.id.id.to_raw();
+
+        let mut request = patient_request(&doctor);
+        request.name = "Renamed Patient".to_string();
+        request.date_of_birth = patient.date_of_birth.clone();
+        update_patient_service(&db, patient_id.clone(), request, &doctor)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let filter = AuditFilter {
+            patient_id: Some(patient_id),
+            ..Default::default()
+        };
+        let entries = get_audit_log_service(&db, filter, &doctor).await.unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let update = entries
+            .iter()
+            .find(|entry| entry.action == AuditAction::Update)
+            .unwrap();
+        let changes = update.changes.as_ref().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "name");
+        assert_eq!(changes[0].after, Some(serde_json::json!("Renamed Patient")));
+    }
+
+    #[tokio::test]
+    async fn test_deleted_patient_is_hidden() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+
+        let patient = create_patient_service(&db, patient_request(&doctor), &doctor)
+            .await
+            .unwrap();
+
+        let deleted = delete_patient_service(
+            &db,
+            patient.id.id.to_raw(),
+            CascadePolicy::Restrict,
+            &doctor,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(deleted.deleted_at.is_some());
+        assert!(get_patient_service(&db, &doctor).await.unwrap().is_empty());
+
+        let again = delete_patient_service(
+            &db,
+            patient.id.id.to_raw(),
+            CascadePolicy::Restrict,
+            &doctor,
+        )
+        .await
+        .unwrap();
+        assert!(again.is_none());
+    }
+
+    async fn create_test_note(db: &Surreal<Any>, patient: &Thing, user: &AuthenticatedUser) -> Thing {
+        let note: Vec<DependentRecord> = db
+            .query(
+                "CREATE PatientNote SET patient = $patient, user_owner = $user, symptoms = 'Test',
+                    diagnosis = 'Test', treatment = 'Test', severity = 'low', is_urgent = false",
+            )
+            .bind(("patient", patient.clone()))
+            .bind(("user", user.id.clone()))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        note[0].id.clone()
+    }
+
+    #[tokio::test]
+    async fn test_restrict_policy_blocks_deletion() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_patient_service(&db, patient_request(&doctor), &doctor)
+            .await
+            .unwrap();
+        create_test_note(&db, &patient.id, &doctor).await;
+
+        let result = delete_patient_service(
+            &db,
+            patient.id.id.to_raw(),
+            CascadePolicy::Restrict,
+            &doctor,
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err(),
+            AppError::OperationFailed("Patient still has 1 active notes and 0 active reports".to_string())
+        );
+        assert_eq!(get_patient_service(&db, &doctor).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_archive_policy_trashes_and_restores_dependents() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_patient_service(&db, patient_request(&doctor), &doctor)
+            .await
+            .unwrap();
+        let archived_note = create_test_note(&db, &patient.id, &doctor).await;
+        let trashed_note = create_test_note(&db, &patient.id, &doctor).await;
+
+        db.query("UPDATE $note SET deleted_at = time::now()")
+            .bind(("note", trashed_note.clone()))
+            .await
+            .unwrap();
+
+        delete_patient_service(
+            &db,
+            patient.id.id.to_raw(),
+            CascadePolicy::Archive,
+            &doctor,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let archived: Vec<Thing> = db
+            .query("SELECT VALUE id FROM PatientNote WHERE deleted_with = $patient")
+            .bind(("patient", patient.id.clone()))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(archived, vec![archived_note.clone()]);
+
+        restore_patient_service(&db, &patient.id, Vec::new()).await.unwrap();
+
+        let active: Vec<Thing> = db
+            .query("SELECT VALUE id FROM PatientNote WHERE deleted_at IS NONE")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(active, vec![archived_note]);
+        assert_eq!(get_patient_service(&db, &doctor).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_reports_rows() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+
+        let csv = "name,date_of_birth,gender,contact_number,address\r\n\
+            Jane Doe,1980-02-29,female,555-0100,\"1 Main St, Springfield\"\r\n\
+            John Roe,1980-02-30,unknown,,\r\n\
+            \r\n\
+            jane  doe,1980-02-29,Female,555-0101,Elsewhere\r\n";
+        let summary = import_patients_service(&db, ImportFormat::Csv, csv.to_string(), &doctor)
+            .await
+            .unwrap();
+
+        assert_eq!((summary.imported, summary.duplicates, summary.invalid), (1, 1, 1));
+        let rows: Vec<(usize, ImportRowStatus)> =
+            summary.rows.iter().map(|row| (row.row, row.status)).collect();
+        assert_eq!(
+            rows,
+            vec![
+                (2, ImportRowStatus::Imported),
+                (3, ImportRowStatus::Invalid),
+                (5, ImportRowStatus::Duplicate),
+            ]
+        );
+        assert_eq!(summary.rows[1].errors.len(), 2);
+        assert_eq!(summary.rows[2].patient, summary.rows[0].patient);
+
+        let patients = get_patient_service(&db, &doctor).await.unwrap();
+        assert_eq!(patients.len(), 1);
+        assert_eq!(patients[0].address, "1 Main St, Springfield");
+    }
+
+    #[tokio::test]
+    async fn test_import_fhir_deduplicates_existing() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+
+        let bundle = serde_json::json!({
+            "resourceType": "Bundle",
+            "type": "collection",
+            "entry": [
+                { "resource": { "resourceType": "Observation", "id": "obs" } },
+                { "resource": {
+                    "resourceType": "Patient",
+                    "name": [{ "given": ["Max"], "family": "Muster" }],
+                    "gender": "unknown",
+                    "birthDate": "1975-06-01",
+                    "telecom": [{ "system": "email", "value": "max@test.com" },
+                                { "system": "phone", "value": "555-0199" }],
+                    "address": [{ "line": ["2 Side St"], "city": "Berlin" }]
+                } },
+                { "resource": { "resourceType": "Patient", "gender": "male" } }
+            ]
+        })
+        .to_string();
+
+        let summary = import_patients_service(&db, ImportFormat::Fhir, bundle.clone(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!((summary.imported, summary.duplicates, summary.invalid), (1, 0, 1));
+        assert_eq!(summary.rows[0].row, 2);
+        assert_eq!(summary.rows[1].row, 3);
+
+        let patients = get_patient_service(&db, &doctor).await.unwrap();
+        assert_eq!(patients[0].name, "Max Muster");
+        assert_eq!(patients[0].gender, "other");
+        assert_eq!(patients[0].contact_number, "555-0199");
+        assert_eq!(patients[0].address, "2 Side St, Berlin");
+
+        let again = import_patients_service(&db, ImportFormat::Fhir, bundle, &other)
+            .await
+            .unwrap();
+        assert_eq!(again.duplicates, 1);
+        assert_eq!(again.rows[0].patient, None);
+        assert!(get_patient_service(&db, &other).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_identifier_conflict_leaves_no_patient() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let owner = create_patient_service(&db, patient_request(&doctor), &doctor)
+            .await
+            .unwrap();
+        let mrn = PatientIdentifier {
+            kind: IdentifierKind::Mrn,
+            system: "HOSP".to_string(),
+            value: "MRN-1".to_string(),
+        };
+        assign_identifiers(&db, &owner.id, std::slice::from_ref(&mrn), &doctor)
+            .await
+            .unwrap();
+
+        let mut request = patient_request(&doctor);
+        request.name = "Other Patient".to_string();
+        assert!(insert_patient(&db, request, &[mrn], &doctor).await.is_err());
+        assert_eq!(get_patient_service(&db, &doctor).await.unwrap().len(), 1);
+
+        let mut request = patient_request(&doctor);
+        request.name = "New Patient".to_string();
+        let ward = PatientIdentifier {
+            kind: IdentifierKind::Mrn,
+            system: "HOSP".to_string(),
+            value: "MRN-2".to_string(),
+        };
+        let created = insert_patient(&db, request, &[ward], &doctor).await.unwrap();
+        let stored: Vec<Thing> = db
+            .query("SELECT VALUE patient FROM PatientIdentifier WHERE value = 'MRN-2'")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(stored, vec![created.id]);
+    }
+
+    async fn create_named_patient(
+        db: &Surreal<Any>,
+        doctor: &AuthenticatedUser,
+        name: &str,
+        date_of_birth: &str,
+        gender: &str,
+    ) -> Thing {
+        let mut request = patient_request(doctor);
+        request.name = name.to_string();
+        request.date_of_birth = Datetime::try_from(date_of_birth).unwrap();
+        request.gender = gender.to_string();
+        create_patient_service(db, request, doctor).await.unwrap().id
+    }
+
+    fn names(response: &PatientSearchResponse) -> Vec<&str> {
+        response.patients.iter().map(|patient| patient.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_patients_filters() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+
+        let anna = create_named_patient(&db, &doctor, "Anna Schmidt", "1980-02-01T00:00:00Z", "female").await;
+        create_named_patient(&db, &doctor, "Andreas Maier", "1955-07-12T00:00:00Z", "male").await;
+        create_named_patient(&db, &other, "Bernd Huber", "1990-11-30T00:00:00Z", "male").await;
+        create_test_note(&db, &anna, &doctor).await;
+        db.query("UPDATE PatientNote SET diagnosis = 'Suspected Pneumothorax'")
+            .await
+            .unwrap();
+
+        let by_prefix = search_patients_service(
+            &db,
+            PatientSearchRequest {
+                name: Some("an".to_string()),
+                ..Default::default()
+            },
+            &doctor,
+        )
+        .await
+        .unwrap();
+        assert_eq!(names(&by_prefix), vec!["Andreas Maier", "Anna Schmidt"]);
+
+        let fuzzy = search_patients_service(
+            &db,
+            PatientSearchRequest {
+                name: Some("schmdt".to_string()),
+                ..Default::default()
+            },
+            &doctor,
+        )
+        .await
+        .unwrap();
+        assert_eq!(names(&fuzzy), vec!["Anna Schmidt"]);
+
+        let by_birth = search_patients_service(
+            &db,
+            PatientSearchRequest {
+                born_from: Some(Datetime::try_from("1950-01-01T00:00:00Z").unwrap()),
+                born_to: Some(Datetime::try_from("1960-01-01T00:00:00Z").unwrap()),
+                ..Default::default()
+            },
+            &doctor,
+        )
+        .await
+        .unwrap();
+        assert_eq!(names(&by_birth), vec!["Andreas Maier"]);
+
+        let by_gender = search_patients_service(
+            &db,
+            PatientSearchRequest {
+                gender: Some("Female".to_string()),
+                ..Default::default()
+            },
+            &doctor,
+        )
+        .await
+        .unwrap();
+        assert_eq!(names(&by_gender), vec!["Anna Schmidt"]);
+
+        let by_note = search_patients_service(
+            &db,
+            PatientSearchRequest {
+                note_text: Some("pneumothorax".to_string()),
+                ..Default::default()
+            },
+            &doctor,
+        )
+        .await
+        .unwrap();
+        assert_eq!(names(&by_note), vec!["Anna Schmidt"]);
+
+        let by_other_doctor = search_patients_service(
+            &db,
+            PatientSearchRequest {
+                doctor_id: Some(other.id.id.to_raw()),
+                ..Default::default()
+            },
+            &doctor,
+        )
+        .await
+        .unwrap();
+        assert!(by_other_doctor.patients.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_patients_pagination() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        for (name, born) in [
+            ("Carla", "1970-01-01T00:00:00Z"),
+            ("Anton", "1990-01-01T00:00:00Z"),
+            ("Bert", "1980-01-01T00:00:00Z"),
+        ] {
+            create_named_patient(&db, &doctor, name, born, "other").await;
+        }
+
+        let mut request = PatientSearchRequest {
+            sort: PatientSortField::DateOfBirth,
+            direction: SortDirection::Desc,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let first = search_patients_service(&db, request.clone(), &doctor).await.unwrap();
+        assert_eq!(names(&first), vec!["Anton", "Bert"]);
+
+        request.cursor = first.next_cursor;
+        let second = search_patients_service(&db, request.clone(), &doctor).await.unwrap();
+        assert_eq!(names(&second), vec!["Carla"]);
+        assert!(second.next_cursor.is_none());
+
+        request.sort = PatientSortField::Name;
+        assert!(search_patients_service(&db, request, &doctor).await.is_err());
+
+        let invalid = PatientSearchRequest {
+            cursor: Some("not-a-cursor".to_string()),
+            ..Default::default()
+        };
+        assert!(search_patients_service(&db, invalid, &doctor).await.is_err());
+    }
+
+    #[test]
+    fn test_duplicate_score_signals() {
+        assert!(name_similarity("Doe, John", "john  doe") > 0.99);
+        assert!(name_similarity("John Doe", "Jon Doe") >= NAME_SIMILARITY_THRESHOLD);
+        assert!(name_similarity("John Doe", "Jane Roe") < NAME_SIMILARITY_THRESHOLD);
+
+        assert!(same_contact_number("+49 170 1234567", "0170/1234567"));
+        assert!(!same_contact_number("0170 1234567", "0170 7654321"));
+        assert!(!same_contact_number("", ""));
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicate_patients() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+
+        let original =
+            create_named_patient(&db, &doctor, "John Doe", "1980-05-01T00:00:00Z", "male").await;
+        let duplicate =
+            create_named_patient(&db, &doctor, "Doe John", "1980-05-01T00:00:00Z", "male").await;
+        create_named_patient(&db, &doctor, "John Doe", "1990-01-01T00:00:00Z", "male").await;
+        let unrelated =
+            create_named_patient(&db, &doctor, "Jane Roe", "1980-05-01T00:00:00Z", "female").await;
+        db.query("UPDATE $patient SET contact_number = '555 0100'")
+            .bind(("patient", unrelated))
+            .await
+            .unwrap();
+
+        let candidates = find_duplicate_patients_service(&db, &doctor).await.unwrap();
+        let same_birth = candidates
+            .iter()
+            .find(|candidate| candidate.first.id == original && candidate.second.id == duplicate)
+            .unwrap();
+        assert_eq!(
+            same_birth.reasons,
+            vec![
+                DuplicateReason::SimilarName,
+                DuplicateReason::SameDateOfBirth,
+                DuplicateReason::SameContactNumber,
+            ]
+        );
+        assert_eq!(candidates[0].first.id, same_birth.first.id);
+        assert!(candidates
+            .iter()
+            .all(|candidate| candidate.first.name != "Jane Roe" && candidate.second.name != "Jane Roe"));
+
+        assert!(find_duplicate_patients_service(&db, &other).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_merge_patients_moves_records() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", Some("clinic")).await;
+        let colleague = create_test_user(&db, "colleague@test.com", Some("clinic")).await;
+
+        let survivor =
+            create_named_patient(&db, &doctor, "John Doe", "1980-05-01T00:00:00Z", "male").await;
+        let duplicate =
+            create_named_patient(&db, &doctor, "John Doe", "1980-05-01T00:00:00Z", "male").await;
+        db.query("UPDATE $survivor SET address = ''")
+            .bind(("survivor", survivor.clone()))
+            .await
+            .unwrap();
+        share_patient_service(&db, duplicate.id.to_raw(), colleague.id.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        let note = create_test_note(&db, &duplicate, &doctor).await;
+
+        let summary =
+            merge_patients_service(&db, survivor.id.to_raw(), duplicate.id.to_raw(), &doctor)
+                .await
+                .unwrap();
+        assert_eq!(summary.notes, 1);
+        assert_eq!(summary.treated_by, 1);
+        assert_eq!(summary.patient.address, "Test Address");
+
+        let moved: Option<Thing> = db
+            .query("SELECT VALUE patient FROM ONLY $note")
+            .bind(("note", note))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(moved, Some(survivor.clone()));
+        assert!(can_access_patient(&db, &survivor, &colleague).await.unwrap());
+        assert!(!can_access_patient(&db, &duplicate, &doctor).await.unwrap());
+
+        let filter = AuditFilter {
+            patient_id: Some(survivor.id.to_raw()),
+            ..Default::default()
+        };
+        let entries = get_audit_log_service(&db, filter, &doctor).await.unwrap();
+        let merge = entries
+            .iter()
+            .find(|entry| entry.action == AuditAction::Merge)
+            .unwrap();
+        let changes = merge.changes.as_ref().unwrap();
+        assert_eq!(changes[0].field, "merged_from");
+
+        let again =
+            merge_patients_service(&db, survivor.id.to_raw(), duplicate.id.to_raw(), &doctor).await;
+        assert!(again.is_err());
+        assert!(merge_patients_service(&db, survivor.id.to_raw(), survivor.id.to_raw(), &doctor)
+            .await
+            .is_err());
+        assert!(restore_patient_service(&db, &duplicate, Vec::new()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_patient_identifiers_are_unique_and_searchable() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+        let first =
+            create_named_patient(&db, &doctor, "Jane Doe", "1980-02-01T00:00:00Z", "female").await;
+        let second =
+            create_named_patient(&db, &doctor, "John Roe", "1975-06-15T00:00:00Z", "male").await;
+
+        let mrn = PatientIdentifier {
+            kind: IdentifierKind::Mrn,
+            system: " HOSP ".to_string(),
+            value: "MRN-1".to_string(),
+        };
+        let stored = add_patient_identifier_service(&db, first.id.to_raw(), mrn.clone(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(stored.system, "HOSP");
+        assert!(add_patient_identifier_service(&db, first.id.to_raw(), mrn.clone(), &doctor)
+            .await
+            .is_ok());
+        assert!(add_patient_identifier_service(&db, second.id.to_raw(), mrn, &doctor)
+            .await
+            .is_err());
+
+        let found =
+            find_patient_by_identifier_service(&db, "HOSP".to_string(), "MRN-1".to_string(), &doctor)
+                .await
+                .unwrap();
+        assert_eq!(found.unwrap().id, first);
+        let hidden =
+            find_patient_by_identifier_service(&db, "HOSP".to_string(), "MRN-1".to_string(), &other)
+                .await
+                .unwrap();
+        assert!(hidden.is_none());
+
+        let csv = "name,date_of_birth,gender,contact_number,address,identifier_kind,identifier_system,identifier_value\r\n\
+            Janet Doe,1980-02-02,female,555-0100,Elsewhere,mrn,HOSP,MRN-1\r\n";
+        let summary = import_patients_service(&db, ImportFormat::Csv, csv.to_string(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(summary.duplicates, 1);
+        assert_eq!(summary.rows[0].patient, Some(first.clone()));
+
+        let identifiers = get_patient_identifiers_service(&db, first.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(identifiers.len(), 1);
+        remove_patient_identifier_service(
+            &db,
+            first.id.to_raw(),
+            identifiers[0].id.id.to_raw(),
+            &doctor,
+        )
         .await
-        .map_err(|e| e.to_string())?;
-    Ok(deleted)
+        .unwrap();
+        assert!(get_patient_identifiers_service(&db, first.id.to_raw(), &doctor)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
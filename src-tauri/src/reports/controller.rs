@@ -1,6 +1,8 @@
-use crate::auth::validate::services::auth_middleware;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
 use super::models;
 use super::services;
+use crate::error::AppError;
 
 use scanlytics_db::DbConnection;
 use scanlytics_db::{Surreal, Any};
@@ -18,15 +20,17 @@ use tauri::State;
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
-/// * `report_request` - JSON string containing report data and image files
+/// * `report_request` - JSON string containing report data and image files;
+///   the report owner is always the authenticated session user
 /// * `app_handle` - Tauri application handle for accessing app paths
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(CreateReportResponse)` - Successfully created report with details
-/// * `Err(String)` - Error message if creation fails
+/// * `Err(AppError)` - Error message if creation fails
 ///
 /// # Errors
 ///
@@ -38,52 +42,96 @@ use tauri::State;
 
 #[tauri::command]
 pub async fn create_report(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
     report_request: String,
     app_handle: tauri::AppHandle,
-) -> Result<models::CreateReportResponse, String> {
-    println!("Creating report");
-    let db: MutexGuard<'_, Surreal<Any>> = db_connection.get().lock().await;
-    let report_request: models::ReportRequest = serde_json::from_str(&report_request)
-        .map_err(|e| format!("Tauri: Failed to parse report request : {}", e))?;
-   
-    let response: models::CreateReportResponse =
-        services::create_report_service(&db, report_request, app_handle).await?;
-
-    Ok(response)
+) -> Result<models::CreateReportResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db: MutexGuard<'_, Surreal<Any>> = db_connection.get().lock().await;
+        let report_request: models::ReportRequest = serde_json::from_str(&report_request)
+            .map_err(|e| AppError::invalid_input("report request", e))?;
+
+        let response: models::CreateReportResponse =
+            services::create_report_service(&db, report_request, &user, app_handle).await?;
+
+        Ok(response)
+    })
+    .await
+}
+
+
+/// Composes a structured report from image analysis results and creates it.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_request` - JSON string containing the analysis, selected statements,
+///   indication and image files
+/// * `app_handle` - Tauri application handle for accessing app paths
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - The created draft with its sections and rendered text
+/// * `Err(AppError)` - Error message if composing or creating fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report request JSON is invalid
+/// * The patient doesn't exist
+/// * Image processing, file system or database operations fail
+
+#[tauri::command]
+pub async fn compose_report(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_request: String,
+    app_handle: tauri::AppHandle,
+) -> Result<models::CreateReportResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let report_request: models::ComposeReportRequest = serde_json::from_str(&report_request)
+            .map_err(|e| AppError::invalid_input("report request", e))?;
+
+        services::compose_report_service(&db, report_request, &user, app_handle)
+            .await
+    })
+    .await
 }
 
 
-/// Retrieves all medical reports accessible to a specific user.
+/// Retrieves all medical reports accessible to the authenticated user.
 ///
-/// This endpoint is protected by authentication middleware and returns
+/// This endpoint is protected by the session middleware and returns
 /// all reports that the authenticated user has permission to view.
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
-/// * `username` - Username of the authenticated user
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Vec<ReportResponse>)` - List of medical reports
-/// * `Err(String)` - Error message if retrieval fails
-///
-/// # Authentication
-///
-/// This endpoint requires valid user authentication through the auth_middleware.
+/// * `Err(AppError)` - Error message if retrieval fails
 #[tauri::command]
 pub async fn get_reports(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
-    username: String,
-) -> Result<Vec<models::ReportResponse>, String> {
-    auth_middleware(&username, || async {
+) -> Result<Vec<models::ReportResponse>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
         let db = db_connection.get().lock().await;
-        services::get_reports_service(&db)
+        services::get_reports_service(&db, &user)
             .await
-            .map_err(|e| e.to_string())
-    }).await
+    })
+    .await
 }
 
 
@@ -91,6 +139,7 @@ pub async fn get_reports(
 ///
 /// # Arguments
 ///
+/// * `session_state` - Managed session state
 /// * `db_connection` - Database connection state
 /// * `report_id` - Unique identifier of the report
 ///
@@ -98,17 +147,338 @@ pub async fn get_reports(
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Vec<ImageInfo>)` - List of image information
-/// * `Err(String)` - Error message if retrieval fails
+/// * `Err(AppError)` - Error message if retrieval fails
 
 #[tauri::command]
 pub async fn get_report_images(
+    session_state: State<'_, SessionState>,
     db_connection: State<'_, DbConnection>,
     report_id: String,
-) -> Result<Vec<models::ImageInfo>, String> {
-    let db = db_connection.get().lock().await;
-    let response: Vec<models::ImageInfo> = services::get_report_images_service(&db, report_id)
-        .await
-        .map_err(|e| e.to_string())?;
+) -> Result<Vec<models::ImageInfo>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let response: Vec<models::ImageInfo> =
+            services::get_report_images_service(&db, report_id, &user).await?;
+
+        Ok(response)
+    })
+    .await
+}
+
+
+/// Moves a medical report to the trash.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - Trashed report
+/// * `Err(AppError)` - Error message if deletion fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report doesn't exist or is already in the trash
+/// * The session user doesn't own the report
+/// * Database operations fail
+
+#[tauri::command]
+pub async fn delete_report(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+) -> Result<models::CreateReportResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::delete_report_service(&db, report_id, &user)
+            .await?
+            .ok_or_else(|| AppError::NotFound("No record deleted".to_string()))
+    })
+    .await
+}
 
-    Ok(response)
+
+/// Updates the text of a draft report.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+/// * `report_request` - JSON string containing the report text and body part
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - Updated report
+/// * `Err(AppError)` - Error message if the update fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report request JSON is invalid
+/// * The report is not a draft
+/// * The session user doesn't own the report
+
+#[tauri::command]
+pub async fn update_report(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+    report_request: String,
+) -> Result<models::CreateReportResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let report_request: models::ReportUpdateRequest = serde_json::from_str(&report_request)
+            .map_err(|e| AppError::invalid_input("report request", e))?;
+
+        services::update_report_service(&db, report_id, report_request, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Releases a draft report as preliminary.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - Preliminary report
+/// * `Err(AppError)` - Error message if the report is not a draft
+
+#[tauri::command]
+pub async fn release_report_preliminary(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+) -> Result<models::CreateReportResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::release_preliminary_service(&db, report_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Signs off a report as final, recording the signing user and time.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - Final report
+/// * `Err(AppError)` - Error message if the report cannot be signed
+
+#[tauri::command]
+pub async fn sign_report(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+) -> Result<models::CreateReportResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::sign_report_service(&db, report_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Amends a final report by creating a new draft version linked to it.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the final report
+/// * `report_request` - JSON string containing the amended text and body part
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - The new draft version
+/// * `Err(AppError)` - Error message if the amendment fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report request JSON is invalid
+/// * The report is not final or already has an amendment in progress
+/// * Database operations fail
+
+#[tauri::command]
+pub async fn amend_report(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+    report_request: String,
+) -> Result<models::CreateReportResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let report_request: models::ReportUpdateRequest = serde_json::from_str(&report_request)
+            .map_err(|e| AppError::invalid_input("report request", e))?;
+
+        services::amend_report_service(&db, report_id, report_request, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Retrieves the revision history of a report.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<ReportRevisionResponse>)` - Revisions of the report and the versions it amends, oldest first
+/// * `Err(AppError)` - Error message if the report is not visible
+
+#[tauri::command]
+pub async fn get_report_history(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+) -> Result<Vec<models::ReportRevisionResponse>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::get_report_history_service(&db, report_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Compares two revisions of a report word by word.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+/// * `from_revision` - Identifier of the older revision
+/// * `to_revision` - Identifier of the newer revision
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(ReportDiffResponse)` - Both revisions and the word-level changes between them
+/// * `Err(AppError)` - Error message if a revision is not part of the report's history
+
+#[tauri::command]
+pub async fn diff_report_revisions(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+    from_revision: String,
+    to_revision: String,
+) -> Result<models::ReportDiffResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::diff_report_revisions_service(&db, report_id, from_revision, to_revision, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Exports a signed report as a PDF document.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `report_id` - Unique identifier of the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(ReportPdfResponse)` - Suggested file name and PDF bytes
+/// * `Err(AppError)` - Error message if the export fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report is not visible to the session user
+/// * The report has not been signed
+
+#[tauri::command]
+pub async fn export_report_pdf(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    report_id: String,
+) -> Result<models::ReportPdfResponse, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await.clone();
+        services::export_report_pdf_service(&db, report_id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Sets the letterhead printed on exported reports of the admin's organization.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `letterhead` - JSON string containing the title, contact lines and footer
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Letterhead)` - The stored letterhead
+/// * `Err(AppError)` - Error message if the update fails
+///
+/// Only admins can call this command.
+
+#[tauri::command]
+pub async fn update_letterhead(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    letterhead: String,
+) -> Result<models::Letterhead, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let letterhead: models::Letterhead = serde_json::from_str(&letterhead)
+            .map_err(|e| AppError::invalid_input("letterhead", e))?;
+
+        services::update_letterhead_service(&db, letterhead, &user)
+            .await
+    })
+    .await
 }
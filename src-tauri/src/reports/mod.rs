@@ -4,18 +4,24 @@
 //! - Report creation and management
 //! - Image processing and storage
 //! - Report retrieval and querying
+//! - Report lifecycle: draft, preliminary, final and amended
 //! 
 //! ## Components
 //! 
 //! - [`controller`]: Tauri command handlers for report operations
 //! - [`services`]: Report management business logic
 //! - [`models`]: Report-related data structures
+//! - `pdf`: Local PDF rendering of exported reports
 //! 
 //! ## Main Features
 //! 
 //! - Create medical reports with multiple images
+//! - Compose structured reports from analysis results and statements
 //! - Retrieve reports with patient and user information
 //! - Manage report-image relationships
+//! - Sign-off and versioned amendments of final reports
+//! - Revision history with word-level diffs between revisions
+//! - PDF export of signed reports with organization letterhead
 //! - Secure file storage and retrieval
 
 
@@ -23,3 +29,5 @@
 pub mod controller;
 pub mod models;
 pub mod services;
+
+mod pdf;
@@ -1,3 +1,5 @@
+use crate::image_analysis::image_processing::models::{AnalysisResponse, StatementResponse};
+use crate::patients::models::PatientIdentifier;
 use serde::{Deserialize, Serialize};
 use scanlytics_db::{Thing, Datetime};
 
@@ -5,10 +7,48 @@ use scanlytics_db::{Thing, Datetime};
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReportRequest {
     pub patient_id: String,
-    pub user_owner: String,
     pub report_text: String,
     pub body_part: String,
     pub files: Vec<FileData>,
+    #[serde(default)]
+    pub sections: Option<ReportSections>,
+}
+
+/// Structured content of a report.
+///
+/// Reports written as free text have no sections; composed reports keep
+/// them next to the rendered `report_text`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReportSections {
+    pub indication: String,
+    pub technique: String,
+    pub findings: String,
+    pub assessment: String,
+}
+
+/// Input for composing a structured report from an image analysis.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComposeReportRequest {
+    pub patient_id: String,
+    pub body_part: String,
+    /// Clinical indication; the statements' indications are used when absent
+    pub indication: Option<String>,
+    pub analysis: AnalysisResponse,
+    /// Statements selected by the user from the analysis
+    pub statements: Vec<StatementResponse>,
+    pub files: Vec<FileData>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatientDemographics {
+    pub id: Thing,
+    pub name: String,
+    pub date_of_birth: Datetime,
+    pub gender: String,
+    pub contact_number: Option<String>,
+    pub address: Option<String>,
+    #[serde(default)]
+    pub identifiers: Vec<PatientIdentifier>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -20,12 +60,51 @@ pub struct FileData {
 }
 
 
+/// Lifecycle status of a report.
+///
+/// Reports start as drafts and may be released as preliminary before they
+/// are signed off as final. A final report is never edited; amending it
+/// creates a new version, and the original becomes `Amended` once that
+/// version is signed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    #[default]
+    Draft,
+    Preliminary,
+    Final,
+    Amended,
+}
+
+impl std::fmt::Display for ReportStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportStatus::Draft => write!(f, "draft"),
+            ReportStatus::Preliminary => write!(f, "preliminary"),
+            ReportStatus::Final => write!(f, "final"),
+            ReportStatus::Amended => write!(f, "amended"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReportRecord {
     pub patient: Thing,
     pub user_owner: Thing,
     pub report_text: String,
     pub body_part: String,
+    pub status: ReportStatus,
+    pub version: u32,
+    pub amends: Option<Thing>,
+    pub sections: Option<ReportSections>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportUpdateRequest {
+    pub report_text: String,
+    pub body_part: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sections: Option<ReportSections>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,14 +120,38 @@ pub struct ImageResponse {
     pub updated_at: Datetime,
 }
 
-#[derive(Debug, Serialize, Deserialize,Clone)]
-pub struct ImageRequest {
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImageRecord {
+    pub id: Thing,
     pub name: String,
     pub path: String,
     pub patient: Thing,
     pub user: Thing,
     pub file_type: String,
     pub modal_type: String,
+    #[serde(default)]
+    pub dicom: Option<DicomReference>,
+    #[serde(default)]
+    pub classification: Option<ImageClassification>,
+}
+
+/// PACS instance an image was retrieved from.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DicomReference {
+    pub study_uid: String,
+    pub series_uid: String,
+    pub sop_instance_uid: String,
+    pub sop_class_uid: String,
+    /// Patient ID as known to the PACS
+    pub patient_id: Option<String>,
+    pub patient_name: Option<String>,
+}
+
+/// Result of classifying an image with an ML model.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImageClassification {
+    pub image_type: String,
+    pub confidence: f32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -57,8 +160,23 @@ pub struct CreateReportResponse {
     pub patient: Thing,
     pub user_owner: Thing,
     pub report_text: String,
+    pub body_part: Option<String>,
+    pub sections: Option<ReportSections>,
+    #[serde(default)]
+    pub status: ReportStatus,
+    #[serde(default = "first_version")]
+    pub version: u32,
+    pub signed_by: Option<Thing>,
+    pub signed_at: Option<Datetime>,
+    pub amends: Option<Thing>,
+    pub superseded_by: Option<Thing>,
     pub created_at: Datetime,
     pub updated_at: Datetime,
+    pub deleted_at: Option<Datetime>,
+}
+
+fn first_version() -> u32 {
+    1
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -68,10 +186,90 @@ pub struct ReportResponse {
     pub user_owner: UserInfo,
     pub report_text: String,
     pub body_part: String,
+    pub sections: Option<ReportSections>,
+    #[serde(default)]
+    pub status: ReportStatus,
+    #[serde(default = "first_version")]
+    pub version: u32,
+    pub signed_by: Option<UserInfo>,
+    pub signed_at: Option<Datetime>,
+    pub amends: Option<Thing>,
     pub created_at: Datetime,
     pub updated_at: Datetime,
 }
 
+/// A stored revision of a report's text.
+///
+/// A revision is written whenever a report is created, edited, released,
+/// signed or amended, so the history shows the text at every status.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportRevisionResponse {
+    pub id: Thing,
+    pub report: Thing,
+    pub version: u32,
+    pub status: ReportStatus,
+    pub report_text: String,
+    pub body_part: Option<String>,
+    pub author: UserInfo,
+    pub created_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A run of consecutive words that share the same diff operation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportDiffResponse {
+    pub from: ReportRevisionResponse,
+    pub to: ReportRevisionResponse,
+    pub segments: Vec<DiffSegment>,
+}
+
+/// Organization letterhead printed on exported report documents.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Letterhead {
+    pub title: String,
+    /// Address and contact lines below the title
+    pub lines: Vec<String>,
+    pub footer: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrganizationLetterhead {
+    pub name: String,
+    pub address: String,
+    pub email: String,
+    pub letterhead: Option<Letterhead>,
+}
+
+/// Names and demographics printed alongside an exported report.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportDocumentInfo {
+    pub author: String,
+    pub signer: Option<String>,
+    pub patient: PatientDemographics,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportPdfResponse {
+    pub filename: String,
+    #[serde(with = "serde_bytes")]
+    pub data: Vec<u8>,
+    /// Images that couldn't be included in the document
+    pub warnings: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PatientInfo {
     pub id: Thing,
@@ -90,4 +288,5 @@ pub struct ImageInfo {
     pub id: Thing,
     pub path: String,
     pub name: String,
+    pub patient: Thing,
 }
\ No newline at end of file
@@ -0,0 +1,400 @@
+//! Minimal PDF writer for report documents.
+//!
+//! Produces single-column A4 documents with wrapped text in the standard
+//! Helvetica fonts and embedded JPEG thumbnails. Everything is generated
+//! locally; no fonts or other resources are embedded or fetched.
+
+use image::codecs::jpeg::JpegEncoder;
+use image::RgbImage;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const FOOTER_HEIGHT: f32 = 30.0;
+const LINE_SPACING: f32 = 1.35;
+const THUMBNAILS_PER_ROW: usize = 3;
+const THUMBNAIL_GAP: f32 = 15.0;
+const THUMBNAIL_SIZE: u32 = 300;
+const THUMBNAIL_QUALITY: u8 = 85;
+
+/// A captioned image scaled down and encoded as JPEG for embedding.
+pub(crate) struct Thumbnail {
+    caption: String,
+    width: u32,
+    height: u32,
+    jpeg: Vec<u8>,
+}
+
+impl Thumbnail {
+    /// Decodes the image file at `path` into a thumbnail.
+    pub(crate) fn open(caption: String, path: &str) -> Result<Self, String> {
+        let decoded = image::open(path).map_err(|e| e.to_string())?;
+        let rgb = decoded.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8();
+        Self::from_rgb(caption, &rgb)
+    }
+
+    /// Encodes an RGB image, which should already be thumbnail sized.
+    pub(crate) fn from_rgb(caption: String, image: &RgbImage) -> Result<Self, String> {
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, THUMBNAIL_QUALITY)
+            .encode_image(image)
+            .map_err(|e| e.to_string())?;
+        Ok(Thumbnail {
+            caption,
+            width: image.width(),
+            height: image.height(),
+            jpeg,
+        })
+    }
+}
+
+/// Lays out text and images top to bottom, starting new pages as needed.
+pub(crate) struct PdfDocument {
+    pages: Vec<String>,
+    images: Vec<Thumbnail>,
+    footer: Option<String>,
+    y: f32,
+}
+
+impl PdfDocument {
+    pub(crate) fn new(footer: Option<String>) -> Self {
+        PdfDocument {
+            pages: vec![String::new()],
+            images: Vec::new(),
+            footer,
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    /// Writes wrapped text at the given font size.
+    pub(crate) fn text(&mut self, text: &str, size: f32, bold: bool) {
+        let max_width = PAGE_WIDTH - 2.0 * MARGIN;
+        for paragraph in text.lines() {
+            let lines = wrap(paragraph, size, max_width);
+            if lines.is_empty() {
+                self.space(size * LINE_SPACING);
+            }
+            for line in lines {
+                self.ensure_space(size * LINE_SPACING);
+                self.y -= size * LINE_SPACING;
+                let font = if bold { "F2" } else { "F1" };
+                self.current().push_str(&format!(
+                    "BT /{} {} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+                    font,
+                    size,
+                    MARGIN,
+                    self.y,
+                    escape(&line)
+                ));
+            }
+        }
+    }
+
+    /// Adds vertical space.
+    pub(crate) fn space(&mut self, height: f32) {
+        self.ensure_space(height);
+        self.y -= height;
+    }
+
+    /// Draws a horizontal rule across the text column.
+    pub(crate) fn rule(&mut self) {
+        self.space(6.0);
+        let y = self.y;
+        self.current().push_str(&format!(
+            "0.6 w {:.2} {:.2} m {:.2} {:.2} l S\n",
+            MARGIN,
+            y,
+            PAGE_WIDTH - MARGIN,
+            y
+        ));
+        self.space(6.0);
+    }
+
+    /// Draws images as a grid of captioned thumbnails.
+    pub(crate) fn thumbnails(&mut self, thumbnails: Vec<Thumbnail>) {
+        let cell = (PAGE_WIDTH - 2.0 * MARGIN - THUMBNAIL_GAP * (THUMBNAILS_PER_ROW - 1) as f32)
+            / THUMBNAILS_PER_ROW as f32;
+        let caption_size = 8.0;
+
+        let mut thumbnails = thumbnails.into_iter().peekable();
+        while thumbnails.peek().is_some() {
+            self.ensure_space(cell + caption_size * 2.0 + THUMBNAIL_GAP);
+            let top = self.y;
+            for (column, thumbnail) in thumbnails.by_ref().take(THUMBNAILS_PER_ROW).enumerate() {
+                let scale = cell / thumbnail.width.max(thumbnail.height).max(1) as f32;
+                let width = thumbnail.width as f32 * scale;
+                let height = thumbnail.height as f32 * scale;
+                let x = MARGIN + column as f32 * (cell + THUMBNAIL_GAP) + (cell - width) / 2.0;
+                let y = top - cell + (cell - height) / 2.0;
+
+                let name = format!("Im{}", self.images.len() + 1);
+                let caption_x = MARGIN + column as f32 * (cell + THUMBNAIL_GAP);
+                let caption = truncate(&thumbnail.caption, caption_size, cell);
+                self.images.push(thumbnail);
+                self.current().push_str(&format!(
+                    "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /{} Do Q\n\
+                     BT /F1 {} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+                    width,
+                    height,
+                    x,
+                    y,
+                    name,
+                    caption_size,
+                    caption_x,
+                    top - cell - caption_size * 1.5,
+                    escape(&caption)
+                ));
+            }
+            self.y = top - cell - caption_size * 2.0 - THUMBNAIL_GAP;
+        }
+    }
+
+    /// Serializes the document, adding the footer and page numbers.
+    pub(crate) fn finish(self) -> Vec<u8> {
+        let page_count = self.pages.len();
+        let image_base = 5;
+        let page_base = image_base + self.images.len();
+
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        let kids: Vec<String> = (0..page_count)
+            .map(|index| format!("{} 0 R", page_base + index * 2))
+            .collect();
+        objects.push(
+            format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), page_count)
+                .into_bytes(),
+        );
+        for font in ["Helvetica", "Helvetica-Bold"] {
+            objects.push(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    font
+                )
+                .into_bytes(),
+            );
+        }
+        for image in &self.images {
+            let mut object = format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} \
+                 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+                image.width,
+                image.height,
+                image.jpeg.len()
+            )
+            .into_bytes();
+            object.extend_from_slice(&image.jpeg);
+            object.extend_from_slice(b"\nendstream");
+            objects.push(object);
+        }
+
+        let image_resources: String = (0..self.images.len())
+            .map(|index| format!("/Im{} {} 0 R ", index + 1, image_base + index))
+            .collect();
+        for (index, content) in self.pages.iter().enumerate() {
+            let mut content = content.clone();
+            if let Some(footer) = &self.footer {
+                content.push_str(&format!(
+                    "BT /F1 8 Tf {:.2} {:.2} Td ({}) Tj ET\n",
+                    MARGIN,
+                    MARGIN / 2.0,
+                    escape(&truncate(footer, 8.0, PAGE_WIDTH - 2.0 * MARGIN - 80.0))
+                ));
+            }
+            let page_number = format!("Page {} of {}", index + 1, page_count);
+            content.push_str(&format!(
+                "BT /F1 8 Tf {:.2} {:.2} Td ({}) Tj ET\n",
+                PAGE_WIDTH - MARGIN - text_width(&page_number, 8.0),
+                MARGIN / 2.0,
+                page_number
+            ));
+
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject << {}>> >> \
+                     /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    image_resources,
+                    page_base + index * 2 + 1
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend_from_slice(content.as_bytes());
+            stream.extend_from_slice(b"endstream");
+            objects.push(stream);
+        }
+
+        let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            pdf.extend_from_slice(object);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = pdf.len();
+        pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        pdf.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .as_bytes(),
+        );
+        pdf
+    }
+
+    fn current(&mut self) -> &mut String {
+        self.pages.last_mut().expect("document always has a page")
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN + FOOTER_HEIGHT {
+            self.pages.push(String::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+}
+
+/// Approximate width of a character in Helvetica, in thousandths of an em.
+fn char_width(c: char) -> f32 {
+    match c {
+        'i' | 'j' | 'l' | '\'' | '|' => 222.0,
+        ' ' | '.' | ',' | ':' | ';' | '!' | 'f' | 't' | 'I' | '/' | '(' | ')' | '[' | ']' => 278.0,
+        'r' | '-' => 333.0,
+        'm' | 'M' => 833.0,
+        'w' | 'W' => 944.0,
+        'A'..='Z' => 700.0,
+        _ => 556.0,
+    }
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().map(char_width).sum::<f32>() * size / 1000.0
+}
+
+/// Breaks a paragraph into lines that fit the given width.
+///
+/// Words longer than a line are split across lines.
+
+fn wrap(paragraph: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in paragraph.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if text_width(&candidate, size) <= max_width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            if !line.is_empty() && text_width(&line, size) + char_width(c) * size / 1000.0 > max_width {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn truncate(text: &str, size: f32, max_width: f32) -> String {
+    if text_width(text, size) <= max_width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    for c in text.chars() {
+        if text_width(&truncated, size) + char_width(c) * size / 1000.0 + text_width("...", size)
+            > max_width
+        {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.push_str("...");
+    truncated
+}
+
+/// Escapes a string for a PDF literal, mapping it to WinAnsi (Latin-1) bytes.
+///
+/// Characters outside Latin-1 are replaced with `?`.
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            '\u{a0}'..='\u{ff}' => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xref_offsets_point_to_objects() {
+        let mut document = PdfDocument::new(Some("Footer".to_string()));
+        document.text("Report (draft) for Zoë", 12.0, true);
+        let thumbnail = Thumbnail::from_rgb("a.png".to_string(), &RgbImage::new(4, 2)).unwrap();
+        document.thumbnails(vec![thumbnail]);
+        let pdf = document.finish();
+
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        let text = String::from_utf8_lossy(&pdf);
+        let start = text.rfind("startxref\n").unwrap() + "startxref\n".len();
+        let xref: usize = text[start..].lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with(b"xref"));
+
+        let entries: Vec<usize> = String::from_utf8_lossy(&pdf[xref..])
+            .lines()
+            .skip(3)
+            .take_while(|line| line.ends_with(" n "))
+            .map(|line| line[..10].parse().unwrap())
+            .collect();
+        assert_eq!(entries.len(), 7);
+        for (index, offset) in entries.into_iter().enumerate() {
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", index + 1).as_bytes()));
+        }
+        assert!(text.contains("(Report \\(draft\\) for Zo\\353)"));
+        assert!(text.contains("/Width 4 /Height 2"));
+        assert!(text.contains("/Filter /DCTDecode"));
+        assert!(Thumbnail::open("missing.png".to_string(), "/missing/image.png").is_err());
+    }
+
+    #[test]
+    fn test_long_text_starts_new_pages() {
+        let mut document = PdfDocument::new(None);
+        document.text(&"Findings ".repeat(2000), 10.0, false);
+        assert!(document.pages.len() > 1);
+
+        let lines = wrap(&"x".repeat(500), 10.0, 100.0);
+        assert!(lines.iter().all(|line| text_width(line, 10.0) <= 100.0));
+    }
+}
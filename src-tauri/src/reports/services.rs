@@ -1,34 +1,51 @@
 use super::models;
+use super::pdf::{PdfDocument, Thumbnail};
+use crate::audit::models::AuditAction;
+use crate::audit::services::{
+    audit_changes, audit_entries, audit_entry, merge_changes, record_audit, record_reads,
+};
+use crate::auth::session::models::AuthenticatedUser;
+use crate::image_analysis::image_processing::models::ImageResult;
+use crate::patients::services::{can_access_patient, load_identifiers};
+use crate::error::{check_transaction, AppError};
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 
-use scanlytics_db::{Surreal, Any, Error as SurrealError};
+use scanlytics_db::{Surreal, Any, Datetime, Id, Thing};
 use tauri::Manager;
 
 /// Creates a new medical report with associated images in the system.
 ///
 /// This service handles:
-/// 1. Validation of patient and user existence
+/// 1. Validation of patient existence
 /// 2. Image processing and storage
 /// 3. Report creation in the database
 /// 4. Relationship creation between reports and images
 ///
+/// Images are decoded and written to a staging directory first. The image
+/// records, the report and their relations are then created in a single
+/// transaction, and the staged files are only moved into `saved_images`
+/// once it has committed. Any failure leaves neither records nor files behind.
+///
 /// # Arguments
 ///
 /// * `db` - Database connection
 /// * `report_request` - Report creation request containing all necessary data
+/// * `user` - Authenticated user owning the report
 /// * `app_handle` - Tauri application handle for file system operations
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(CreateReportResponse)` - Successfully created report
-/// * `Err(String)` - Error message detailing what went wrong
+/// * `Err(AppError)` - Error message detailing what went wrong
 ///
 /// # Errors
 ///
 /// This function will return an error if:
-/// * Patient or user not found in database
+/// * Patient not found in database, or not treated by the user
 /// * Image processing fails
 /// * File system operations fail
 /// * Database operations fail
@@ -36,107 +53,428 @@ use tauri::Manager;
 pub async fn create_report_service(
     db: &Surreal<Any>,
     report_request: models::ReportRequest,
+    user: &AuthenticatedUser,
     app_handle: tauri::AppHandle,
-) -> Result<models::CreateReportResponse, String> {
-    let patient: Option<models::PatientInfo> = db
-        .select(("Patient", &report_request.patient_id))
-        .await
-        .map_err(|e| e.to_string())?;
-    let patient = patient.ok_or_else(|| "Patient not found".to_string())?;
-
-    let user_owner: Option<models::UserInfo> = db
-        .select(("User", &report_request.user_owner))
-        .await
-        .map_err(|e| e.to_string())?;
-    let user_owner = user_owner.ok_or_else(|| "User not found".to_string())?;
-
-    let mut image_ids = Vec::new();
-
+) -> Result<models::CreateReportResponse, AppError> {
     let app_local_data_dir = app_handle
         .path()
         .app_local_data_dir()
-        .map_err(|e| format!("Failed to get app local data directory: {}", e))?;
+        .map_err(|e| AppError::OperationFailed(format!("Failed to get app local data directory: {}", e)))?;
 
     let save_dir = app_local_data_dir.join("saved_images");
-    if !save_dir.exists() {
-        fs::create_dir_all(&save_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    create_report_in_dir(db, report_request, user, &save_dir).await
+}
+
+/// Creates a report whose images are stored in `save_dir`.
+///
+/// See [`create_report_service`] for the transactional guarantees.
+
+async fn create_report_in_dir(
+    db: &Surreal<Any>,
+    report_request: models::ReportRequest,
+    user: &AuthenticatedUser,
+    save_dir: &Path,
+) -> Result<models::CreateReportResponse, AppError> {
+    let patient: Option<models::PatientInfo> = db
+        .query("SELECT id, name FROM type::thing('Patient', $id) WHERE deleted_at IS NONE")
+        .bind(("id", report_request.patient_id.clone()))
+        .await?
+        .take(0)?;
+    let patient = patient.ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+    if !can_access_patient(db, &patient.id, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
     }
 
-    for file in &report_request.files {
-        let image_request = models::ImageRequest {
-            name: file.filename.clone(),
-            path: String::new(),
-            patient: patient.id.clone(),
-            user: user_owner.id.clone(),
-            file_type: file.extension.clone(),
-            modal_type: "xray".to_string(),
-        };
+    let report_id = Thing::from(("Report", Id::rand()));
+    let staging = StagingDir::create(save_dir, &report_id.id.to_raw())?;
 
-        let created_image: models::ImageResponse = db
-        .create("Image")
-        .content(image_request)
-        .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Failed to create image".to_string())?;
-    
-        let file_name = format!("{}.{}", created_image.id, file.extension);
+    let mut images = Vec::new();
+    let mut staged_files = Vec::new();
+
+    for file in &report_request.files {
+        let image_id = Thing::from(("Image", Id::rand()));
+        let file_name = format!("{}.{}", image_id, file.extension);
+        let staged_path = staging.path.join(&file_name);
         let file_path = save_dir.join(&file_name);
 
         let image = image::load_from_memory(&file.data)
-            .map_err(|e| format!("Failed to load image: {}", e))?;
+            .map_err(|e| AppError::InvalidInput(format!("Failed to load image: {}", e)))?;
 
         image
-            .save(&file_path)
-            .map_err(|e| format!("Failed to save image: {}", e))?;
+            .save(&staged_path)
+            .map_err(|e| AppError::OperationFailed(format!("Failed to save image: {}", e)))?;
 
         let file_path_str = file_path
             .to_str()
-            .ok_or_else(|| "File path contains invalid Unicode".to_string())?
+            .ok_or_else(|| AppError::OperationFailed("File path contains invalid Unicode".to_string()))?
             .to_string();
 
-        let image_id = created_image.id.clone();
-
-        db.query("UPDATE type::thing($table, $id) SET path = $path")
-            .bind(("table", "Image"))
-            .bind(("id", created_image.id))
-            .bind(("path", file_path_str))
-            .await
-            .map_err(|e| e.to_string())?;
-       
-            image_ids.push(image_id);
-
+        images.push(models::ImageRecord {
+            id: image_id,
+            name: file.filename.clone(),
+            path: file_path_str,
+            patient: patient.id.clone(),
+            user: user.id.clone(),
+            file_type: file.extension.clone(),
+            modal_type: "xray".to_string(),
+            dicom: None,
+            classification: None,
+        });
+        staged_files.push((staged_path, file_path));
     }
 
     let report_record = models::ReportRecord {
-        patient: patient.id,
-        user_owner: user_owner.id,
+        patient: patient.id.clone(),
+        user_owner: user.id.clone(),
         report_text: report_request.report_text,
         body_part: report_request.body_part,
+        status: models::ReportStatus::Draft,
+        version: 1,
+        amends: None,
+        sections: report_request.sections,
     };
+    let image_ids: Vec<Thing> = images.iter().map(|image| image.id.clone()).collect();
 
-    let report: models::CreateReportResponse = db
-        .create("Report")
-        .content(report_record)
-        .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Failed to create report".to_string())?;
+    let mut audit: Vec<_> = images
+        .iter()
+        .map(|image| {
+            let changes = audit_changes(None::<&models::ImageRecord>, Some(image));
+            audit_entry(user, AuditAction::Create, &image.id, Some(&patient.id), changes)
+        })
+        .collect();
+    let changes = audit_changes(None::<&models::ReportRecord>, Some(&report_record));
+    audit.push(audit_entry(user, AuditAction::Create, &report_id, Some(&patient.id), changes));
 
-    let report_id = report.id.clone();
-    
+    let create = with_revision(
+        "
+        IF array::len($images) > 0 {
+            INSERT INTO Image $images;
+        };
+        CREATE $report CONTENT $content;
+        FOR $image IN $image_ids {
+            RELATE $image -> Images_Reports_Join -> $report;
+        };
+        ",
+    );
+    db.query(create)
+        .bind(("images", images))
+        .bind(("image_ids", image_ids.clone()))
+        .bind(("report", report_id.clone()))
+        .bind(("content", report_record))
+        .bind(("revision_of", report_id.clone()))
+        .bind(("author", user.id.clone()))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
 
-    for image_id in image_ids {
-        db.query("RELATE $image -> Images_Reports_Join -> $report")
-            .bind(("image", image_id))
+    if let Err(e) = publish_staged_files(&staged_files) {
+        let removed = image_ids
+            .iter()
+            .chain(std::iter::once(&report_id))
+            .map(|target| (target.clone(), Some(patient.id.clone())))
+            .collect();
+        let rollback = db
+            .query(
+                "
+                BEGIN TRANSACTION;
+                DELETE Images_Reports_Join WHERE out = $report;
+                DELETE ReportRevision WHERE report = $report;
+                DELETE $report;
+                DELETE Image WHERE id INSIDE $image_ids;
+                INSERT INTO AuditLog $audit;
+                COMMIT TRANSACTION;
+                ",
+            )
             .bind(("report", report_id.clone()))
+            .bind(("image_ids", image_ids))
+            .bind(("audit", audit_entries(Some(user), AuditAction::Delete, removed)))
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(AppError::from)
+            .and_then(check_transaction);
+        if let Err(rollback_error) = rollback {
+            log::error!("Failed to roll back report {}: {}", report_id, rollback_error);
+            return Err(AppError::OperationFailed(format!(
+                "{}; removing the report again also failed: {}",
+                e, rollback_error
+            )));
+        }
+        return Err(e);
+    }
+
+    let report: models::CreateReportResponse = db
+        .select(("Report", report_id.id.to_raw()))
+        .await?
+        .ok_or_else(|| AppError::OperationFailed("Failed to create report".to_string()))?;
+
+    Ok(report)
+}
+
+/// Moves staged image files to their final location.
+///
+/// Files that were already moved are removed again if a later move fails,
+/// so the caller only has to roll back the database.
+
+fn publish_staged_files(files: &[(PathBuf, PathBuf)]) -> Result<(), AppError> {
+    for (index, (staged, target)) in files.iter().enumerate() {
+        if let Err(e) = fs::rename(staged, target) {
+            for (_, published) in &files[..index] {
+                let _ = fs::remove_file(published);
+            }
+            return Err(AppError::OperationFailed(format!("Failed to store image: {}", e)));
+        }
+    }
+    Ok(())
+}
+
+/// Temporary directory for files of a report being created.
+///
+/// The directory and anything left in it are removed when dropped, which
+/// cleans up after failed report creations.
+
+struct StagingDir {
+    path: PathBuf,
+}
+
+impl StagingDir {
+    fn create(save_dir: &Path, name: &str) -> Result<Self, AppError> {
+        let path = save_dir.join(".staging").join(name);
+        fs::create_dir_all(&path)
+            .map_err(|e| AppError::OperationFailed(format!("Failed to create directory: {}", e)))?;
+        Ok(StagingDir { path })
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
     }
+}
+
+
+/// Composes a structured report from an image analysis and creates it.
+///
+/// The indication, technique, findings and assessment sections are built
+/// from the analysis results, the selected statements and the patient's
+/// demographics. They are stored on the report together with the rendered
+/// report text, and the report is created as a draft like any other.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `request` - Analysis results, selected statements and images
+/// * `user` - Authenticated user composing the report
+/// * `app_handle` - Tauri application handle for accessing app paths
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - The created draft report
+/// * `Err(AppError)` - Error message if composing or creating fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * Patient not found in database
+/// * Report creation fails
+
+pub async fn compose_report_service(
+    db: &Surreal<Any>,
+    request: models::ComposeReportRequest,
+    user: &AuthenticatedUser,
+    app_handle: tauri::AppHandle,
+) -> Result<models::CreateReportResponse, AppError> {
+    let results = request.analysis.results.clone();
+    let report_request = compose_report_request(db, request, user).await?;
+    let report = create_report_service(db, report_request, user, app_handle).await?;
+
+    let images: Vec<Thing> = db
+        .query("SELECT VALUE in FROM Images_Reports_Join WHERE out = $report")
+        .bind(("report", report.id.clone()))
+        .await?
+        .take(0)?;
+    store_classifications(db, &report.patient, &images, &results, user).await?;
 
     Ok(report)
 }
 
+/// Stores analysis results on the images they were computed for.
+///
+/// Results are matched to images by filename; results without a matching
+/// image are ignored. The classifications are written together with an
+/// `AnalysisRun` record listing the matched images, in one transaction.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient` - Patient the images belong to
+/// * `images` - Images the results may belong to
+/// * `results` - Analysis results, one per image file
+/// * `user` - Authenticated user who ran the analysis
+///
+/// # Returns
+///
+/// Returns a `Result` indicating success or failure
+
+pub(crate) async fn store_classifications(
+    db: &Surreal<Any>,
+    patient: &Thing,
+    images: &[Thing],
+    results: &[ImageResult],
+    user: &AuthenticatedUser,
+) -> Result<(), AppError> {
+    let classifications: HashMap<String, models::ImageClassification> = results
+        .iter()
+        .map(|result| {
+            let classification = models::ImageClassification {
+                image_type: result.image_type.clone(),
+                confidence: result.confidence,
+            };
+            (result.filename.clone(), classification)
+        })
+        .collect();
+    let names: Vec<String> = classifications.keys().cloned().collect();
+
+    let store = "
+        BEGIN TRANSACTION;
+        LET $matched = SELECT
+                id AS image,
+                $classifications[name].image_type AS image_type,
+                $classifications[name].confidence AS confidence
+            FROM Image
+            WHERE id INSIDE $images AND name INSIDE $names;
+        FOR $result IN $matched {
+            UPDATE $result.image SET
+                classification = { image_type: $result.image_type, confidence: $result.confidence },
+                analyzed_at = $analyzed_at;
+        };
+        IF array::len($matched) > 0 {
+            CREATE AnalysisRun SET patient = $patient, user = $user, results = $matched,
+                analyzed_at = $analyzed_at;
+        };
+        COMMIT TRANSACTION;
+    ";
+    db.query(store)
+        .bind(("classifications", classifications))
+        .bind(("names", names))
+        .bind(("images", images.to_vec()))
+        .bind(("patient", patient.clone()))
+        .bind(("user", user.id.clone()))
+        .bind(("analyzed_at", Datetime::default()))
+        .await?
+        .check()?;
+    Ok(())
+}
+
+/// Builds the report request for a composed report.
 
-/// Retrieves all medical reports from the database with related information.
+async fn compose_report_request(
+    db: &Surreal<Any>,
+    request: models::ComposeReportRequest,
+    user: &AuthenticatedUser,
+) -> Result<models::ReportRequest, AppError> {
+    let patient: Option<models::PatientDemographics> = db
+        .query(
+            "SELECT id, name, date_of_birth, gender FROM type::thing('Patient', $id)
+            WHERE deleted_at IS NONE",
+        )
+        .bind(("id", request.patient_id.clone()))
+        .await?
+        .take(0)?;
+    let patient = patient.ok_or_else(|| AppError::NotFound("Patient not found".to_string()))?;
+    if !can_access_patient(db, &patient.id, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
+
+    let sections = compose_sections(&request);
+    let report_text = render_report_text(&patient, &request.body_part, &sections);
+
+    Ok(models::ReportRequest {
+        patient_id: request.patient_id,
+        report_text,
+        body_part: request.body_part,
+        files: request.files,
+        sections: Some(sections),
+    })
+}
+
+/// Builds the report sections from the analysis and selected statements.
+///
+/// Repeated statement texts are included once, in the order selected.
+
+pub(crate) fn compose_sections(request: &models::ComposeReportRequest) -> models::ReportSections {
+    let indication = match request.indication.as_deref().map(str::trim) {
+        Some(indication) if !indication.is_empty() => indication.to_string(),
+        _ => join_distinct(request.statements.iter().map(|s| s.indication.as_str())),
+    };
+
+    let images = request.analysis.results.len();
+    let mut technique = format!(
+        "Radiograph of the {}, {} image{}.",
+        request.body_part,
+        images,
+        if images == 1 { "" } else { "s" }
+    );
+    for result in &request.analysis.results {
+        technique.push_str(&format!(
+            "\n{}: classified as {} ({:.0}% confidence).",
+            result.filename,
+            result.image_type,
+            result.confidence * 100.0
+        ));
+    }
+
+    models::ReportSections {
+        indication,
+        technique,
+        findings: join_distinct(request.statements.iter().map(|s| s.statement.as_str())),
+        assessment: join_distinct(request.statements.iter().map(|s| s.assessment.as_str())),
+    }
+}
+
+/// Renders the sections of a composed report as plain text.
+
+pub(crate) fn render_report_text(
+    patient: &models::PatientDemographics,
+    body_part: &str,
+    sections: &models::ReportSections,
+) -> String {
+    let section = |title: &str, text: &str| {
+        let text = if text.trim().is_empty() { "Not specified." } else { text };
+        format!("{}\n{}", title, text)
+    };
+
+    [
+        format!(
+            "Patient: {}, {}, born {}\nExamination: {}",
+            patient.name,
+            patient.gender,
+            patient.date_of_birth.0.format("%Y-%m-%d"),
+            body_part
+        ),
+        section("INDICATION", &sections.indication),
+        section("TECHNIQUE", &sections.technique),
+        section("FINDINGS", &sections.findings),
+        section("ASSESSMENT", &sections.assessment),
+    ]
+    .join("\n\n")
+}
+
+fn join_distinct<'a>(texts: impl Iterator<Item = &'a str>) -> String {
+    let mut distinct: Vec<&str> = Vec::new();
+    for text in texts.map(str::trim).filter(|text| !text.is_empty()) {
+        if !distinct.contains(&text) {
+            distinct.push(text);
+        }
+    }
+    distinct.join("\n")
+}
+
+
+/// Retrieves the medical reports visible to the user with related information.
+///
+/// A report is visible when the user owns it or treats the report's patient.
+/// Admins see every report. Reports in the trash, or whose patient is in the
+/// trash, are excluded, as are versions replaced by a signed amendment.
 ///
 /// Fetches reports including:
 /// - Basic report information
@@ -146,59 +484,1466 @@ pub async fn create_report_service(
 /// # Arguments
 ///
 /// * `db` - Database connection
+/// * `user` - Authenticated user
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Vec<ReportResponse>)` - List of reports with related data
-/// * `Err(SurrealError)` - Database error if query fails
+/// * `Err(AppError)` - Error message if the query fails
 
 pub async fn get_reports_service(
     db: &Surreal<Any>,
-) -> Result<Vec<models::ReportResponse>, SurrealError> {
+    user: &AuthenticatedUser,
+) -> Result<Vec<models::ReportResponse>, AppError> {
     let query = "
             SELECT
                 id,
                 report_text,
                 body_part,
+                sections,
                 condition,
+                status,
+                version,
+                signed_by.{ id, name } as signed_by,
+                signed_at,
+                amends,
                 { id: patient.id, name: patient.name } as patient,
                 { id: user_owner.id, name: user_owner.name } as user_owner,
                 created_at,
                 updated_at
             FROM Report
+            WHERE deleted_at IS NONE
+                AND superseded_by IS NONE
+                AND patient.deleted_at IS NONE
+                AND ($is_admin
+                    OR user_owner = $user
+                    OR $user INSIDE patient->Treated_By->User)
             FETCH patient, user_owner;
         ";
-    let result: Vec<models::ReportResponse> = db.query(query).await?.take(0)?;
+    let result: Vec<models::ReportResponse> = db
+        .query(query)
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?
+        .take(0)?;
+
+    let reads = result
+        .iter()
+        .map(|report| (report.id.clone(), Some(report.patient.id.clone())))
+        .collect();
+    record_reads(db, user, reads).await?;
+
     Ok(result)
 }
 
 
 /// Retrieves all images associated with a specific report.
 ///
+/// The report must be visible to the user; reports in the trash, or of
+/// patients the user doesn't treat, are reported as not found.
+///
 /// # Arguments
 ///
 /// * `db` - Database connection
 /// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user viewing the images
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Vec<ImageInfo>)` - List of image information
-/// * `Err(SurrealError)` - Database error if query fails
+/// * `Err(AppError)` - Error message if the query fails
 
 pub async fn get_report_images_service(
     db: &Surreal<Any>,
     report_id: String,
-) -> Result<Vec<models::ImageInfo>, SurrealError> {
+    user: &AuthenticatedUser,
+) -> Result<Vec<models::ImageInfo>, AppError> {
+    let report = load_visible_report(db, &report_id, user).await?;
+
     let query = "
-    SELECT id, name, path FROM (SELECT * FROM Images_Reports_Join WHERE out = type::thing('Report', $report_id)).in
- 
+    SELECT id, name, path, patient FROM (SELECT * FROM Images_Reports_Join WHERE out = $report).in
     ";
     let result: Vec<models::ImageInfo> = db
         .query(query)
-        .bind(("report_id", report_id))
+        .bind(("report", report.id))
         .await?
         .take(0)?;
+
+    let reads = result
+        .iter()
+        .map(|image| (image.id.clone(), Some(image.patient.clone())))
+        .collect();
+    record_reads(db, user, reads).await?;
+
     Ok(result)
 }
+
+
+/// Moves a medical report to the trash.
+///
+/// Only the report owner, or a user whose role permits it, may delete a
+/// report. The report can be restored from the trash until it is purged.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user performing the deletion
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Option<CreateReportResponse>)` - Trashed report if found
+/// * `Err(AppError)` - Error message if deletion fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The user is not permitted to delete the report
+/// * Database operations fail
+
+pub async fn delete_report_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    user: &AuthenticatedUser,
+) -> Result<Option<models::CreateReportResponse>, AppError> {
+    let existing: Option<models::CreateReportResponse> = db
+        .select(("Report", &report_id))
+        .await?;
+    let Some(existing) = existing.filter(|report| report.deleted_at.is_none()) else {
+        return Ok(None);
+    };
+
+    if !user.can_modify(&existing.user_owner) {
+        return Err(AppError::Forbidden("Not permitted to delete a report owned by another user".to_string()));
+    }
+
+    let now = Datetime::default();
+    let changes = merge_changes(&existing, &serde_json::json!({ "deleted_at": now }));
+    let audit = vec![audit_entry(user, AuditAction::Delete, &existing.id, Some(&existing.patient), changes)];
+
+    let delete = "
+        BEGIN TRANSACTION;
+        UPDATE $report SET deleted_at = $now, deleted_by = $user;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    db.query(delete)
+        .bind(("report", existing.id.clone()))
+        .bind(("now", now))
+        .bind(("user", user.id.clone()))
+        .bind(("audit", audit))
+        .await?
+        .check()?;
+
+    db.select(("Report", &report_id)).await.map_err(AppError::from)
+}
+
+
+/// Updates the text of a draft report.
+///
+/// Only drafts can be edited; preliminary and final reports are locked. The
+/// status is checked again inside the update, so a report released or signed
+/// concurrently is left untouched.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `request` - Updated report text and body part
+/// * `user` - Authenticated user editing the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - Updated report
+/// * `Err(AppError)` - Error message if the update fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report doesn't exist or is in the trash
+/// * The user is not permitted to edit the report
+/// * The report is not a draft
+
+pub async fn update_report_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    request: models::ReportUpdateRequest,
+    user: &AuthenticatedUser,
+) -> Result<models::CreateReportResponse, AppError> {
+    let existing = load_report(db, &report_id, user).await?;
+    ensure_status(&existing, &[models::ReportStatus::Draft], "edited")?;
+
+    let changes = merge_changes(&existing, &request);
+    let audit = vec![audit_entry(user, AuditAction::Update, &existing.id, Some(&existing.patient), changes)];
+
+    let update = with_revision(
+        "
+        LET $changed = UPDATE $report MERGE $changes WHERE status = 'draft' AND deleted_at IS NONE;
+        IF array::len($changed) = 0 {
+            THROW 'The report is no longer a draft';
+        };
+        ",
+    );
+    let response = db
+        .query(update)
+        .bind(("report", existing.id.clone()))
+        .bind(("changes", request))
+        .bind(("revision_of", existing.id.clone()))
+        .bind(("author", user.id.clone()))
+        .bind(("audit", audit))
+        .await?;
+    check_transaction(response)?;
+
+    load_report(db, &report_id, user).await
+}
+
+/// Releases a draft report as preliminary.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user releasing the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - Preliminary report
+/// * `Err(AppError)` - Error message if the transition fails
+
+pub async fn release_preliminary_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    user: &AuthenticatedUser,
+) -> Result<models::CreateReportResponse, AppError> {
+    let existing = load_report(db, &report_id, user).await?;
+    ensure_status(&existing, &[models::ReportStatus::Draft], "released as preliminary")?;
+
+    let changes = merge_changes(
+        &existing,
+        &serde_json::json!({ "status": models::ReportStatus::Preliminary }),
+    );
+    let audit = vec![audit_entry(user, AuditAction::Update, &existing.id, Some(&existing.patient), changes)];
+
+    let release = with_revision(
+        "
+        LET $changed = UPDATE $report SET status = 'preliminary'
+            WHERE status = 'draft' AND deleted_at IS NONE;
+        IF array::len($changed) = 0 {
+            THROW 'The report is no longer a draft';
+        };
+        ",
+    );
+    let response = db
+        .query(release)
+        .bind(("report", existing.id.clone()))
+        .bind(("revision_of", existing.id.clone()))
+        .bind(("author", user.id.clone()))
+        .bind(("audit", audit))
+        .await?;
+    check_transaction(response)?;
+
+    load_report(db, &report_id, user).await
+}
+
+/// Signs off a draft or preliminary report as final.
+///
+/// Records the signing user and time. Signing an amendment also marks the
+/// version it amends as `amended`. A report signed concurrently is rejected
+/// rather than signed twice.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user signing the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - Final report
+/// * `Err(AppError)` - Error message if sign-off fails
+
+pub async fn sign_report_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    user: &AuthenticatedUser,
+) -> Result<models::CreateReportResponse, AppError> {
+    let existing = load_report(db, &report_id, user).await?;
+    ensure_status(
+        &existing,
+        &[models::ReportStatus::Draft, models::ReportStatus::Preliminary],
+        "signed",
+    )?;
+
+    let now = Datetime::default();
+    let changes = merge_changes(
+        &existing,
+        &serde_json::json!({
+            "status": models::ReportStatus::Final,
+            "signed_by": user.id,
+            "signed_at": now,
+        }),
+    );
+    let audit = vec![audit_entry(user, AuditAction::Update, &existing.id, Some(&existing.patient), changes)];
+
+    let sign = with_revision(
+        "
+        LET $changed = UPDATE $report SET status = 'final', signed_by = $user, signed_at = $now
+            WHERE status INSIDE ['draft', 'preliminary'] AND deleted_at IS NONE;
+        IF array::len($changed) = 0 {
+            THROW 'The report has already been signed';
+        };
+        IF $amends {
+            UPDATE $amends SET status = 'amended', superseded_by = $report;
+        };
+        ",
+    );
+    let response = db
+        .query(sign)
+        .bind(("report", existing.id.clone()))
+        .bind(("user", user.id.clone()))
+        .bind(("now", now))
+        .bind(("amends", existing.amends.clone()))
+        .bind(("revision_of", existing.id.clone()))
+        .bind(("author", user.id.clone()))
+        .bind(("audit", audit))
+        .await?;
+    check_transaction(response)?;
+
+    load_report(db, &report_id, user).await
+}
+
+/// Amends a final report by creating a new draft version linked to it.
+///
+/// The original stays final and unchanged until the amendment is signed.
+/// The new version is attached to the same images. The check for an
+/// amendment already in progress runs in the same transaction as the insert.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the final report
+/// * `request` - Amended report text and body part
+/// * `user` - Authenticated user amending the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(CreateReportResponse)` - The new draft version
+/// * `Err(AppError)` - Error message if the amendment fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report is not final
+/// * An amendment of the report is already in progress
+/// * Database operations fail
+
+pub async fn amend_report_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    request: models::ReportUpdateRequest,
+    user: &AuthenticatedUser,
+) -> Result<models::CreateReportResponse, AppError> {
+    let original = load_report(db, &report_id, user).await?;
+    ensure_status(&original, &[models::ReportStatus::Final], "amended")?;
+
+    let amendment_id = Thing::from(("Report", Id::rand()));
+    let amendment_record = models::ReportRecord {
+        patient: original.patient.clone(),
+        user_owner: user.id.clone(),
+        report_text: request.report_text,
+        body_part: request.body_part,
+        status: models::ReportStatus::Draft,
+        version: original.version + 1,
+        amends: Some(original.id.clone()),
+        sections: request.sections,
+    };
+    let changes = merge_changes(&original, &amendment_record);
+    let audit = vec![audit_entry(user, AuditAction::Create, &amendment_id, Some(&original.patient), changes)];
+
+    let amend = with_revision(
+        "
+        IF $report.status != 'final' OR $report.deleted_at IS NOT NONE {
+            THROW 'The report is no longer final';
+        };
+        IF array::len(SELECT VALUE id FROM Report WHERE amends = $report AND deleted_at IS NONE) > 0 {
+            THROW 'An amendment of this report is already in progress';
+        };
+        CREATE $amendment CONTENT $content;
+        FOR $image IN (SELECT VALUE in FROM Images_Reports_Join WHERE out = $report) {
+            RELATE $image -> Images_Reports_Join -> $amendment;
+        };
+        ",
+    );
+    let response = db
+        .query(amend)
+        .bind(("amendment", amendment_id.clone()))
+        .bind(("content", amendment_record))
+        .bind(("report", original.id.clone()))
+        .bind(("revision_of", amendment_id.clone()))
+        .bind(("author", user.id.clone()))
+        .bind(("audit", audit))
+        .await?;
+    check_transaction(response)?;
+
+    load_report(db, &amendment_id.id.to_raw(), user).await
+}
+
+/// Retrieves every stored revision of a report.
+///
+/// The history follows the `amends` links back to the first version, so an
+/// amendment also lists the revisions of the reports it replaces.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user viewing the history
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<ReportRevisionResponse>)` - Revisions, oldest first
+/// * `Err(AppError)` - Error message if the report is not visible or the query fails
+
+pub async fn get_report_history_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    user: &AuthenticatedUser,
+) -> Result<Vec<models::ReportRevisionResponse>, AppError> {
+    let report = load_visible_report(db, &report_id, user).await?;
+
+    let mut lineage = vec![report.id.clone()];
+    let mut amends = report.amends.clone();
+    while let Some(previous) = amends {
+        if lineage.contains(&previous) {
+            break;
+        }
+        let older: Option<Option<Thing>> = db
+            .query("SELECT VALUE amends FROM ONLY $report")
+            .bind(("report", previous.clone()))
+            .await?
+            .take(0)?;
+        lineage.push(previous);
+        amends = older.flatten();
+    }
+
+    let query = "
+        SELECT
+            id,
+            report,
+            version,
+            status,
+            report_text,
+            body_part,
+            author.{ id, name } AS author,
+            created_at
+        FROM ReportRevision
+        WHERE report INSIDE $lineage
+        ORDER BY version ASC, created_at ASC;
+    ";
+    let revisions: Vec<models::ReportRevisionResponse> = db
+        .query(query)
+        .bind(("lineage", lineage))
+        .await?
+        .take(0)?;
+
+    record_reads(db, user, vec![(report.id, Some(report.patient))]).await?;
+
+    Ok(revisions)
+}
+
+/// Compares two revisions of a report word by word.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `from_revision` - Identifier of the older revision
+/// * `to_revision` - Identifier of the newer revision
+/// * `user` - Authenticated user viewing the diff
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(ReportDiffResponse)` - Both revisions and the word-level changes between them
+/// * `Err(AppError)` - Error message if a revision is not part of the report's history
+
+pub async fn diff_report_revisions_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    from_revision: String,
+    to_revision: String,
+    user: &AuthenticatedUser,
+) -> Result<models::ReportDiffResponse, AppError> {
+    let history = get_report_history_service(db, report_id, user).await?;
+    let find = |revision_id: &str| {
+        history
+            .iter()
+            .find(|revision| revision.id.id.to_raw() == revision_id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Revision {} not found in report history", revision_id)))
+    };
+    let from = find(&from_revision)?;
+    let to = find(&to_revision)?;
+
+    let segments = word_diff(&from.report_text, &to.report_text);
+    Ok(models::ReportDiffResponse { from, to, segments })
+}
+
+/// Computes a word-level diff from the longest common subsequence of words.
+///
+/// The subsequence is found with Hirschberg's algorithm, so memory grows
+/// with the length of the texts rather than their product. Whitespace is
+/// not preserved; consecutive words with the same operation are joined by
+/// single spaces.
+
+pub(crate) fn word_diff(old: &str, new: &str) -> Vec<models::DiffSegment> {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    let mut ops = Vec::with_capacity(old.len().max(new.len()));
+    diff_words(&old, &new, &mut ops);
+
+    let mut segments: Vec<models::DiffSegment> = Vec::new();
+    for (op, word) in ops {
+        match segments.last_mut() {
+            Some(last) if last.op == op => {
+                last.text.push(' ');
+                last.text.push_str(word);
+            }
+            _ => segments.push(models::DiffSegment { op, text: word.to_string() }),
+        }
+    }
+    segments
+}
+
+/// Appends the operations turning `old` into `new` to `ops`.
+///
+/// Common leading and trailing words are matched directly. The rest is
+/// split where an optimal alignment of the first half of `old` ends, and
+/// each side is diffed on its own.
+
+fn diff_words<'a>(old: &[&'a str], new: &[&'a str], ops: &mut Vec<(models::DiffOp, &'a str)>) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    ops.extend(old[..prefix].iter().map(|word| (models::DiffOp::Equal, *word)));
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old.iter().rev().zip(new.iter().rev()).take_while(|(a, b)| a == b).count();
+    let (common, old, new) = (
+        &new[new.len() - suffix..],
+        &old[..old.len() - suffix],
+        &new[..new.len() - suffix],
+    );
+
+    match (old.len(), new.len()) {
+        (0, _) => ops.extend(new.iter().map(|word| (models::DiffOp::Insert, *word))),
+        (_, 0) => ops.extend(old.iter().map(|word| (models::DiffOp::Delete, *word))),
+        (1, _) => match new.iter().position(|word| *word == old[0]) {
+            Some(k) => {
+                ops.extend(new[..k].iter().map(|word| (models::DiffOp::Insert, *word)));
+                ops.push((models::DiffOp::Equal, old[0]));
+                ops.extend(new[k + 1..].iter().map(|word| (models::DiffOp::Insert, *word)));
+            }
+            None => {
+                ops.push((models::DiffOp::Delete, old[0]));
+                ops.extend(new.iter().map(|word| (models::DiffOp::Insert, *word)));
+            }
+        },
+        _ => {
+            let mid = old.len() / 2;
+            let left = lcs_lengths(old[..mid].iter().copied(), new.iter().copied());
+            let right = lcs_lengths(old[mid..].iter().rev().copied(), new.iter().rev().copied());
+            let split = (0..=new.len())
+                .max_by_key(|&j| (left[j] + right[new.len() - j], std::cmp::Reverse(j)))
+                .unwrap_or(0);
+            diff_words(&old[..mid], &new[..split], ops);
+            diff_words(&old[mid..], &new[split..], ops);
+        }
+    }
+    ops.extend(common.iter().map(|word| (models::DiffOp::Equal, *word)));
+}
+
+/// Lengths of the longest common subsequences of `old` and each prefix of
+/// `new`, computed one row at a time.
+
+fn lcs_lengths<'a>(
+    old: impl Iterator<Item = &'a str>,
+    new: impl Iterator<Item = &'a str> + Clone,
+) -> Vec<usize> {
+    let len = new.clone().count();
+    let mut previous = vec![0; len + 1];
+    let mut current = vec![0; len + 1];
+    for a in old {
+        for (j, b) in new.clone().enumerate() {
+            current[j + 1] = if a == b {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous
+}
+
+/// Exports a signed report as a PDF document.
+///
+/// The document contains the organization letterhead, a patient header,
+/// the report text, author and sign-off, and thumbnails of the report's
+/// images. It is rendered locally on a blocking thread; images that can't
+/// be decoded are left out with a warning. The export is recorded in the
+/// audit log as a share of the report.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `report_id` - Unique identifier of the report
+/// * `user` - Authenticated user exporting the report
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(ReportPdfResponse)` - File name, PDF bytes and warnings
+/// * `Err(AppError)` - Error message if the export fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The report is not visible to the user
+/// * The report has not been signed
+/// * Database operations fail
+
+pub async fn export_report_pdf_service(
+    db: &Surreal<Any>,
+    report_id: String,
+    user: &AuthenticatedUser,
+) -> Result<models::ReportPdfResponse, AppError> {
+    let report = load_visible_report(db, &report_id, user).await?;
+    ensure_status(
+        &report,
+        &[models::ReportStatus::Final, models::ReportStatus::Amended],
+        "exported",
+    )?;
+
+    let info = load_document_info(db, &report.id).await?;
+    let letterhead = load_letterhead(db, user).await?;
+    let images = get_report_images_service(db, report_id, user).await?;
+
+    let filename = format!("report-{}-v{}.pdf", report.id.id.to_raw(), report.version);
+    let (target, patient) = (report.id.clone(), report.patient.clone());
+    let (data, warnings) = tauri::async_runtime::spawn_blocking(move || {
+        render_report_pdf(&report, &info, letterhead.as_ref(), &images)
+    })
+    .await
+    .map_err(|e| AppError::OperationFailed(format!("Failed to render PDF: {}", e)))?;
+
+    record_audit(db, user, AuditAction::Share, &target, Some(&patient), None).await?;
+
+    Ok(models::ReportPdfResponse { filename, data, warnings })
+}
+
+/// Lays out a report document and serializes it.
+///
+/// Images that can't be decoded are left out and reported as warnings.
+
+fn render_report_pdf(
+    report: &models::CreateReportResponse,
+    info: &models::ReportDocumentInfo,
+    letterhead: Option<&models::Letterhead>,
+    images: &[models::ImageInfo],
+) -> (Vec<u8>, Vec<String>) {
+    let mut document = PdfDocument::new(letterhead.and_then(|l| l.footer.clone()));
+    if let Some(letterhead) = letterhead {
+        document.text(&letterhead.title, 16.0, true);
+        for line in &letterhead.lines {
+            document.text(line, 9.0, false);
+        }
+        document.rule();
+    }
+
+    document.text("Medical Report", 14.0, true);
+    document.space(4.0);
+    document.text(
+        &format!(
+            "Patient: {}    Date of birth: {}    Gender: {}",
+            info.patient.name,
+            info.patient.date_of_birth.0.format("%Y-%m-%d"),
+            info.patient.gender
+        ),
+        10.0,
+        false,
+    );
+    document.text(
+        &format!(
+            "Examination: {}    Status: {}    Version: {}",
+            report.body_part.as_deref().unwrap_or("-"),
+            report.status,
+            report.version
+        ),
+        10.0,
+        false,
+    );
+    document.rule();
+    document.text(&report.report_text, 11.0, false);
+    document.space(12.0);
+    document.rule();
+
+    document.text(
+        &format!("Author: {}, created {}", info.author, format_datetime(&report.created_at)),
+        10.0,
+        false,
+    );
+    if let (Some(signer), Some(signed_at)) = (&info.signer, &report.signed_at) {
+        document.text(
+            &format!("Electronically signed by {} on {}", signer, format_datetime(signed_at)),
+            10.0,
+            true,
+        );
+    }
+    if report.status == models::ReportStatus::Amended {
+        document.text("This report has been superseded by an amended version.", 10.0, true);
+    }
+
+    let mut warnings = Vec::new();
+    let thumbnails: Vec<Thumbnail> = images
+        .iter()
+        .filter_map(|image| match Thumbnail::open(image.name.clone(), &image.path) {
+            Ok(thumbnail) => Some(thumbnail),
+            Err(e) => {
+                warnings.push(format!("Image {} was left out: {}", image.name, e));
+                None
+            }
+        })
+        .collect();
+    if !thumbnails.is_empty() {
+        document.space(12.0);
+        document.text("Images", 12.0, true);
+        document.space(6.0);
+        document.thumbnails(thumbnails);
+    }
+
+    (document.finish(), warnings)
+}
+
+/// Loads the author, signer and patient demographics and identifiers of a report.
+
+pub(crate) async fn load_document_info(
+    db: &Surreal<Any>,
+    report: &Thing,
+) -> Result<models::ReportDocumentInfo, AppError> {
+    let info: Option<models::ReportDocumentInfo> = db
+        .query(
+            "SELECT
+                user_owner.name AS author,
+                signed_by.name AS signer,
+                patient.{ id, name, date_of_birth, gender, contact_number, address } AS patient
+            FROM $report",
+        )
+        .bind(("report", report.clone()))
+        .await?
+        .take(0)?;
+    let mut info = info.ok_or_else(|| AppError::NotFound("Report not found".to_string()))?;
+    info.patient.identifiers = load_identifiers(db, &info.patient.id).await?;
+    Ok(info)
+}
+
+/// Sets the letterhead printed on reports exported by the admin's organization.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `letterhead` - Title, contact lines and footer of the letterhead
+/// * `user` - Authenticated admin
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Letterhead)` - The stored letterhead
+/// * `Err(AppError)` - Error message if the update fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The user is not an admin or has no organization
+/// * The letterhead title is empty
+
+pub async fn update_letterhead_service(
+    db: &Surreal<Any>,
+    letterhead: models::Letterhead,
+    user: &AuthenticatedUser,
+) -> Result<models::Letterhead, AppError> {
+    if !user.is_admin() {
+        return Err(AppError::Forbidden("Only admins can change the letterhead".to_string()));
+    }
+    let organization = user
+        .organization
+        .clone()
+        .ok_or_else(|| AppError::Forbidden("User doesn't belong to an organization".to_string()))?;
+    if letterhead.title.trim().is_empty() {
+        return Err(AppError::validation("title", "Letterhead title must not be empty"));
+    }
+
+    db.query("UPDATE $organization SET letterhead = $letterhead")
+        .bind(("organization", organization))
+        .bind(("letterhead", letterhead.clone()))
+        .await?
+        .check()?;
+
+    Ok(letterhead)
+}
+
+/// Loads the letterhead of the user's organization.
+///
+/// Organizations without a configured letterhead use their name, address
+/// and email.
+
+async fn load_letterhead(
+    db: &Surreal<Any>,
+    user: &AuthenticatedUser,
+) -> Result<Option<models::Letterhead>, AppError> {
+    let Some(organization) = user.organization.clone() else {
+        return Ok(None);
+    };
+    let organization: Option<models::OrganizationLetterhead> = db
+        .query("SELECT name, address, email, letterhead FROM $organization")
+        .bind(("organization", organization))
+        .await?
+        .take(0)?;
+
+    Ok(organization.map(|organization| {
+        organization.letterhead.unwrap_or(models::Letterhead {
+            title: organization.name,
+            lines: vec![organization.address, organization.email],
+            footer: None,
+        })
+    }))
+}
+
+fn format_datetime(datetime: &Datetime) -> String {
+    datetime.0.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+/// Wraps report statements in a transaction that also stores a revision and
+/// the audit entries of the change.
+///
+/// The caller binds `$revision_of` to the report whose state is recorded,
+/// `$author` to the user making the change and `$audit` to the audit entries.
+
+fn with_revision(statements: &str) -> String {
+    format!(
+        "
+        BEGIN TRANSACTION;
+        {}
+        LET $revised = SELECT * FROM ONLY $revision_of;
+        CREATE ReportRevision SET
+            report = $revised.id,
+            version = $revised.version,
+            status = $revised.status,
+            report_text = $revised.report_text,
+            body_part = $revised.body_part,
+            author = $author;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+        ",
+        statements
+    )
+}
+
+/// Loads a report the user may view, ignoring reports in the trash.
+
+pub(crate) async fn load_visible_report(
+    db: &Surreal<Any>,
+    report_id: &str,
+    user: &AuthenticatedUser,
+) -> Result<models::CreateReportResponse, AppError> {
+    let report: Option<models::CreateReportResponse> = db
+        .select(("Report", report_id))
+        .await?;
+    let report = report
+        .filter(|report| report.deleted_at.is_none())
+        .ok_or_else(|| AppError::NotFound("Report not found".to_string()))?;
+
+    if report.user_owner != user.id && !can_access_patient(db, &report.patient, user).await? {
+        return Err(AppError::NotFound("Report not found".to_string()));
+    }
+    Ok(report)
+}
+
+/// Loads a report the user may modify, ignoring reports in the trash.
+
+async fn load_report(
+    db: &Surreal<Any>,
+    report_id: &str,
+    user: &AuthenticatedUser,
+) -> Result<models::CreateReportResponse, AppError> {
+    let report: Option<models::CreateReportResponse> = db
+        .select(("Report", report_id))
+        .await?;
+    let report = report
+        .filter(|report| report.deleted_at.is_none())
+        .ok_or_else(|| AppError::NotFound("Report not found".to_string()))?;
+
+    if !user.can_modify(&report.user_owner) {
+        return Err(AppError::Forbidden("Not permitted to modify a report owned by another user".to_string()));
+    }
+    Ok(report)
+}
+
+/// Rejects lifecycle actions that are not allowed in the report's status.
+
+pub(crate) fn ensure_status(
+    report: &models::CreateReportResponse,
+    allowed: &[models::ReportStatus],
+    action: &str,
+) -> Result<(), AppError> {
+    if allowed.contains(&report.status) {
+        Ok(())
+    } else {
+        Err(AppError::OperationFailed(format!(
+            "A {} report cannot be {}",
+            report.status, action
+        )))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_test_db;
+    use image::{DynamicImage, ImageFormat, RgbImage};
+    use std::io::Cursor;
+
+    async fn setup_patient(db: &Surreal<Any>) -> (AuthenticatedUser, String) {
+        let mut created = db
+            .query("CREATE User SET name = 'Test Doctor', email = 'doctor@test.com', role = 'user'")
+            .query(
+                "CREATE Patient SET name = 'Test Patient', date_of_birth = time::now(),
+                    gender = 'male', contact_number = '1234567890', address = 'Test Address'",
+            )
+            .await
+            .unwrap();
+        let users: Vec<models::UserInfo> = created.take(0).unwrap();
+        let patients: Vec<models::PatientInfo> = created.take(1).unwrap();
+        db.query("RELATE $patient->Treated_By->$user")
+            .bind(("patient", patients[0].id.clone()))
+            .bind(("user", users[0].id.clone()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let user = AuthenticatedUser {
+            id: users[0].id.clone(),
+            name: users[0].name.clone(),
+            email: "doctor@test.com".to_string(),
+            role: "user".to_string(),
+            organization: None,
+        };
+        (user, patients[0].id.id.to_raw())
+    }
+
+    fn png_file(filename: &str) -> models::FileData {
+        let mut data = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(2, 2))
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .unwrap();
+        models::FileData {
+            filename: filename.to_string(),
+            extension: "png".to_string(),
+            data,
+        }
+    }
+
+    fn report_request(patient_id: String, files: Vec<models::FileData>) -> models::ReportRequest {
+        models::ReportRequest {
+            patient_id,
+            report_text: "Unremarkable findings".to_string(),
+            body_part: "thorax".to_string(),
+            files,
+            sections: None,
+        }
+    }
+
+    async fn count(db: &Surreal<Any>, table: &str) -> usize {
+        let ids: Vec<Thing> = db
+            .query("SELECT VALUE id FROM type::table($table)")
+            .bind(("table", table.to_string()))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        ids.len()
+    }
+
+    #[tokio::test]
+    async fn test_create_report_stores_images() {
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let request = report_request(patient_id, vec![png_file("a.png"), png_file("b.png")]);
+        let report = create_report_in_dir(&db, request, &user, dir.path()).await.unwrap();
+
+        let images = get_report_images_service(&db, report.id.id.to_raw(), &user)
+            .await
+            .unwrap();
+        assert_eq!(images.len(), 2);
+        assert!(images.iter().all(|image| Path::new(&image.path).exists()));
+
+        let staging = dir.path().join(".staging").join(report.id.id.to_raw());
+        assert!(!staging.exists());
+    }
+
+    #[tokio::test]
+    async fn test_reports_require_patient_access() {
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let request = report_request(patient_id.clone(), vec![png_file("a.png")]);
+        let report = create_report_in_dir(&db, request, &user, dir.path()).await.unwrap();
+
+        let other: Vec<models::UserInfo> = db
+            .query("CREATE User SET name = 'Other Doctor', email = 'other@test.com', role = 'user'")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        let other = AuthenticatedUser {
+            id: other[0].id.clone(),
+            name: other[0].name.clone(),
+            email: "other@test.com".to_string(),
+            role: "user".to_string(),
+            organization: None,
+        };
+
+        let request = report_request(patient_id, vec![png_file("b.png")]);
+        let result = create_report_in_dir(&db, request, &other, dir.path()).await;
+        assert_eq!(result.unwrap_err(), AppError::NotFound("Patient not found".to_string()));
+
+        let result = get_report_images_service(&db, report.id.id.to_raw(), &other).await;
+        assert_eq!(result.unwrap_err(), AppError::NotFound("Report not found".to_string()));
+
+        delete_report_service(&db, report.id.id.to_raw(), &user).await.unwrap();
+        let result = get_report_images_service(&db, report.id.id.to_raw(), &user).await;
+        assert_eq!(result.unwrap_err(), AppError::NotFound("Report not found".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_failed_report_leaves_no_orphans() {
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let broken = models::FileData {
+            filename: "broken.png".to_string(),
+            extension: "png".to_string(),
+            data: vec![0, 1, 2, 3],
+        };
+        let request = report_request(patient_id, vec![png_file("a.png"), broken]);
+        let result = create_report_in_dir(&db, request, &user, dir.path()).await;
+        assert!(result.is_err());
+
+        assert_eq!(count(&db, "Image").await, 0);
+        assert_eq!(count(&db, "Report").await, 0);
+
+        let leftovers: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name() != ".staging")
+            .collect();
+        assert!(leftovers.is_empty());
+        assert_eq!(fs::read_dir(dir.path().join(".staging")).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_classifications_are_stored_as_one_run() {
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let request = report_request(patient_id, vec![png_file("a.png"), png_file("b.png")]);
+        let report = create_report_in_dir(&db, request, &user, dir.path()).await.unwrap();
+        let images: Vec<Thing> = db
+            .query("SELECT VALUE in FROM Images_Reports_Join WHERE out = $report")
+            .bind(("report", report.id.clone()))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+
+        let results = vec![
+            ImageResult {
+                filename: "a.png".to_string(),
+                image_type: "chest".to_string(),
+                confidence: 0.9,
+            },
+            ImageResult {
+                filename: "missing.png".to_string(),
+                image_type: "knee".to_string(),
+                confidence: 0.5,
+            },
+        ];
+        store_classifications(&db, &report.patient, &images, &results, &user)
+            .await
+            .unwrap();
+
+        let runs: Vec<usize> = db
+            .query("SELECT VALUE array::len(results) FROM AnalysisRun WHERE patient = $patient")
+            .bind(("patient", report.patient.clone()))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(runs, vec![1]);
+
+        let classified: Vec<models::ImageClassification> = db
+            .query("SELECT VALUE classification FROM Image WHERE classification IS NOT NONE")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(classified.len(), 1);
+        assert_eq!(classified[0].image_type, "chest");
+    }
+
+    #[tokio::test]
+    async fn test_report_lifecycle() {
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let report = create_report_in_dir(&db, report_request(patient_id, vec![]), &user, dir.path())
+            .await
+            .unwrap();
+        assert_eq!(report.status, models::ReportStatus::Draft);
+        let report_id = report.id.id.to_raw();
+
+        let edit = models::ReportUpdateRequest {
+            report_text: "Edited findings".to_string(),
+            body_part: "thorax".to_string(),
+            sections: None,
+        };
+        let updated = update_report_service(&db, report_id.clone(), edit.clone(), &user)
+            .await
+            .unwrap();
+        assert_eq!(updated.report_text, "Edited findings");
+
+        release_preliminary_service(&db, report_id.clone(), &user).await.unwrap();
+        assert!(update_report_service(&db, report_id.clone(), edit.clone(), &user)
+            .await
+            .is_err());
+
+        let signed = sign_report_service(&db, report_id.clone(), &user).await.unwrap();
+        assert_eq!(signed.status, models::ReportStatus::Final);
+        assert_eq!(signed.signed_by, Some(user.id.clone()));
+        assert!(signed.signed_at.is_some());
+        assert!(sign_report_service(&db, report_id, &user).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_amendment_creates_linked_version() {
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let request = report_request(patient_id, vec![png_file("a.png")]);
+        let original = create_report_in_dir(&db, request, &user, dir.path()).await.unwrap();
+        let original_id = original.id.id.to_raw();
+        sign_report_service(&db, original_id.clone(), &user).await.unwrap();
+
+        let amendment_text = models::ReportUpdateRequest {
+            report_text: "Amended findings".to_string(),
+            body_part: "thorax".to_string(),
+            sections: None,
+        };
+        let amendment = amend_report_service(&db, original_id.clone(), amendment_text.clone(), &user)
+            .await
+            .unwrap();
+        assert_eq!(amendment.version, 2);
+        assert_eq!(amendment.amends, Some(original.id.clone()));
+        assert!(amend_report_service(&db, original_id.clone(), amendment_text, &user)
+            .await
+            .is_err());
+
+        let images = get_report_images_service(&db, amendment.id.id.to_raw(), &user)
+            .await
+            .unwrap();
+        assert_eq!(images.len(), 1);
+
+        sign_report_service(&db, amendment.id.id.to_raw(), &user).await.unwrap();
+        let original = load_report(&db, &original_id, &user).await.unwrap();
+        assert_eq!(original.status, models::ReportStatus::Amended);
+        assert_eq!(original.report_text, "Unremarkable findings");
+        assert_eq!(original.superseded_by, Some(amendment.id.clone()));
+
+        let reports = get_reports_service(&db, &user).await.unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].id, amendment.id);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_transitions_apply_once() {
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let report = create_report_in_dir(&db, report_request(patient_id, vec![]), &user, dir.path())
+            .await
+            .unwrap();
+        let report_id = report.id.id.to_raw();
+
+        let (first, second) = tokio::join!(
+            sign_report_service(&db, report_id.clone(), &user),
+            sign_report_service(&db, report_id.clone(), &user),
+        );
+        assert_eq!([first.is_ok(), second.is_ok()].iter().filter(|ok| **ok).count(), 1);
+
+        let amendment_text = models::ReportUpdateRequest {
+            report_text: "Amended findings".to_string(),
+            body_part: "thorax".to_string(),
+            sections: None,
+        };
+        let (first, second) = tokio::join!(
+            amend_report_service(&db, report_id.clone(), amendment_text.clone(), &user),
+            amend_report_service(&db, report_id.clone(), amendment_text, &user),
+        );
+        assert_eq!([first.is_ok(), second.is_ok()].iter().filter(|ok| **ok).count(), 1);
+
+        let amendments: Vec<Thing> = db
+            .query("SELECT VALUE id FROM Report WHERE amends = $report")
+            .bind(("report", report.id.clone()))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(amendments.len(), 1);
+    }
+
+    #[test]
+    fn test_word_diff() {
+        let segments = word_diff("No acute fracture seen", "No fracture of the rib seen");
+        let ops: Vec<(models::DiffOp, &str)> = segments
+            .iter()
+            .map(|segment| (segment.op, segment.text.as_str()))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                (models::DiffOp::Equal, "No"),
+                (models::DiffOp::Delete, "acute"),
+                (models::DiffOp::Equal, "fracture"),
+                (models::DiffOp::Insert, "of the rib"),
+                (models::DiffOp::Equal, "seen"),
+            ]
+        );
+        assert!(word_diff("", "").is_empty());
+
+        let old: Vec<String> = (0..3_000).map(|i| format!("w{}", i % 97)).collect();
+        let mut new = old.clone();
+        new.retain(|word| word != "w13");
+        new.insert(1_500, "added".to_string());
+        let segments = word_diff(&old.join(" "), &new.join(" "));
+        let side = |skip: models::DiffOp| {
+            segments
+                .iter()
+                .filter(|segment| segment.op != skip)
+                .map(|segment| segment.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        assert_eq!(side(models::DiffOp::Insert), old.join(" "));
+        assert_eq!(side(models::DiffOp::Delete), new.join(" "));
+        let inserted: Vec<&str> = segments
+            .iter()
+            .filter(|segment| segment.op == models::DiffOp::Insert)
+            .map(|segment| segment.text.as_str())
+            .collect();
+        assert_eq!(inserted, vec!["added"]);
+    }
+
+    #[tokio::test]
+    async fn test_report_history_and_diff() {
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let report = create_report_in_dir(&db, report_request(patient_id, vec![]), &user, dir.path())
+            .await
+            .unwrap();
+        let report_id = report.id.id.to_raw();
+        let edit = models::ReportUpdateRequest {
+            report_text: "Mildly remarkable findings".to_string(),
+            body_part: "thorax".to_string(),
+            sections: None,
+        };
+        update_report_service(&db, report_id.clone(), edit, &user).await.unwrap();
+        release_preliminary_service(&db, report_id.clone(), &user).await.unwrap();
+        sign_report_service(&db, report_id.clone(), &user).await.unwrap();
+
+        let amendment_text = models::ReportUpdateRequest {
+            report_text: "Mildly remarkable findings, follow-up advised".to_string(),
+            body_part: "thorax".to_string(),
+            sections: None,
+        };
+        let amendment = amend_report_service(&db, report_id.clone(), amendment_text, &user)
+            .await
+            .unwrap();
+
+        let history = get_report_history_service(&db, amendment.id.id.to_raw(), &user)
+            .await
+            .unwrap();
+        let statuses: Vec<models::ReportStatus> =
+            history.iter().map(|revision| revision.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                models::ReportStatus::Draft,
+                models::ReportStatus::Draft,
+                models::ReportStatus::Preliminary,
+                models::ReportStatus::Final,
+                models::ReportStatus::Draft,
+            ]
+        );
+        assert!(history.iter().all(|revision| revision.author.id == user.id));
+
+        let diff = diff_report_revisions_service(
+            &db,
+            amendment.id.id.to_raw(),
+            history[0].id.id.to_raw(),
+            history[4].id.id.to_raw(),
+            &user,
+        )
+        .await
+        .unwrap();
+        assert_eq!(diff.from.version, 1);
+        assert_eq!(diff.to.version, 2);
+        assert_eq!(
+            diff.segments,
+            vec![
+                models::DiffSegment { op: models::DiffOp::Delete, text: "Unremarkable findings".to_string() },
+                models::DiffSegment {
+                    op: models::DiffOp::Insert,
+                    text: "Mildly remarkable findings, follow-up advised".to_string(),
+                },
+            ]
+        );
+
+        let unrelated = diff_report_revisions_service(
+            &db,
+            report_id,
+            history[0].id.id.to_raw(),
+            history[4].id.id.to_raw(),
+            &user,
+        )
+        .await;
+        assert!(unrelated.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compose_structured_report() {
+        use crate::image_analysis::image_processing::models::{
+            AnalysisResponse, ImageResult, StatementResponse,
+        };
+
+        let db = setup_test_db().await;
+        let (user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let statement = StatementResponse {
+            indication: "Chest pain".to_string(),
+            statement: "No pneumothorax.".to_string(),
+            assessment: "Normal chest radiograph.".to_string(),
+        };
+        let request = models::ComposeReportRequest {
+            patient_id,
+            body_part: "thorax".to_string(),
+            indication: None,
+            analysis: AnalysisResponse {
+                results: vec![ImageResult {
+                    filename: "a.png".to_string(),
+                    image_type: "thorax".to_string(),
+                    confidence: 0.97,
+                }],
+                statements: vec![statement.clone()],
+            },
+            statements: vec![statement.clone(), statement],
+            files: vec![png_file("a.png")],
+        };
+
+        let report_request = compose_report_request(&db, request, &user).await.unwrap();
+        let sections = report_request.sections.clone().unwrap();
+        assert_eq!(sections.indication, "Chest pain");
+        assert_eq!(sections.findings, "No pneumothorax.");
+        assert_eq!(
+            sections.technique,
+            "Radiograph of the thorax, 1 image.\na.png: classified as thorax (97% confidence)."
+        );
+        assert!(report_request.report_text.starts_with("Patient: Test Patient, male, born "));
+        assert!(report_request.report_text.contains("ASSESSMENT\nNormal chest radiograph."));
+
+        let report = create_report_in_dir(&db, report_request, &user, dir.path()).await.unwrap();
+        assert_eq!(report.sections, Some(sections));
+        assert_eq!(report.status, models::ReportStatus::Draft);
+    }
+
+    #[tokio::test]
+    async fn test_export_signed_report_pdf() {
+        let db = setup_test_db().await;
+        let (mut user, patient_id) = setup_patient(&db).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let request = report_request(patient_id, vec![png_file("a.png")]);
+        let report = create_report_in_dir(&db, request, &user, dir.path()).await.unwrap();
+        let report_id = report.id.id.to_raw();
+        assert!(export_report_pdf_service(&db, report_id.clone(), &user).await.is_err());
+
+        let organization: Vec<Thing> = db
+            .query(
+                "CREATE Organization SET name = 'General Hospital', address = '1 Main St',
+                    email = 'info@hospital.test' RETURN VALUE id",
+            )
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        user.organization = Some(organization[0].clone());
+        let letterhead = models::Letterhead {
+            title: "General Hospital Radiology".to_string(),
+            lines: vec!["1 Main St".to_string()],
+            footer: Some("Confidential".to_string()),
+        };
+        assert!(update_letterhead_service(&db, letterhead.clone(), &user).await.is_err());
+        let admin = AuthenticatedUser {
+            role: crate::auth::session::models::ADMIN_ROLE.to_string(),
+            ..user.clone()
+        };
+        update_letterhead_service(&db, letterhead, &admin).await.unwrap();
+
+        sign_report_service(&db, report_id.clone(), &user).await.unwrap();
+        let pdf = export_report_pdf_service(&db, report_id.clone(), &user).await.unwrap();
+        assert_eq!(pdf.filename, format!("report-{}-v1.pdf", report_id));
+        assert!(pdf.data.starts_with(b"%PDF-"));
+
+        let text = String::from_utf8_lossy(&pdf.data);
+        assert!(text.contains("(General Hospital Radiology)"));
+        assert!(text.contains("(Confidential)"));
+        assert!(text.contains("Electronically signed by Test Doctor"));
+        assert!(text.contains("/Subtype /Image"));
+        assert!(text.contains("/Filter /DCTDecode"));
+        assert!(pdf.warnings.is_empty());
+
+        let images = get_report_images_service(&db, report_id.clone(), &user).await.unwrap();
+        fs::remove_file(&images[0].path).unwrap();
+        let pdf = export_report_pdf_service(&db, report_id, &user).await.unwrap();
+        assert_eq!(pdf.warnings.len(), 1);
+        assert!(!String::from_utf8_lossy(&pdf.data).contains("/Subtype /Image"));
+    }
+}
@@ -0,0 +1,41 @@
+use super::models;
+use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+
+/// Searches the notes and reports visible to the session user.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `request` - JSON string containing the query, record kinds and limit
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<SearchHit>)` - Hits ordered by relevance with highlighted snippets
+/// * `Err(AppError)` - Error message if the search fails
+
+#[tauri::command]
+pub async fn search(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    request: String,
+) -> Result<Vec<models::SearchHit>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let request: models::SearchRequest = serde_json::from_str(&request)
+            .map_err(|e| AppError::invalid_input("search request", e))?;
+
+        services::search_service(&db, request, &user)
+            .await
+    })
+    .await
+}
@@ -0,0 +1,25 @@
+//! # Search Module
+//! 
+//! This module provides full-text search over clinical text, including:
+//! - Patient note symptoms, diagnoses and treatments
+//! - Report texts
+//! 
+//! ## Components
+//! 
+//! - [`controller`]: Tauri command handlers for search
+//! - [`services`]: Ranked full-text queries and highlighting
+//! - [`models`]: Search request and hit structures
+//! 
+//! ## Main Features
+//! 
+//! - English and German stemming through separate search indexes
+//! - BM25 ranking across notes and reports
+//! - Highlighted snippets of the matching text
+//! - Results scoped to the records the user may access
+//! 
+//! A search index has a single analyzer, so every searchable field has a
+//! `_de` mirror in the schema that carries the German index.
+
+pub mod controller;
+pub mod models;
+pub mod services;
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use scanlytics_db::{Datetime, Thing};
+
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchHitKind {
+    Note,
+    Report,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SearchRequest {
+    pub query: String,
+    /// Kinds of records to search; all kinds when empty
+    #[serde(default)]
+    pub kinds: Vec<SearchHitKind>,
+    /// Maximum number of hits, 20 by default
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchPatient {
+    pub id: Thing,
+    pub name: String,
+}
+
+/// A note or report matching a search.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub id: Thing,
+    pub patient: SearchPatient,
+    /// Field the snippet was taken from
+    pub field: String,
+    /// Excerpt of the matching text with matches wrapped in `<mark>` tags
+    pub snippet: String,
+    pub score: f32,
+    pub created_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NoteMatch {
+    pub id: Thing,
+    pub patient: SearchPatient,
+    pub symptoms: String,
+    pub diagnosis: String,
+    pub treatment: String,
+    pub score: f32,
+    pub created_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportMatch {
+    pub id: Thing,
+    pub patient: SearchPatient,
+    pub report_text: String,
+    pub score: f32,
+    pub created_at: Datetime,
+}
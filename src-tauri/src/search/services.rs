@@ -0,0 +1,421 @@
+use super::models::{NoteMatch, ReportMatch, SearchHit, SearchHitKind, SearchRequest};
+use crate::audit::services::record_reads;
+use crate::auth::session::models::AuthenticatedUser;
+use crate::error::AppError;
+
+use scanlytics_db::{Surreal, Any};
+
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 100;
+/// Characters of context kept on each side of the first match in a snippet
+const SNIPPET_CONTEXT: usize = 80;
+
+
+/// Searches the notes and reports visible to the user.
+///
+/// Each text is matched against an English and a German index, so both
+/// languages' inflections are found. Hits of both kinds are ranked together
+/// by their BM25 score and carry a snippet of the matching text.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `request` - Query text, kinds of records and maximum number of hits
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<SearchHit>)` - Hits ordered by relevance
+/// * `Err(AppError)` - Error message if the search fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The query is empty
+/// * Database operations fail
+
+pub async fn search_service(
+    db: &Surreal<Any>,
+    request: SearchRequest,
+    user: &AuthenticatedUser,
+) -> Result<Vec<SearchHit>, AppError> {
+    let query = request.query.trim().to_string();
+    let terms = terms(&query);
+    if terms.is_empty() {
+        return Err(AppError::validation("query", "Search query is empty"));
+    }
+    let limit = request.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let includes = |kind| request.kinds.is_empty() || request.kinds.contains(&kind);
+
+    let mut hits = Vec::new();
+    if includes(SearchHitKind::Note) {
+        let notes = search_notes(db, &query, limit, user).await?;
+        hits.extend(notes.into_iter().map(|note| note_hit(note, &terms)));
+    }
+    if includes(SearchHitKind::Report) {
+        let reports = search_reports(db, &query, limit, user).await?;
+        hits.extend(reports.into_iter().map(|report| report_hit(report, &terms)));
+    }
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit);
+
+    let reads = hits
+        .iter()
+        .map(|hit| (hit.id.clone(), Some(hit.patient.id.clone())))
+        .collect();
+    record_reads(db, user, reads).await?;
+
+    Ok(hits)
+}
+
+async fn search_notes(
+    db: &Surreal<Any>,
+    query: &str,
+    limit: usize,
+    user: &AuthenticatedUser,
+) -> Result<Vec<NoteMatch>, AppError> {
+    let statement = "
+        SELECT
+            id,
+            { id: patient.id, name: patient.name } AS patient,
+            symptoms,
+            diagnosis,
+            treatment,
+            created_at,
+            math::max([
+                search::score(1) OR 0, search::score(2) OR 0, search::score(3) OR 0,
+                search::score(4) OR 0, search::score(5) OR 0, search::score(6) OR 0
+            ]) AS score
+        FROM PatientNote
+        WHERE (symptoms @1@ $query OR diagnosis @2@ $query OR treatment @3@ $query
+                OR symptoms_de @4@ $query OR diagnosis_de @5@ $query OR treatment_de @6@ $query)
+            AND deleted_at IS NONE
+            AND patient.deleted_at IS NONE
+            AND ($is_admin
+                OR user_owner = $user
+                OR $user INSIDE patient->Treated_By->User)
+        ORDER BY score DESC
+        LIMIT $limit;
+    ";
+    db.query(statement)
+        .bind(("query", query.to_string()))
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .bind(("limit", limit))
+        .await?
+        .take(0)
+        .map_err(AppError::from)
+}
+
+/// Searches current report versions; superseded versions are left out.
+
+async fn search_reports(
+    db: &Surreal<Any>,
+    query: &str,
+    limit: usize,
+    user: &AuthenticatedUser,
+) -> Result<Vec<ReportMatch>, AppError> {
+    let statement = "
+        SELECT
+            id,
+            { id: patient.id, name: patient.name } AS patient,
+            report_text,
+            created_at,
+            math::max([search::score(1) OR 0, search::score(2) OR 0]) AS score
+        FROM Report
+        WHERE (report_text @1@ $query OR report_text_de @2@ $query)
+            AND deleted_at IS NONE
+            AND superseded_by IS NONE
+            AND patient.deleted_at IS NONE
+            AND ($is_admin
+                OR user_owner = $user
+                OR $user INSIDE patient->Treated_By->User)
+        ORDER BY score DESC
+        LIMIT $limit;
+    ";
+    db.query(statement)
+        .bind(("query", query.to_string()))
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .bind(("limit", limit))
+        .await?
+        .take(0)
+        .map_err(AppError::from)
+}
+
+/// Uses the first note field with a highlighted match for the snippet.
+
+fn note_hit(note: NoteMatch, terms: &[String]) -> SearchHit {
+    let fields = [
+        ("symptoms", &note.symptoms),
+        ("diagnosis", &note.diagnosis),
+        ("treatment", &note.treatment),
+    ];
+    let (field, snippet) = fields
+        .iter()
+        .find_map(|(field, text)| highlight(text, terms).map(|snippet| (*field, snippet)))
+        .unwrap_or_else(|| ("diagnosis", escape(&excerpt(&note.diagnosis))));
+
+    SearchHit {
+        kind: SearchHitKind::Note,
+        id: note.id,
+        patient: note.patient,
+        field: field.to_string(),
+        snippet,
+        score: note.score,
+        created_at: note.created_at,
+    }
+}
+
+fn report_hit(report: ReportMatch, terms: &[String]) -> SearchHit {
+    let snippet = highlight(&report.report_text, terms)
+        .unwrap_or_else(|| escape(&excerpt(&report.report_text)));
+
+    SearchHit {
+        kind: SearchHitKind::Report,
+        id: report.id,
+        patient: report.patient,
+        field: "report_text".to_string(),
+        snippet,
+        score: report.score,
+        created_at: report.created_at,
+    }
+}
+
+/// Splits a query into lowercase words.
+
+pub fn terms(query: &str) -> Vec<String> {
+    words(query)
+        .into_iter()
+        .map(|(start, end)| query[start..end].to_lowercase())
+        .filter(|term| term.chars().count() >= 2)
+        .collect()
+}
+
+/// Returns an excerpt of `text` around its first word matching a term, with
+/// matching words wrapped in `<mark>` tags and the rest HTML-escaped.
+///
+/// A word matches a term when one is a prefix of the other or they share a
+/// stem-length prefix, so inflected forms found by the stemmed indexes are
+/// highlighted too. Returns `None` when no word matches.
+
+pub fn highlight(text: &str, terms: &[String]) -> Option<String> {
+    let matches: Vec<(usize, usize)> = words(text)
+        .into_iter()
+        .filter(|(start, end)| {
+            let word = text[*start..*end].to_lowercase();
+            terms.iter().any(|term| word_matches(&word, term))
+        })
+        .collect();
+    let (first_start, first_end) = *matches.first()?;
+
+    let mut start = text[..first_start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT)
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+    if start > 0 {
+        start = text[start..first_start]
+            .find(' ')
+            .map(|space| start + space + 1)
+            .unwrap_or(first_start);
+    }
+    let mut end = text[first_end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT * 2)
+        .map(|(index, _)| first_end + index)
+        .unwrap_or(text.len());
+    if end < text.len() {
+        end = text[first_end..end]
+            .rfind(' ')
+            .map(|space| first_end + space)
+            .unwrap_or(end);
+    }
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut position = start;
+    for (match_start, match_end) in matches {
+        if match_start < start || match_end > end {
+            continue;
+        }
+        snippet.push_str(&escape(&text[position..match_start]));
+        snippet.push_str("<mark>");
+        snippet.push_str(&escape(&text[match_start..match_end]));
+        snippet.push_str("</mark>");
+        position = match_end;
+    }
+    snippet.push_str(&escape(&text[position..end]));
+    if end < text.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+fn word_matches(word: &str, term: &str) -> bool {
+    let common = word
+        .chars()
+        .zip(term.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let word_length = word.chars().count();
+    let term_length = term.chars().count();
+
+    common == term_length
+        || (common == word_length && word_length >= 4)
+        || common >= 5
+}
+
+/// Byte ranges of the alphanumeric words in `text`.
+
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(word_start)) => {
+                words.push((word_start, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(word_start) = start {
+        words.push((word_start, text.len()));
+    }
+    words
+}
+
+fn excerpt(text: &str) -> String {
+    match text.char_indices().nth(SNIPPET_CONTEXT * 2) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{create_test_user, setup_test_db};
+    use scanlytics_db::Thing;
+
+    async fn create_records(db: &Surreal<Any>, user: &AuthenticatedUser) {
+        let mut created = db
+            .query(
+                "CREATE ONLY Patient SET name = 'Test Patient', date_of_birth = time::now(),
+                    gender = 'male', contact_number = '1234567890', address = 'Test Address'
+                    RETURN VALUE id",
+            )
+            .await
+            .unwrap();
+        let patient: Option<Thing> = created.take(0).unwrap();
+        let patient = patient.unwrap();
+
+        db.query(
+            "RELATE $patient -> Treated_By -> $user;
+            CREATE PatientNote SET patient = $patient, user_owner = $user, severity = 'high',
+                is_urgent = true, symptoms = 'Sudden chest pain and dyspnea',
+                diagnosis = 'Tension pneumothorax on the right', treatment = 'Chest tube';
+            CREATE PatientNote SET patient = $patient, user_owner = $user, severity = 'low',
+                is_urgent = false, symptoms = 'Sturz auf die Brust',
+                diagnosis = 'Mehrere Rippenfrakturen links', treatment = 'Analgesie';
+            CREATE Report SET patient = $patient, user_owner = $user, body_part = 'thorax',
+                report_text = 'FINDINGS: Small apical pneumothorax. No effusion.';",
+        )
+        .bind(("patient", patient))
+        .bind(("user", user.id.clone()))
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+    }
+
+    fn search_request(query: &str) -> SearchRequest {
+        SearchRequest {
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_highlight_marks_inflected_words() {
+        let terms = terms("Rippenfraktur");
+        assert_eq!(
+            highlight("Mehrere Rippenfrakturen links", &terms).unwrap(),
+            "Mehrere <mark>Rippenfrakturen</mark> links"
+        );
+        assert!(highlight("No fracture", &terms).is_none());
+    }
+
+    #[test]
+    fn test_highlight_trims_long_text_and_escapes() {
+        let text = format!("{} <b>pneumothorax</b> {}", "word ".repeat(50), "tail ".repeat(50));
+        let snippet = highlight(&text, &terms("pneumothorax")).unwrap();
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("&lt;b&gt;<mark>pneumothorax</mark>&lt;/b&gt;"));
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_notes_and_reports() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        create_records(&db, &doctor).await;
+
+        let hits = search_service(&db, search_request("Pneumothorax"), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().any(|hit| hit.kind == SearchHitKind::Report));
+        let note = hits.iter().find(|hit| hit.kind == SearchHitKind::Note).unwrap();
+        assert_eq!(note.field, "diagnosis");
+        assert!(note.snippet.contains("<mark>pneumothorax</mark>"));
+        assert!(hits.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+        let reports_only = SearchRequest {
+            kinds: vec![SearchHitKind::Report],
+            ..search_request("pneumothorax")
+        };
+        let hits = search_service(&db, reports_only, &doctor).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, SearchHitKind::Report);
+    }
+
+    #[tokio::test]
+    async fn test_search_uses_german_stemming() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        create_records(&db, &doctor).await;
+
+        let hits = search_service(&db, search_request("Rippenfraktur"), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].snippet.contains("<mark>Rippenfrakturen</mark>"));
+    }
+
+    #[tokio::test]
+    async fn test_search_is_scoped_to_user() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+        create_records(&db, &doctor).await;
+
+        let hits = search_service(&db, search_request("pneumothorax"), &other)
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+        assert!(search_service(&db, search_request("  "), &doctor).await.is_err());
+    }
+}
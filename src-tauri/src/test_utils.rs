@@ -0,0 +1,69 @@
+//! Helpers shared by the service tests.
+
+use crate::auth::session::models::AuthenticatedUser;
+use crate::notes::models::PatientNoteRequest;
+use crate::patients::models::{PatientRequest, UserResponse};
+use crate::patients::services::create_patient_service;
+
+use scanlytics_db::{Surreal, Any, Datetime, Thing};
+
+/// Opens a fresh in-memory database with the schema defined.
+pub(crate) async fn setup_test_db() -> Surreal<Any> {
+    let db_conn = scanlytics_db::init_db(None, true).await.unwrap();
+    scanlytics_db::define_db_on_startup(db_conn.clone()).await.unwrap();
+    let db = db_conn.get().lock().await;
+    db.clone()
+}
+
+/// Creates a user with the `user` role, optionally in an organization.
+pub(crate) async fn create_test_user(
+    db: &Surreal<Any>,
+    email: &str,
+    organization: Option<&str>,
+) -> AuthenticatedUser {
+    let created: Option<UserResponse> = db
+        .query("CREATE ONLY User SET name = 'Test Doctor', email = $email, role = 'user', organization = $organization")
+        .bind(("email", email.to_string()))
+        .bind(("organization", organization.map(|org| Thing::from(("Organization", org)))))
+        .await
+        .unwrap()
+        .take(0)
+        .unwrap();
+    let created = created.unwrap();
+
+    AuthenticatedUser {
+        id: created.id,
+        name: created.name,
+        email: created.email,
+        role: created.role,
+        organization: created.organization,
+    }
+}
+
+/// Creates a patient with `doctor` as primary doctor.
+pub(crate) async fn create_test_patient(db: &Surreal<Any>, doctor: &AuthenticatedUser) -> Thing {
+    let request = PatientRequest {
+        name: "Test Patient".to_string(),
+        date_of_birth: Datetime::default(),
+        gender: "male".to_string(),
+        contact_number: "1234567890".to_string(),
+        address: "Test Address".to_string(),
+        notes: None,
+        reports: None,
+        images: None,
+        primary_doctor: doctor.id.id.to_raw(),
+    };
+    create_patient_service(db, request, doctor).await.unwrap().id
+}
+
+/// Request for a low severity, non-urgent note on `patient`.
+pub(crate) fn note_request(patient: &Thing) -> PatientNoteRequest {
+    PatientNoteRequest {
+        patient_id: patient.id.to_raw(),
+        symptoms: "Test symptoms".to_string(),
+        diagnosis: "Test diagnosis".to_string(),
+        treatment: "Test treatment".to_string(),
+        severity: "low".to_string(),
+        is_urgent: false,
+    }
+}
@@ -0,0 +1,38 @@
+use super::models;
+use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+
+/// Retrieves the chronological timeline of a patient's case.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `patient_id` - Unique identifier of the patient
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<TimelineEntry>)` - Notes, reports, images, analysis runs and audit events from oldest to newest
+/// * `Err(AppError)` - Error message if the timeline can't be built
+
+#[tauri::command]
+pub async fn get_patient_timeline(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    patient_id: String,
+) -> Result<Vec<models::TimelineEntry>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::get_patient_timeline_service(&db, patient_id, &user)
+            .await
+    })
+    .await
+}
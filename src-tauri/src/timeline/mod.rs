@@ -0,0 +1,24 @@
+//! # Timeline Module
+//! 
+//! This module assembles a chronological view of a patient's case, including:
+//! - Patient notes
+//! - Reports and their revisions
+//! - Images and the analysis runs performed on them
+//! - Audit events on the patient's records
+//! 
+//! ## Components
+//! 
+//! - [`controller`]: Tauri command handlers for timelines
+//! - [`services`]: Collection and ordering of timeline entries
+//! - [`models`]: Typed timeline entries
+//! 
+//! ## Main Features
+//! 
+//! - Single chronologically ordered list of typed entries
+//! - Reports linked to their images through the image join table
+//! - Analysis runs grouped from the images classified together
+//! - Access checks against the patient's treating users
+
+pub mod controller;
+pub mod models;
+pub mod services;
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use scanlytics_db::{Datetime, Thing};
+
+use crate::audit::models::{AuditAction, UserInfo};
+use crate::reports::models::ReportStatus;
+
+
+/// A single event in a patient's timeline.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TimelineEntry {
+    pub timestamp: Datetime,
+    /// User who authored the record or performed the action
+    pub actor: Option<UserInfo>,
+    #[serde(flatten)]
+    pub detail: TimelineDetail,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimelineDetail {
+    Note {
+        id: Thing,
+        severity: String,
+        is_urgent: bool,
+        symptoms: String,
+        diagnosis: String,
+        treatment: String,
+    },
+    Report {
+        id: Thing,
+        status: ReportStatus,
+        version: u32,
+        body_part: Option<String>,
+        amends: Option<Thing>,
+        images: Vec<Thing>,
+    },
+    Image {
+        id: Thing,
+        name: String,
+        modal_type: String,
+        reports: Vec<Thing>,
+    },
+    Analysis {
+        id: Thing,
+        results: Vec<AnalysisResult>,
+    },
+    Audit {
+        id: Thing,
+        action: AuditAction,
+        target: Thing,
+    },
+}
+
+/// Classification of one image in an analysis run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnalysisResult {
+    pub image: Thing,
+    pub image_type: String,
+    pub confidence: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NoteRow {
+    pub id: Thing,
+    pub actor: Option<UserInfo>,
+    pub severity: String,
+    pub is_urgent: bool,
+    pub symptoms: String,
+    pub diagnosis: String,
+    pub treatment: String,
+    pub created_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportRow {
+    pub id: Thing,
+    pub actor: Option<UserInfo>,
+    pub status: ReportStatus,
+    pub version: u32,
+    pub body_part: Option<String>,
+    pub amends: Option<Thing>,
+    pub images: Vec<Thing>,
+    pub created_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImageRow {
+    pub id: Thing,
+    pub actor: Option<UserInfo>,
+    pub name: String,
+    pub modal_type: String,
+    pub reports: Vec<Thing>,
+    pub created_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnalysisRow {
+    pub id: Thing,
+    pub actor: Option<UserInfo>,
+    pub results: Vec<AnalysisResult>,
+    pub analyzed_at: Datetime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditRow {
+    pub id: Thing,
+    pub actor: Option<UserInfo>,
+    pub action: AuditAction,
+    pub target: Thing,
+    pub created_at: Datetime,
+}
@@ -0,0 +1,355 @@
+use super::models::{
+    AnalysisRow, AuditRow, ImageRow, NoteRow, ReportRow, TimelineDetail, TimelineEntry,
+};
+use crate::audit::services::record_reads;
+use crate::auth::session::models::AuthenticatedUser;
+use crate::patients::services::can_access_patient;
+use crate::error::AppError;
+
+use scanlytics_db::{Surreal, Any, Thing};
+
+
+/// Builds the chronological timeline of a patient's case.
+///
+/// Records are found through their `patient` field and through the
+/// patient's `notes`, `report` and `image` links. Reports and images are
+/// cross-referenced through the image join table, and each stored analysis
+/// run is one entry listing its classified images. Audit events exclude reads and follow the
+/// audit log's visibility: non-admin users only see their own actions.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `patient_id` - Unique identifier of the patient
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<TimelineEntry>)` - Entries ordered from oldest to newest
+/// * `Err(AppError)` - Error message if the timeline can't be built
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The patient doesn't exist or isn't visible to the user
+/// * Database operations fail
+
+pub async fn get_patient_timeline_service(
+    db: &Surreal<Any>,
+    patient_id: String,
+    user: &AuthenticatedUser,
+) -> Result<Vec<TimelineEntry>, AppError> {
+    let patient = Thing::from(("Patient", patient_id.as_str()));
+    if !can_access_patient(db, &patient, user).await? {
+        return Err(AppError::NotFound("Patient not found".to_string()));
+    }
+
+    let query = "
+        SELECT
+            id,
+            { id: user_owner.id, name: user_owner.name } AS actor,
+            severity,
+            is_urgent,
+            symptoms,
+            diagnosis,
+            treatment,
+            created_at
+        FROM PatientNote
+        WHERE (patient = $patient OR id INSIDE $patient.notes)
+            AND deleted_at IS NONE;
+        SELECT
+            id,
+            { id: user_owner.id, name: user_owner.name } AS actor,
+            status,
+            version,
+            body_part,
+            amends,
+            <-Images_Reports_Join<-Image AS images,
+            created_at
+        FROM Report
+        WHERE (patient = $patient OR id INSIDE $patient.report)
+            AND deleted_at IS NONE;
+        SELECT
+            id,
+            { id: user.id, name: user.name } AS actor,
+            name,
+            modal_type,
+            ->Images_Reports_Join->(Report WHERE deleted_at IS NONE) AS reports,
+            created_at
+        FROM Image
+        WHERE patient = $patient OR id INSIDE $patient.image;
+        SELECT
+            id,
+            { id: user.id, name: user.name } AS actor,
+            results,
+            analyzed_at
+        FROM AnalysisRun
+        WHERE patient = $patient;
+        SELECT
+            id,
+            IF actor IS NONE THEN NONE ELSE { id: actor.id, name: actor.name } END AS actor,
+            action,
+            target,
+            created_at
+        FROM AuditLog
+        WHERE patient = $patient
+            AND action != 'read'
+            AND ($is_admin OR actor = $user);
+    ";
+    let mut response = db
+        .query(query)
+        .bind(("patient", patient.clone()))
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?;
+    let notes: Vec<NoteRow> = response.take(0)?;
+    let reports: Vec<ReportRow> = response.take(1)?;
+    let images: Vec<ImageRow> = response.take(2)?;
+    let runs: Vec<AnalysisRow> = response.take(3)?;
+    let audit: Vec<AuditRow> = response.take(4)?;
+
+    let reads = notes
+        .iter()
+        .map(|note| note.id.clone())
+        .chain(reports.iter().map(|report| report.id.clone()))
+        .map(|target| (target, Some(patient.clone())))
+        .collect();
+    record_reads(db, user, reads).await?;
+
+    Ok(build_timeline(notes, reports, images, runs, audit))
+}
+
+/// Converts the fetched records into entries ordered by time.
+///
+/// Entries with the same timestamp keep the order notes, reports, images,
+/// analysis runs, audit events.
+
+fn build_timeline(
+    notes: Vec<NoteRow>,
+    reports: Vec<ReportRow>,
+    images: Vec<ImageRow>,
+    runs: Vec<AnalysisRow>,
+    audit: Vec<AuditRow>,
+) -> Vec<TimelineEntry> {
+    let mut entries = Vec::new();
+
+    for note in notes {
+        entries.push(TimelineEntry {
+            timestamp: note.created_at,
+            actor: note.actor,
+            detail: TimelineDetail::Note {
+                id: note.id,
+                severity: note.severity,
+                is_urgent: note.is_urgent,
+                symptoms: note.symptoms,
+                diagnosis: note.diagnosis,
+                treatment: note.treatment,
+            },
+        });
+    }
+
+    for report in reports {
+        entries.push(TimelineEntry {
+            timestamp: report.created_at,
+            actor: report.actor,
+            detail: TimelineDetail::Report {
+                id: report.id,
+                status: report.status,
+                version: report.version,
+                body_part: report.body_part,
+                amends: report.amends,
+                images: report.images,
+            },
+        });
+    }
+
+    for image in images {
+        entries.push(TimelineEntry {
+            timestamp: image.created_at,
+            actor: image.actor,
+            detail: TimelineDetail::Image {
+                id: image.id,
+                name: image.name,
+                modal_type: image.modal_type,
+                reports: image.reports,
+            },
+        });
+    }
+
+    for run in runs {
+        entries.push(TimelineEntry {
+            timestamp: run.analyzed_at,
+            actor: run.actor,
+            detail: TimelineDetail::Analysis {
+                id: run.id,
+                results: run.results,
+            },
+        });
+    }
+
+    for event in audit {
+        entries.push(TimelineEntry {
+            timestamp: event.created_at,
+            actor: event.actor,
+            detail: TimelineDetail::Audit {
+                id: event.id,
+                action: event.action,
+                target: event.target,
+            },
+        });
+    }
+
+    entries.sort_by_key(|entry| entry.timestamp.0);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::models::AuditAction;
+    use crate::notes::models::PatientNoteRequest;
+    use crate::notes::services::update_patient_note_service;
+    use crate::reports::models::ReportStatus;
+    use crate::test_utils::{create_test_user, setup_test_db};
+
+    /// Creates a patient with a report, two analysed images, a note and audit
+    /// events, each at a fixed point in time.
+    async fn create_case(db: &Surreal<Any>, user: &AuthenticatedUser) -> Thing {
+        let mut created = db
+            .query(
+                "CREATE ONLY Patient SET name = 'Test Patient', date_of_birth = time::now(),
+                    gender = 'male', contact_number = '1234567890', address = 'Test Address'
+                    RETURN VALUE id",
+            )
+            .await
+            .unwrap();
+        let patient: Option<Thing> = created.take(0).unwrap();
+        let patient = patient.unwrap();
+
+        db.query(
+            "RELATE $patient -> Treated_By -> $user;
+            CREATE Report:first SET patient = $patient, user_owner = $user, report_text = 'Findings',
+                created_at = d'2024-01-02T10:00:00Z';
+            CREATE Image:left SET name = 'left.png', path = '/tmp/left.png', modal_type = 'xray',
+                file_type = 'png', patient = $patient, user = $user,
+                created_at = d'2024-01-01T10:00:00Z';
+            CREATE Image:right SET name = 'right.png', path = '/tmp/right.png', modal_type = 'xray',
+                file_type = 'png', patient = $patient, user = $user,
+                created_at = d'2024-01-01T10:01:00Z';
+            RELATE Image:left -> Images_Reports_Join -> Report:first;
+            RELATE Image:right -> Images_Reports_Join -> Report:first;
+            CREATE AnalysisRun SET patient = $patient, user = $user, analyzed_at = d'2024-01-01T10:05:00Z',
+                results = [
+                    { image: Image:left, image_type: 'chest', confidence: 0.9 },
+                    { image: Image:right, image_type: 'knee', confidence: 0.7 }
+                ];
+            CREATE PatientNote:cough SET patient = $patient, user_owner = $user, severity = 'low',
+                is_urgent = false, symptoms = 'Cough', diagnosis = 'Bronchitis', treatment = 'Rest',
+                created_at = d'2024-01-02T12:00:00Z';
+            CREATE PatientNote SET patient = $patient, user_owner = $user, severity = 'low',
+                is_urgent = false, symptoms = 'Old', diagnosis = 'Old', treatment = 'Old',
+                deleted_at = time::now(), deleted_by = $user;
+            CREATE AuditLog SET actor = $user, action = 'update', target = Report:first,
+                patient = $patient, created_at = d'2024-01-03T10:00:00Z';
+            CREATE AuditLog SET actor = $user, action = 'read', target = Report:first,
+                patient = $patient;",
+        )
+        .bind(("patient", patient.clone()))
+        .bind(("user", user.id.clone()))
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+        patient
+    }
+
+    #[tokio::test]
+    async fn test_timeline_is_ordered_and_typed() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_case(&db, &doctor).await;
+
+        let timeline = get_patient_timeline_service(&db, patient.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+
+        let kinds: Vec<&str> = timeline
+            .iter()
+            .map(|entry| match &entry.detail {
+                TimelineDetail::Note { .. } => "note",
+                TimelineDetail::Report { .. } => "report",
+                TimelineDetail::Image { .. } => "image",
+                TimelineDetail::Analysis { .. } => "analysis",
+                TimelineDetail::Audit { .. } => "audit",
+            })
+            .collect();
+        assert_eq!(kinds, vec!["image", "image", "analysis", "report", "note", "audit"]);
+
+        match &timeline[2].detail {
+            TimelineDetail::Analysis { results, .. } => assert_eq!(results.len(), 2),
+            detail => panic!("Expected an analysis run, got {:?}", detail),
+        }
+        match &timeline[3].detail {
+            TimelineDetail::Report { status, images, .. } => {
+                assert_eq!(*status, ReportStatus::Draft);
+                assert_eq!(images.len(), 2);
+            }
+            detail => panic!("Expected a report, got {:?}", detail),
+        }
+        match &timeline[5].detail {
+            TimelineDetail::Audit { action, .. } => assert_eq!(*action, AuditAction::Update),
+            detail => panic!("Expected an audit event, got {:?}", detail),
+        }
+        assert_eq!(timeline[0].actor.as_ref().unwrap().id, doctor.id);
+    }
+
+    #[tokio::test]
+    async fn test_updating_a_note_keeps_its_position() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_case(&db, &doctor).await;
+        let note_position = |timeline: &[TimelineEntry]| {
+            timeline
+                .iter()
+                .position(|entry| matches!(entry.detail, TimelineDetail::Note { .. }))
+                .unwrap()
+        };
+
+        let before = get_patient_timeline_service(&db, patient.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        let request = PatientNoteRequest {
+            patient_id: patient.id.to_raw(),
+            symptoms: "Cough".to_string(),
+            diagnosis: "Pneumonia".to_string(),
+            treatment: "Antibiotics".to_string(),
+            severity: "medium".to_string(),
+            is_urgent: false,
+        };
+        update_patient_note_service(&db, "cough".to_string(), request, &doctor)
+            .await
+            .unwrap()
+            .unwrap();
+        let after = get_patient_timeline_service(&db, patient.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+
+        assert_eq!(note_position(&after), note_position(&before));
+        assert_eq!(
+            after[note_position(&after)].timestamp,
+            before[note_position(&before)].timestamp
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeline_requires_patient_access() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+        let patient = create_case(&db, &doctor).await;
+
+        let result = get_patient_timeline_service(&db, patient.id.to_raw(), &other).await;
+        assert_eq!(result.unwrap_err(), AppError::NotFound("Patient not found".to_string()));
+    }
+}
@@ -0,0 +1,120 @@
+use super::models;
+use super::services;
+use crate::auth::session::models::SessionState;
+use crate::auth::session::services::session_middleware;
+use crate::error::AppError;
+
+use scanlytics_db::DbConnection;
+use tauri::State;
+
+
+/// Retrieves the trashed records visible to the session user.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<TrashItem>)` - Trashed patients, notes and reports
+/// * `Err(AppError)` - Error message if retrieval fails
+
+#[tauri::command]
+pub async fn get_trash(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+) -> Result<Vec<models::TrashItem>, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        services::get_trash_service(&db, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Restores a patient, note or report from the trash.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `kind` - Kind of record: `patient`, `note` or `report`
+/// * `id` - Unique identifier of the record
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(TrashItem)` - The restored record
+/// * `Err(AppError)` - Error message if the restore fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The kind is unknown
+/// * The record isn't in the trash or isn't visible to the session user
+/// * Database operations fail
+
+#[tauri::command]
+pub async fn restore_from_trash(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    kind: String,
+    id: String,
+) -> Result<models::TrashItem, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        let db = db_connection.get().lock().await;
+        let kind: models::TrashKind = kind.parse().map_err(AppError::InvalidInput)?;
+
+        services::restore_service(&db, kind, id, &user)
+            .await
+    })
+    .await
+}
+
+
+/// Permanently removes trashed records older than the retention period.
+///
+/// # Arguments
+///
+/// * `session_state` - Managed session state
+/// * `db_connection` - Database connection state
+/// * `retention_days` - Minimum days in the trash, defaults to the regular retention period
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PurgeSummary)` - Number of removed records and files
+/// * `Err(AppError)` - Error message if the purge fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The session user is not an admin
+/// * Database operations fail
+///
+/// Only admins can call this command.
+
+#[tauri::command]
+pub async fn purge_trash(
+    session_state: State<'_, SessionState>,
+    db_connection: State<'_, DbConnection>,
+    retention_days: Option<i64>,
+) -> Result<models::PurgeSummary, AppError> {
+    let db_connection = db_connection.inner();
+    session_middleware(&session_state, db_connection, |user| async move {
+        if !user.is_admin() {
+            return Err(AppError::Forbidden("Only admins can purge the trash".to_string()));
+        }
+
+        let db = db_connection.get().lock().await;
+        let retention_days = retention_days.unwrap_or(models::TRASH_RETENTION_DAYS);
+        services::purge_trash_service(&db, retention_days, Some(&user))
+            .await
+    })
+    .await
+}
@@ -0,0 +1,22 @@
+//! # Trash Module
+//! 
+//! This module handles soft-deleted clinical records, including:
+//! - Listing patients, notes and reports moved to the trash
+//! - Restoring trashed records
+//! - Purging expired records together with their dependent data
+//! 
+//! ## Components
+//! 
+//! - [`controller`]: Tauri command handlers for trash operations
+//! - [`services`]: Restore and purge business logic
+//! - [`models`]: Trash-related data structures
+//! 
+//! ## Main Features
+//! 
+//! - Retention period before records are removed for good
+//! - Cascading purge of notes, reports, images on disk and graph edges
+//! - Background purge job
+
+pub mod controller;
+pub mod models;
+pub mod services;
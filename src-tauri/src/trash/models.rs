@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use scanlytics_db::{Thing, Datetime};
+use std::str::FromStr;
+
+/// Days a record stays in the trash before the purge job removes it.
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashKind {
+    Patient,
+    Note,
+    Report,
+}
+
+impl TrashKind {
+    pub fn table(self) -> &'static str {
+        match self {
+            TrashKind::Patient => "Patient",
+            TrashKind::Note => "PatientNote",
+            TrashKind::Report => "Report",
+        }
+    }
+}
+
+impl FromStr for TrashKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "patient" => Ok(TrashKind::Patient),
+            "note" => Ok(TrashKind::Note),
+            "report" => Ok(TrashKind::Report),
+            other => Err(format!("Unknown trash kind: {}", other)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashItem {
+    pub id: Thing,
+    pub kind: TrashKind,
+    pub label: String,
+    pub patient: Thing,
+    pub deleted_at: Datetime,
+    pub deleted_by: Option<Thing>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PurgeTarget {
+    pub id: Thing,
+    pub patient: Thing,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PurgeImage {
+    pub id: Thing,
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PurgeSet {
+    pub patients: Vec<Thing>,
+    pub notes: Vec<PurgeTarget>,
+    pub reports: Vec<PurgeTarget>,
+    pub images: Vec<PurgeImage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PurgeSummary {
+    pub patients: usize,
+    pub notes: usize,
+    pub reports: usize,
+    pub images: usize,
+    pub files: usize,
+}
@@ -0,0 +1,444 @@
+use super::models::{PurgeSet, PurgeSummary, TrashItem, TrashKind, TRASH_RETENTION_DAYS};
+use crate::audit::models::{AuditAction, FieldChange};
+use crate::audit::services::{audit_entries, audit_entry};
+use crate::auth::session::models::AuthenticatedUser;
+use crate::patients::services::restore_patient_service;
+use crate::error::AppError;
+
+use scanlytics_db::{Any, DbConnection, Surreal, Thing};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{Manager, Runtime};
+
+/// Interval between background purge runs.
+const PURGE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Retrieves the trashed records visible to the user, most recently deleted first.
+///
+/// Patients are visible to the users treating them; notes and reports to
+/// their owners. Admins see the whole trash.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `user` - Authenticated user
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(Vec<TrashItem>)` - Trashed patients, notes and reports
+/// * `Err(AppError)` - Error message if the query fails
+
+pub async fn get_trash_service(
+    db: &Surreal<Any>,
+    user: &AuthenticatedUser,
+) -> Result<Vec<TrashItem>, AppError> {
+    let mut items = Vec::new();
+    for kind in [TrashKind::Patient, TrashKind::Note, TrashKind::Report] {
+        items.extend(find_trashed(db, kind, None, user).await?);
+    }
+
+    items.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(items)
+}
+
+/// Restores a record from the trash.
+///
+/// Restoring a patient also restores the notes and reports that were
+/// archived together with them.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `kind` - Kind of record to restore
+/// * `id` - Unique identifier of the record
+/// * `user` - Authenticated user restoring the record
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(TrashItem)` - The restored record
+/// * `Err(AppError)` - Error message if the restore fails
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// * The record isn't in the trash or isn't visible to the user
+/// * Database operations fail
+
+pub async fn restore_service(
+    db: &Surreal<Any>,
+    kind: TrashKind,
+    id: String,
+    user: &AuthenticatedUser,
+) -> Result<TrashItem, AppError> {
+    let record = Thing::from((kind.table(), id.as_str()));
+    let item = find_trashed(db, kind, Some(record.clone()), user)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound("Record not found in trash".to_string()))?;
+
+    let changes = vec![FieldChange {
+        field: "deleted_at".to_string(),
+        before: serde_json::to_value(&item.deleted_at).ok(),
+        after: None,
+    }];
+    let audit = vec![audit_entry(user, AuditAction::Restore, &record, Some(&item.patient), Some(changes))];
+
+    if kind == TrashKind::Patient {
+        restore_patient_service(db, &record, audit).await?;
+    } else {
+        let restore = "
+            BEGIN TRANSACTION;
+            UPDATE $record SET deleted_at = NONE, deleted_by = NONE;
+            INSERT INTO AuditLog $audit;
+            COMMIT TRANSACTION;
+        ";
+        db.query(restore)
+            .bind(("record", record.clone()))
+            .bind(("audit", audit))
+            .await?
+            .check()?;
+    }
+
+    Ok(item)
+}
+
+/// Permanently removes trashed records older than the retention period.
+///
+/// Purging a patient also removes all of their notes, reports, images,
+/// analysis runs and external identifiers. Purging a report removes the images that are not
+/// attached to any other report. All graph edges and reference arrays pointing at purged records
+/// are cleaned up in a single transaction; image files are deleted from
+/// disk once the transaction has committed.
+///
+/// # Arguments
+///
+/// * `db` - Database connection
+/// * `retention_days` - Minimum days a record must have been in the trash
+/// * `user` - User who requested the purge, or `None` for the background job
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PurgeSummary)` - Number of removed records and files
+/// * `Err(AppError)` - Error message if the purge fails
+
+pub async fn purge_trash_service(
+    db: &Surreal<Any>,
+    retention_days: i64,
+    user: Option<&AuthenticatedUser>,
+) -> Result<PurgeSummary, AppError> {
+    if retention_days < 0 {
+        return Err(AppError::validation("retention_days", "Retention period cannot be negative"));
+    }
+
+    let query = "
+        LET $cutoff = time::now() - duration::from::days($days);
+        LET $patients = SELECT VALUE id FROM Patient
+            WHERE deleted_at IS NOT NONE AND deleted_at <= $cutoff;
+        LET $notes = SELECT VALUE id FROM PatientNote
+            WHERE (deleted_at IS NOT NONE AND deleted_at <= $cutoff) OR patient INSIDE $patients;
+        LET $reports = SELECT VALUE id FROM Report
+            WHERE (deleted_at IS NOT NONE AND deleted_at <= $cutoff) OR patient INSIDE $patients;
+        LET $shared = SELECT VALUE in FROM Images_Reports_Join WHERE out NOT INSIDE $reports;
+        LET $images = SELECT VALUE id FROM Image
+            WHERE patient INSIDE $patients
+                OR (id INSIDE (SELECT VALUE in FROM Images_Reports_Join WHERE out INSIDE $reports)
+                    AND id NOT INSIDE $shared);
+        RETURN {
+            patients: $patients,
+            notes: (SELECT id, patient FROM PatientNote WHERE id INSIDE $notes),
+            reports: (SELECT id, patient FROM Report WHERE id INSIDE $reports),
+            images: (SELECT id, path FROM Image WHERE id INSIDE $images)
+        };
+    ";
+    let purge_set: Option<PurgeSet> = db
+        .query(query)
+        .bind(("days", retention_days))
+        .await?
+        .take(6)?;
+    let purge_set = purge_set.unwrap_or_default();
+
+    let notes: Vec<Thing> = purge_set.notes.iter().map(|note| note.id.clone()).collect();
+    let reports: Vec<Thing> = purge_set.reports.iter().map(|report| report.id.clone()).collect();
+    let images: Vec<Thing> = purge_set.images.iter().map(|image| image.id.clone()).collect();
+
+    let mut summary = PurgeSummary {
+        patients: purge_set.patients.len(),
+        notes: notes.len(),
+        reports: reports.len(),
+        images: images.len(),
+        files: 0,
+    };
+    if summary == PurgeSummary::default() {
+        return Ok(summary);
+    }
+
+    let targets = purge_set
+        .patients
+        .iter()
+        .map(|patient| (patient.clone(), Some(patient.clone())))
+        .chain(
+            purge_set
+                .notes
+                .into_iter()
+                .chain(purge_set.reports)
+                .map(|target| (target.id, Some(target.patient))),
+        )
+        .collect();
+
+    let purge = "
+        BEGIN TRANSACTION;
+        DELETE Images_Reports_Join WHERE in INSIDE $images OR out INSIDE $reports;
+        DELETE Statements_Reports_Join WHERE out INSIDE $reports;
+        DELETE Write_Reports WHERE out INSIDE $reports;
+        DELETE ReportRevision WHERE report INSIDE $reports;
+        DELETE PatientNotes_Reports_Join WHERE in INSIDE $notes;
+        DELETE Treated_By WHERE in INSIDE $patients;
+        DELETE PatientIdentifier WHERE patient INSIDE $patients;
+        DELETE AnalysisRun WHERE patient INSIDE $patients;
+        UPDATE AnalysisRun SET results = results[WHERE image NOT INSIDE $images]
+            WHERE results.image CONTAINSANY $images;
+        DELETE AnalysisRun WHERE array::len(results) = 0;
+        UPDATE User SET notes = array::complement(notes, $notes) WHERE notes CONTAINSANY $notes;
+        UPDATE User SET Image = array::complement(Image, $images) WHERE Image CONTAINSANY $images;
+        UPDATE Patient SET notes = array::complement(notes, $notes) WHERE notes CONTAINSANY $notes;
+        UPDATE Patient SET report = array::complement(report, $reports) WHERE report CONTAINSANY $reports;
+        UPDATE Patient SET image = array::complement(image, $images) WHERE image CONTAINSANY $images;
+        DELETE Image WHERE id INSIDE $images;
+        DELETE PatientNote WHERE id INSIDE $notes;
+        DELETE Report WHERE id INSIDE $reports;
+        DELETE Patient WHERE id INSIDE $patients;
+        INSERT INTO AuditLog $audit;
+        COMMIT TRANSACTION;
+    ";
+    db.query(purge)
+        .bind(("patients", purge_set.patients))
+        .bind(("notes", notes))
+        .bind(("reports", reports))
+        .bind(("images", images))
+        .bind(("audit", audit_entries(user, AuditAction::Purge, targets)))
+        .await?
+        .check()?;
+
+    for image in &purge_set.images {
+        let path = Path::new(&image.path);
+        if image.path.is_empty() || !path.exists() {
+            continue;
+        }
+        if fs::remove_file(path).is_ok() {
+            summary.files += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Purges expired trash in the background once a day.
+///
+/// Purged records are audited without an actor. A failed run is retried at
+/// the next interval.
+///
+/// # Arguments
+///
+/// * `app_handle` - Tauri application handle managing the `DbConnection`
+
+pub fn spawn_purge_job<R: Runtime>(app_handle: tauri::AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(db_connection) = app_handle.try_state::<DbConnection>() else {
+                continue;
+            };
+
+            let db = db_connection.get().lock().await;
+            let _ = purge_trash_service(&db, TRASH_RETENTION_DAYS, None).await;
+        }
+    });
+}
+
+/// Lists trashed records of one kind visible to the user, optionally limited to one record.
+///
+/// Notes and reports archived together with a patient are listed through
+/// that patient only. Patients merged into another patient are not listed,
+/// so they can't be restored.
+
+async fn find_trashed(
+    db: &Surreal<Any>,
+    kind: TrashKind,
+    record: Option<Thing>,
+    user: &AuthenticatedUser,
+) -> Result<Vec<TrashItem>, AppError> {
+    let query = match kind {
+        TrashKind::Patient => "
+            SELECT id, 'patient' AS kind, name AS label, id AS patient, deleted_at, deleted_by
+            FROM Patient
+            WHERE deleted_at IS NOT NONE
+                AND merged_into IS NONE
+                AND (!$record OR id = $record)
+                AND ($is_admin OR $user INSIDE ->Treated_By->User);
+        ",
+        TrashKind::Note => "
+            SELECT id, 'note' AS kind, diagnosis AS label, patient, deleted_at, deleted_by
+            FROM PatientNote
+            WHERE deleted_at IS NOT NONE
+                AND deleted_with IS NONE
+                AND (!$record OR id = $record)
+                AND ($is_admin OR user_owner = $user);
+        ",
+        TrashKind::Report => "
+            SELECT id, 'report' AS kind, string::slice(report_text, 0, 80) AS label, patient, deleted_at, deleted_by
+            FROM Report
+            WHERE deleted_at IS NOT NONE
+                AND deleted_with IS NONE
+                AND (!$record OR id = $record)
+                AND ($is_admin OR user_owner = $user);
+        ",
+    };
+
+    db.query(query)
+        .bind(("record", record))
+        .bind(("user", user.id.clone()))
+        .bind(("is_admin", user.is_admin()))
+        .await?
+        .take(0)
+        .map_err(AppError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::services::{
+        create_patient_note_service, delete_patient_note_service, get_patient_notes_service,
+    };
+    use crate::patients::models::CascadePolicy;
+    use crate::patients::services::{delete_patient_service, get_patient_service};
+    use crate::test_utils::{create_test_patient, create_test_user, note_request, setup_test_db};
+
+    #[tokio::test]
+    async fn test_restore_note() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_test_patient(&db, &doctor).await;
+
+        let note = create_patient_note_service(&db, note_request(&patient), &doctor)
+            .await
+            .unwrap();
+        delete_patient_note_service(&db, note.id.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        assert!(get_patient_notes_service(&db, &doctor).await.unwrap().is_empty());
+
+        let trash = get_trash_service(&db, &doctor).await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].kind, TrashKind::Note);
+
+        restore_service(&db, TrashKind::Note, note.id.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(get_patient_notes_service(&db, &doctor).await.unwrap().len(), 1);
+        assert!(get_trash_service(&db, &doctor).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_restore_requires_visibility() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let other = create_test_user(&db, "other@test.com", None).await;
+        let patient = create_test_patient(&db, &doctor).await;
+
+        delete_patient_service(&db, patient.id.to_raw(), CascadePolicy::Archive, &doctor)
+            .await
+            .unwrap();
+
+        assert!(get_trash_service(&db, &other).await.unwrap().is_empty());
+        let result = restore_service(&db, TrashKind::Patient, patient.id.to_raw(), &other).await;
+        assert!(result.is_err());
+
+        restore_service(&db, TrashKind::Patient, patient.id.to_raw(), &doctor)
+            .await
+            .unwrap();
+        assert_eq!(get_patient_service(&db, &doctor).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_cascades_to_dependents() {
+        let db = setup_test_db().await;
+        let doctor = create_test_user(&db, "doctor@test.com", None).await;
+        let patient = create_test_patient(&db, &doctor).await;
+
+        create_patient_note_service(&db, note_request(&patient), &doctor)
+            .await
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let image_path = dir.path().join("scan.png");
+        fs::write(&image_path, b"image").unwrap();
+
+        db.query(
+            "
+            LET $image = CREATE ONLY Image SET name = 'scan', path = $path, patient = $patient,
+                user = $user, file_type = 'png', modal_type = 'xray';
+            LET $report = CREATE ONLY Report SET report_text = 'Findings', patient = $patient, user_owner = $user;
+            RELATE ($image.id) -> Images_Reports_Join -> ($report.id);
+            ",
+        )
+        .bind(("path", image_path.to_string_lossy().to_string()))
+        .bind(("patient", patient.clone()))
+        .bind(("user", doctor.id.clone()))
+        .await
+        .unwrap();
+
+        delete_patient_service(&db, patient.id.to_raw(), CascadePolicy::Archive, &doctor)
+            .await
+            .unwrap();
+
+        let kept = purge_trash_service(&db, TRASH_RETENTION_DAYS, None)
+            .await
+            .unwrap();
+        assert_eq!(kept, PurgeSummary::default());
+
+        let summary = purge_trash_service(&db, 0, Some(&doctor)).await.unwrap();
+        assert_eq!(
+            summary,
+            PurgeSummary {
+                patients: 1,
+                notes: 1,
+                reports: 1,
+                images: 1,
+                files: 1,
+            }
+        );
+        assert!(!image_path.exists());
+
+        let purged: Vec<Thing> = db
+            .query("SELECT VALUE target FROM AuditLog WHERE action = 'purge'")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        assert_eq!(purged.len(), 3);
+
+        let mut remaining = db
+            .query(
+                "
+                SELECT VALUE id FROM Patient;
+                SELECT VALUE id FROM PatientNote;
+                SELECT VALUE id FROM Report;
+                SELECT VALUE id FROM Image;
+                SELECT VALUE id FROM Treated_By;
+                SELECT VALUE id FROM Images_Reports_Join;
+                ",
+            )
+            .await
+            .unwrap();
+        for index in 0..6 {
+            let ids: Vec<Thing> = remaining.take(index).unwrap();
+            assert!(ids.is_empty(), "statement {} left records behind", index);
+        }
+    }
+}
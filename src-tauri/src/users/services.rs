@@ -1,4 +1,5 @@
 use super::models;
+use crate::error::AppError;
 use scanlytics_db::{Surreal, Any};
 
 
@@ -12,13 +13,12 @@ use scanlytics_db::{Surreal, Any};
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(Vec<UserResponse>)` - List of all users
-/// * `Err(String)` - Error message if the database operation fails
+/// * `Err(AppError)` - Error if the database operation fails
 
-pub async fn get_users_service(db: &Surreal<Any>) -> Result<Vec<models::UserResponse>, String> {
+pub async fn get_users_service(db: &Surreal<Any>) -> Result<Vec<models::UserResponse>, AppError> {
     let records: Vec<models::UserResponse> = db
         .select("User")
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
     Ok(records)
 }
 
@@ -33,20 +33,19 @@ pub async fn get_users_service(db: &Surreal<Any>) -> Result<Vec<models::UserResp
 ///
 /// Returns a `Result` containing either:
 /// * `Ok(UserResponse)` - Created user record
-/// * `Err(String)` - Error message if creation fails
+/// * `Err(AppError)` - Error if creation fails
 ///
 /// # Errors
 ///
 /// This function will return an error if:
 /// * The database operation fails
 /// * The user creation is unsuccessful
-pub async fn create_user_service(user_record: models::UserRecord, db: &Surreal<Any>) -> Result<models::UserResponse, String> {
+pub async fn create_user_service(user_record: models::UserRecord, db: &Surreal<Any>) -> Result<models::UserResponse, AppError> {
     let user: models::UserResponse = db
         .create("User")
         .content(user_record)
-        .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Failed to create patient".to_string())?;
+        .await?
+        .ok_or_else(|| AppError::OperationFailed("Failed to create user".to_string()))?;
 
     Ok(user)
 
@@ -0,0 +1,24 @@
+//! # Validation Module
+//! 
+//! This module validates and normalizes user input before it is stored,
+//! including:
+//! - Patient demographics
+//! - Patient notes
+//! 
+//! ## Components
+//! 
+//! - [`services`]: Request and field validators
+//! - [`models`]: Accepted enum values and field-level errors
+//! 
+//! ## Main Features
+//! 
+//! - Gender and severity values matching the schema
+//! - Phone number format checks
+//! - Date of birth range checks
+//! - Every problem reported at once, keyed by the offending field
+//! 
+//! Controllers validate requests after parsing them, and patient imports
+//! reuse the same field validators for each row.
+
+pub mod models;
+pub mod services;
@@ -0,0 +1,259 @@
+use super::models::{Gender, Severity, ValidationErrors};
+use crate::notes::models::PatientNoteRequest;
+use crate::patients::models::PatientRequest;
+
+use scanlytics_db::Datetime;
+
+
+const MAX_NAME_LENGTH: usize = 200;
+
+/// Digits allowed in a phone number; E.164 numbers have at most 15.
+const MIN_PHONE_DIGITS: usize = 5;
+const MAX_PHONE_DIGITS: usize = 15;
+
+/// Oldest accepted date of birth, in years before the current year.
+const MAX_AGE_YEARS: i32 = 150;
+
+
+/// Validates and normalizes a patient request.
+///
+/// Text fields are trimmed, the gender is lowercased and the contact number
+/// has its whitespace collapsed.
+///
+/// # Arguments
+///
+/// * `request` - Patient request as sent by the UI
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PatientRequest)` - The normalized request
+/// * `Err(ValidationErrors)` - Every invalid field with its message
+
+pub fn validate_patient_request(request: PatientRequest) -> Result<PatientRequest, ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+
+    let name = check_name(&request.name).unwrap_or_else(|e| {
+        errors.push("name", e);
+        String::new()
+    });
+    if let Err(e) = check_birth_date(&request.date_of_birth) {
+        errors.push("date_of_birth", e);
+    }
+    let gender = match check_gender(&request.gender) {
+        Ok(gender) => gender.to_string(),
+        Err(e) => {
+            errors.push("gender", e);
+            String::new()
+        }
+    };
+    let contact_number = check_contact_number(&request.contact_number).unwrap_or_else(|e| {
+        errors.push("contact_number", e);
+        String::new()
+    });
+
+    errors.into_result(PatientRequest {
+        name,
+        gender,
+        contact_number,
+        address: request.address.trim().to_string(),
+        ..request
+    })
+}
+
+/// Validates and normalizes a patient note request.
+///
+/// Text fields are trimmed and the severity is lowercased.
+///
+/// # Arguments
+///
+/// * `request` - Note request as sent by the UI
+///
+/// # Returns
+///
+/// Returns a `Result` containing either:
+/// * `Ok(PatientNoteRequest)` - The normalized request
+/// * `Err(ValidationErrors)` - Every invalid field with its message
+
+pub fn validate_note_request(
+    request: PatientNoteRequest,
+) -> Result<PatientNoteRequest, ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+
+    let patient_id = request.patient_id.trim().to_string();
+    if patient_id.is_empty() {
+        errors.push("patient_id", "patient is required");
+    }
+    let severity = match check_severity(&request.severity) {
+        Ok(severity) => severity.to_string(),
+        Err(e) => {
+            errors.push("severity", e);
+            String::new()
+        }
+    };
+
+    errors.into_result(PatientNoteRequest {
+        patient_id,
+        symptoms: request.symptoms.trim().to_string(),
+        diagnosis: request.diagnosis.trim().to_string(),
+        treatment: request.treatment.trim().to_string(),
+        severity,
+        is_urgent: request.is_urgent,
+    })
+}
+
+/// Trims a patient name and checks that it is present and not overly long.
+
+pub fn check_name(name: &str) -> Result<String, String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err("name is required".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("name must be at most {} characters", MAX_NAME_LENGTH));
+    }
+    Ok(name)
+}
+
+pub fn check_gender(gender: &str) -> Result<Gender, String> {
+    Gender::parse(gender).ok_or_else(|| {
+        let accepted: Vec<&str> = Gender::ALL.iter().map(|gender| gender.as_str()).collect();
+        format!("gender must be one of {}", accepted.join(", "))
+    })
+}
+
+pub fn check_severity(severity: &str) -> Result<Severity, String> {
+    Severity::parse(severity).ok_or_else(|| {
+        let accepted: Vec<&str> = Severity::ALL.iter().map(|severity| severity.as_str()).collect();
+        format!("severity must be one of {}", accepted.join(", "))
+    })
+}
+
+/// Checks the format of an optional phone number.
+///
+/// An empty number is accepted. Otherwise the number may start with `+` and
+/// contain digits separated by spaces, `-`, `.`, `/` or parentheses.
+
+pub fn check_contact_number(number: &str) -> Result<String, String> {
+    let number = number.split_whitespace().collect::<Vec<_>>().join(" ");
+    if number.is_empty() {
+        return Ok(number);
+    }
+
+    let well_formed = number
+        .strip_prefix('+')
+        .unwrap_or(&number)
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '/' | '(' | ')'));
+    let digits = number.chars().filter(char::is_ascii_digit).count();
+    if !well_formed || !(MIN_PHONE_DIGITS..=MAX_PHONE_DIGITS).contains(&digits) {
+        return Err(format!(
+            "contact_number must be a phone number with {} to {} digits",
+            MIN_PHONE_DIGITS, MAX_PHONE_DIGITS
+        ));
+    }
+    Ok(number)
+}
+
+/// Checks that a date of birth is neither in the future nor implausibly old.
+
+pub fn check_birth_date(date_of_birth: &Datetime) -> Result<(), String> {
+    let now = Datetime::default();
+    if date_of_birth.0 > now.0 {
+        return Err("date_of_birth is in the future".to_string());
+    }
+
+    let year: i32 = now.0.format("%Y").to_string().parse().unwrap_or_default();
+    let earliest = year - MAX_AGE_YEARS;
+    let born: i32 = date_of_birth.0.format("%Y").to_string().parse().unwrap_or_default();
+    if born < earliest {
+        return Err(format!("date_of_birth must not be before {}", earliest));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patient_request(name: &str, gender: &str, contact_number: &str, date: &str) -> PatientRequest {
+        PatientRequest {
+            name: name.to_string(),
+            date_of_birth: Datetime::try_from(date).unwrap(),
+            gender: gender.to_string(),
+            contact_number: contact_number.to_string(),
+            address: " 1 Main St ".to_string(),
+            notes: None,
+            reports: None,
+            images: None,
+            primary_doctor: "doctor".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_patient_request_is_normalized() {
+        let request = patient_request(
+            "  Jane   Doe ",
+            "Female",
+            " +49 (170)  123-4567 ",
+            "1980-02-29T00:00:00Z",
+        );
+        let request = validate_patient_request(request).unwrap();
+
+        assert_eq!(request.name, "Jane Doe");
+        assert_eq!(request.gender, "female");
+        assert_eq!(request.contact_number, "+49 (170) 123-4567");
+        assert_eq!(request.address, "1 Main St");
+    }
+
+    #[test]
+    fn test_patient_request_reports_every_field() {
+        let request = patient_request(" ", "unknown", "call me", "2999-01-01T00:00:00Z");
+        let errors = validate_patient_request(request).unwrap_err();
+
+        let fields: Vec<&str> = errors.errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, vec!["name", "date_of_birth", "gender", "contact_number"]);
+
+        assert_eq!(errors.errors[2].message, "gender must be one of male, female, other");
+    }
+
+    #[test]
+    fn test_contact_number_format() {
+        assert_eq!(check_contact_number("").unwrap(), "");
+        assert!(check_contact_number("0170/1234567").is_ok());
+        assert!(check_contact_number("555-0100").is_ok());
+        assert!(check_contact_number("1234").is_err());
+        assert!(check_contact_number("12345678901234567").is_err());
+        assert!(check_contact_number("555+0100").is_err());
+    }
+
+    #[test]
+    fn test_birth_date_range() {
+        assert!(check_birth_date(&Datetime::try_from("1900-01-01T00:00:00Z").unwrap()).is_ok());
+        assert!(check_birth_date(&Datetime::try_from("1800-01-01T00:00:00Z").unwrap()).is_err());
+        assert!(check_birth_date(&Datetime::try_from("2999-01-01T00:00:00Z").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_note_request_severity() {
+        let request = PatientNoteRequest {
+            patient_id: " patient ".to_string(),
+            symptoms: " Cough ".to_string(),
+            diagnosis: "Bronchitis".to_string(),
+            treatment: "Rest".to_string(),
+            severity: "High".to_string(),
+            is_urgent: false,
+        };
+        let request = validate_note_request(request).unwrap();
+        assert_eq!(request.severity, "high");
+        assert_eq!(request.patient_id, "patient");
+        assert_eq!(request.symptoms, "Cough");
+
+        let errors = validate_note_request(PatientNoteRequest {
+            severity: "Severe".to_string(),
+            ..request
+        })
+        .unwrap_err();
+        assert_eq!(errors.errors[0].field, "severity");
+    }
+}
@@ -0,0 +1,25 @@
+export type ErrorCode =
+  | "not_authenticated"
+  | "session_expired"
+  | "forbidden"
+  | "not_found"
+  | "invalid_input"
+  | "validation"
+  | "network"
+  | "keyring"
+  | "database"
+  | "model"
+  | "operation_failed";
+
+export interface FieldError {
+  field: string;
+  message: string;
+}
+
+/** Error returned by every Tauri command. */
+export interface AppError {
+  code: ErrorCode;
+  message: string;
+  fields: FieldError[];
+  retryable: boolean;
+}
@@ -59,4 +59,14 @@ export const flyAndScale = (
 		},
 		easing: cubicOut
 	};
-};
\ No newline at end of file
+};
+/** Reads the message of an error thrown by `invoke` or by frontend code. */
+export function errorMessage(error: unknown): string {
+	if (error instanceof Error) {
+		return error.message;
+	}
+	if (typeof error === "object" && error !== null && "message" in error) {
+		return String((error as { message: unknown }).message);
+	}
+	return String(error);
+}
@@ -1,5 +1,6 @@
 import { writable } from 'svelte/store';
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 interface Auth {
     user_email: string;
@@ -64,4 +65,8 @@ const AuthService = {
     }
 };
 
+listen<string>("session-expired", () => {
+    AuthService.logout();
+});
+
 export default AuthService;